@@ -115,15 +115,23 @@ impl From<&CenoStdin> for Vec<u32> {
 }
 
 impl CenoStdin {
-    pub fn write_slice(&mut self, bytes: AlignedVec) {
-        self.items.push(bytes);
+    /// Add raw bytes as a hint, to be read by `ceno_rt::read_slice` in the guest.
+    ///
+    /// The bytes are padded to a multiple of 4 and followed by their length as a `u32`,
+    /// so that the guest can locate them from the end of the hint.
+    pub fn write_slice(&mut self, bytes: &[u8]) {
+        let mut item = AlignedVec::new();
+        item.extend_from_slice(bytes);
+        item.resize(bytes.len().next_multiple_of(size_of::<u32>()), 0);
+        item.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        self.items.push(item);
     }
 
     pub fn write(
         &mut self,
         item: &impl for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
     ) -> Result<(), Error> {
-        to_bytes::<Error>(item).map(|bytes| self.write_slice(bytes))
+        to_bytes::<Error>(item).map(|bytes| self.items.push(bytes))
     }
 }
 
@@ -88,6 +88,28 @@ fn test_ceno_rt_alloc() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ceno_rt_heap_reset() -> Result<()> {
+    let all_messages = ceno_host::run(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_heap_reset,
+        &CenoStdin::default(),
+    );
+    assert_eq!(all_messages[0], (0..1000).sum::<u32>().to_string());
+    assert_eq!(all_messages[2], "4000");
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Trap IllegalInstruction")]
+fn test_ceno_rt_oom() {
+    let _ = ceno_host::run(
+        CENO_PLATFORM,
+        ceno_examples::ceno_rt_oom,
+        &CenoStdin::default(),
+    );
+}
+
 #[test]
 fn test_ceno_rt_io() -> Result<()> {
     let program_elf = ceno_examples::ceno_rt_io;
@@ -124,6 +146,26 @@ fn test_hints() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_read_slice() -> Result<()> {
+    let mut hints = CenoStdin::default();
+    hints.write_slice(b"hello");
+    // an unaligned length right after another slice
+    let second = (0..=254).collect::<Vec<u8>>();
+    hints.write_slice(&second);
+
+    let all_messages = ceno_host::run(CENO_PLATFORM, ceno_examples::ceno_rt_slice, &hints);
+    for (i, msg) in enumerate(&all_messages) {
+        println!("{i}: {msg}");
+    }
+    assert_eq!(all_messages[0], "255");
+    assert_eq!(
+        all_messages[2],
+        second.iter().map(|&b| b as u32).sum::<u32>().to_string()
+    );
+    Ok(())
+}
+
 #[test]
 fn test_bubble_sorting() -> Result<()> {
     use rand::Rng;
@@ -1,5 +1,7 @@
 
 _stack_start = ORIGIN(REGION_STACK) + LENGTH(REGION_STACK);
+/* The heap must not grow into the stack, matching the stack range of the emulator's platform. */
+_eheap = _stack_start - 256M;
 _hints_start = ORIGIN(REGION_HINTS);
 _hints_length = LENGTH(REGION_HINTS);
 _lengths_of_hints_start = ORIGIN(REGION_HINTS);
@@ -19,8 +19,11 @@ unsafe impl GlobalAlloc for SimpleAllocator {
         heap_pos = heap_pos.add(heap_pos.align_offset(align));
 
         let ptr = heap_pos;
-        // We don't want to wrap around, and overwrite stack etc.
-        // (We could also return a null pointer, but only malicious programs would ever hit this.)
+        // We don't want to wrap around, and overwrite the stack etc.
+        // Returning a null pointer makes the program abort via `handle_alloc_error`.
+        if (&raw const _eheap as usize).saturating_sub(heap_pos as usize) < layout.size() {
+            return core::ptr::null_mut();
+        }
         heap_pos = heap_pos.add(layout.size());
 
         HEAP.next_alloc = heap_pos;
@@ -28,7 +31,12 @@ unsafe impl GlobalAlloc for SimpleAllocator {
     }
 
     unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
-        self.alloc(layout)
+        let ptr = self.alloc(layout);
+        // Fresh heap is zero, but memory handed out again after `heap_reset` is not.
+        if !ptr.is_null() {
+            ptr.write_bytes(0, layout.size());
+        }
+        ptr
     }
 
     /// Never deallocate.
@@ -40,9 +48,23 @@ extern "C" {
     ///
     /// It is defined in the linker script.
     static mut _sheap: u8;
+    /// The address of this variable is the end of the heap, below the stack region.
+    ///
+    /// It is defined in the linker script.
+    static _eheap: u8;
 }
 
 #[global_allocator]
 static mut HEAP: SimpleAllocator = SimpleAllocator {
     next_alloc: &raw mut _sheap,
 };
+
+/// Reclaim the whole heap, so that the next allocation starts at its beginning again.
+///
+/// # Safety
+///
+/// Everything allocated before is freed: make sure no reference to it is used afterwards,
+/// e.g. by dropping or leaking all heap-backed values first.
+pub unsafe fn heap_reset() {
+    HEAP.next_alloc = &raw mut _sheap;
+}
@@ -5,8 +5,10 @@
 #[cfg(target_arch = "riscv32")]
 use core::arch::{asm, global_asm};
 
-#[cfg(target_arch = "riscv32")]
+#[cfg(all(target_arch = "riscv32", feature = "alloc"))]
 mod allocator;
+#[cfg(all(target_arch = "riscv32", feature = "alloc"))]
+pub use allocator::heap_reset;
 
 mod mmio;
 pub use mmio::{read, read_slice};
@@ -41,13 +41,28 @@ fn hint_len() -> usize {
     }
 }
 
-pub fn read_slice<'a>() -> &'a [u8] {
+/// The hints region up to the end of the next hint.
+///
+/// Hints don't record their start; rkyv archives and raw slices are both located from their end.
+fn read_hint<'a>() -> &'a [u8] {
     &hints_region()[..hint_len()]
 }
 
+/// Read the next hint as raw bytes, as written by the host with `CenoStdin::write_slice`.
+///
+/// The hint ends with a `u32` little-endian byte length, preceded by the bytes themselves
+/// padded to a multiple of 4.  Hints start 4-byte aligned, so the returned slice is too.
+pub fn read_slice<'a>() -> &'a [u8] {
+    let hint = read_hint();
+    let (data, len) = hint.split_at(hint.len() - size_of::<u32>());
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    let start = data.len() - len.next_multiple_of(size_of::<u32>());
+    &data[start..][..len]
+}
+
 pub fn read<'a, T>() -> &'a T
 where
     T: Portable + for<'c> CheckBytes<HighValidator<'c, Failure>>,
 {
-    rkyv::access::<T, Failure>(read_slice()).expect("Deserialised access failed.")
+    rkyv::access::<T, Failure>(read_hint()).expect("Deserialised access failed.")
 }
@@ -103,6 +103,7 @@ fn bench_add(c: &mut Criterion) {
                                 &circuit_pk,
                                 wits_in.into_iter().map(|mle| mle.into()).collect_vec(),
                                 commit,
+                                None,
                                 &[],
                                 num_instances,
                                 &mut transcript,
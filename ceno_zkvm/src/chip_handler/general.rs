@@ -90,7 +90,33 @@ impl<'a, E: ExtensionField> CircuitBuilder<'a, E> {
         N: FnOnce() -> NR,
     {
         self.cs
-            .lk_table_record(name_fn, table_len, rom_type, record, multiplicity)
+            .lk_table_record(name_fn, table_len, rom_type, record, multiplicity, None)
+    }
+
+    /// same as [`Self::lk_table_record`], while declaring that every multiplicity fits in
+    /// `multiplicity_bits` bits. the verifier then rejects any multiplicity out of range,
+    /// which would otherwise wrap around the field to balance the logup sum
+    pub fn lk_table_record_with_multiplicity_bits<NR, N>(
+        &mut self,
+        name_fn: N,
+        table_len: usize,
+        rom_type: ROMType,
+        record: Vec<Expression<E>>,
+        multiplicity: Expression<E>,
+        multiplicity_bits: usize,
+    ) -> Result<(), ZKVMError>
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.lk_table_record(
+            name_fn,
+            table_len,
+            rom_type,
+            record,
+            multiplicity,
+            Some(multiplicity_bits),
+        )
     }
 
     pub fn r_table_record<NR, N>(
@@ -1,15 +1,17 @@
 use ceno_emul::Addr;
 use itertools::{Itertools, chain};
-use std::{collections::HashMap, iter::once, marker::PhantomData};
+use std::{collections::HashMap, fmt::Write, iter::once, marker::PhantomData};
 
 use ff_ext::ExtensionField;
 use mpcs::PolynomialCommitmentScheme;
+use transcript::{BasicTranscript, Transcript};
 
 use crate::{
     ROMType,
     chip_handler::utils::rlc_chip_record,
     error::ZKVMError,
-    expression::{Expression, Fixed, Instance, WitIn},
+    expression::{Expression, Fixed, Instance, WitIn, fmt},
+    scheme::{constants::SEL_DEGREE, utils::eval_by_expr},
     structs::{ProgramParams, ProvingKey, RAMType, VerifyingKey, WitnessId},
     witness::RowMajorMatrix,
 };
@@ -52,11 +54,13 @@ impl NameSpace {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
 pub struct LogupTableExpression<E: ExtensionField> {
     pub multiplicity: Expression<E>,
     pub values: Expression<E>,
     pub table_len: usize,
+    /// declared bit width of every multiplicity, range checked by the verifier if set
+    pub multiplicity_bits: Option<usize>,
 }
 
 // TODO encapsulate few information of table spec to SetTableAddrType value
@@ -88,6 +92,9 @@ pub struct SetTableExpression<E: ExtensionField> {
     pub table_spec: SetTableSpec,
 }
 
+/// default cap on constraint degree, generous enough for every built-in circuit
+pub const DEFAULT_MAX_DEGREE: usize = 8;
+
 #[derive(Clone, Debug)]
 pub struct ConstraintSystem<E: ExtensionField> {
     pub(crate) ns: NameSpace,
@@ -135,6 +142,8 @@ pub struct ConstraintSystem<E: ExtensionField> {
 
     /// max zero sumcheck degree
     pub max_non_lc_degree: usize,
+    /// cap on the degree of any registered constraint or record expression
+    pub max_degree: usize,
 
     // alpha, beta challenge for chip record
     pub chip_record_alpha: Expression<E>,
@@ -175,6 +184,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
             assert_zero_sumcheck_expressions: vec![],
             assert_zero_sumcheck_expressions_namespace_map: vec![],
             max_non_lc_degree: 0,
+            max_degree: DEFAULT_MAX_DEGREE,
             chip_record_alpha: Expression::Challenge(0, 1, E::ONE, E::ZERO),
             chip_record_beta: Expression::Challenge(1, 1, E::ONE, E::ZERO),
 
@@ -268,6 +278,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
                 .chain(record.clone())
                 .collect(),
         );
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
@@ -290,6 +301,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         rom_type: ROMType,
         record: Vec<Expression<E>>,
         multiplicity: Expression<E>,
+        multiplicity_bits: Option<usize>,
     ) -> Result<(), ZKVMError>
     where
         NR: Into<String>,
@@ -301,16 +313,19 @@ impl<E: ExtensionField> ConstraintSystem<E> {
                 .chain(record.clone())
                 .collect_vec(),
         );
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
             "rlc lk_table_record degree ({})",
             name_fn().into()
         );
+        self.check_degree(&multiplicity)?;
         self.lk_table_expressions.push(LogupTableExpression {
             values: rlc_record,
             multiplicity,
             table_len,
+            multiplicity_bits,
         });
         let path = self.ns.compute_path(name_fn().into());
         self.lk_table_expressions_namespace_map.push(path);
@@ -333,6 +348,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         N: FnOnce() -> NR,
     {
         let rlc_record = self.rlc_chip_record(record.clone());
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
@@ -362,6 +378,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         N: FnOnce() -> NR,
     {
         let rlc_record = self.rlc_chip_record(record.clone());
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
@@ -386,6 +403,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         record: Vec<Expression<E>>,
     ) -> Result<(), ZKVMError> {
         let rlc_record = self.rlc_chip_record(record.clone());
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
@@ -408,6 +426,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         record: Vec<Expression<E>>,
     ) -> Result<(), ZKVMError> {
         let rlc_record = self.rlc_chip_record(record.clone());
+        self.check_degree(&rlc_record)?;
         assert_eq!(
             rlc_record.degree(),
             1,
@@ -430,6 +449,7 @@ impl<E: ExtensionField> ConstraintSystem<E> {
             assert_zero_expr.degree() > 0,
             "constant expression assert to zero ?"
         );
+        self.check_degree(&assert_zero_expr)?;
         if assert_zero_expr.degree() == 1 {
             self.assert_zero_expressions.push(assert_zero_expr);
             let path = self.ns.compute_path(name_fn().into());
@@ -451,6 +471,127 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         Ok(())
     }
 
+    /// append all witnesses and constraints of `other` to this constraint system.
+    /// `other`'s witness ids are shifted past ours, while fixed columns and the chip record
+    /// challenges are shared and thus must agree
+    pub fn merge(&mut self, other: ConstraintSystem<E>) -> Result<(), ZKVMError> {
+        if self.chip_record_alpha != other.chip_record_alpha
+            || self.chip_record_beta != other.chip_record_beta
+        {
+            return Err(ZKVMError::ConstraintSystemMismatch(
+                "chip record alpha/beta differ".into(),
+            ));
+        }
+        if self.num_fixed == 0 {
+            self.num_fixed = other.num_fixed;
+            self.fixed_namespace_map = other.fixed_namespace_map;
+        } else if other.num_fixed != 0 && self.fixed_namespace_map != other.fixed_namespace_map {
+            return Err(ZKVMError::ConstraintSystemMismatch(format!(
+                "fixed columns differ: {:?} != {:?}",
+                self.fixed_namespace_map, other.fixed_namespace_map
+            )));
+        }
+
+        let offset = self.num_witin;
+        let remap = |expr: &Expression<E>| expr.remap_witnesses(&|id| id + offset);
+        let remap_all = |exprs: Vec<Expression<E>>| exprs.iter().map(remap).collect_vec();
+        let remap_ram_types = |ram_types: Vec<(RAMType, Vec<Expression<E>>)>| {
+            ram_types
+                .into_iter()
+                .map(|(ram_type, record)| (ram_type, remap_all(record)))
+                .collect_vec()
+        };
+
+        self.num_witin = self.num_witin.strict_add(other.num_witin);
+        self.witin_namespace_map.extend(other.witin_namespace_map);
+        self.instance_name_map.extend(other.instance_name_map);
+
+        self.r_expressions.extend(remap_all(other.r_expressions));
+        self.r_expressions_namespace_map
+            .extend(other.r_expressions_namespace_map);
+        self.r_ram_types.extend(remap_ram_types(other.r_ram_types));
+        self.w_expressions.extend(remap_all(other.w_expressions));
+        self.w_expressions_namespace_map
+            .extend(other.w_expressions_namespace_map);
+        self.w_ram_types.extend(remap_ram_types(other.w_ram_types));
+
+        self.r_table_expressions
+            .extend(
+                other
+                    .r_table_expressions
+                    .into_iter()
+                    .map(|e| SetTableExpression {
+                        expr: remap(&e.expr),
+                        table_spec: e.table_spec,
+                    }),
+            );
+        self.r_table_expressions_namespace_map
+            .extend(other.r_table_expressions_namespace_map);
+        self.w_table_expressions
+            .extend(
+                other
+                    .w_table_expressions
+                    .into_iter()
+                    .map(|e| SetTableExpression {
+                        expr: remap(&e.expr),
+                        table_spec: e.table_spec,
+                    }),
+            );
+        self.w_table_expressions_namespace_map
+            .extend(other.w_table_expressions_namespace_map);
+
+        self.lk_expressions.extend(remap_all(other.lk_expressions));
+        self.lk_expressions_namespace_map
+            .extend(other.lk_expressions_namespace_map);
+        self.lk_table_expressions
+            .extend(
+                other
+                    .lk_table_expressions
+                    .into_iter()
+                    .map(|e| LogupTableExpression {
+                        multiplicity: remap(&e.multiplicity),
+                        values: remap(&e.values),
+                        table_len: e.table_len,
+                        multiplicity_bits: e.multiplicity_bits,
+                    }),
+            );
+        self.lk_table_expressions_namespace_map
+            .extend(other.lk_table_expressions_namespace_map);
+        self.lk_expressions_items_map.extend(
+            other
+                .lk_expressions_items_map
+                .into_iter()
+                .map(|(rom_type, record)| (rom_type, remap_all(record))),
+        );
+
+        self.assert_zero_expressions
+            .extend(remap_all(other.assert_zero_expressions));
+        self.assert_zero_expressions_namespace_map
+            .extend(other.assert_zero_expressions_namespace_map);
+        self.assert_zero_sumcheck_expressions
+            .extend(remap_all(other.assert_zero_sumcheck_expressions));
+        self.assert_zero_sumcheck_expressions_namespace_map
+            .extend(other.assert_zero_sumcheck_expressions_namespace_map);
+        self.max_non_lc_degree = self.max_non_lc_degree.max(other.max_non_lc_degree);
+
+        Ok(())
+    }
+
+    pub fn set_max_degree(&mut self, max_degree: usize) {
+        self.max_degree = max_degree;
+    }
+
+    fn check_degree(&self, expr: &Expression<E>) -> Result<(), ZKVMError> {
+        let degree = expr.degree();
+        if degree > self.max_degree {
+            return Err(ZKVMError::DegreeTooHigh {
+                got: degree,
+                max: self.max_degree,
+            });
+        }
+        Ok(())
+    }
+
     pub fn namespace<NR: Into<String>, N: FnOnce() -> NR, T>(
         &mut self,
         name_fn: N,
@@ -461,6 +602,307 @@ impl<E: ExtensionField> ConstraintSystem<E> {
         self.ns.pop_namespace();
         t
     }
+
+    /// number of challenges referenced by this circuit, i.e. max challenge id + 1
+    pub fn num_challenges(&self) -> usize {
+        chain!(
+            &self.r_expressions,
+            &self.w_expressions,
+            &self.lk_expressions,
+            &self.assert_zero_expressions,
+            &self.assert_zero_sumcheck_expressions,
+            self.r_table_expressions.iter().map(|e| &e.expr),
+            self.w_table_expressions.iter().map(|e| &e.expr),
+            self.lk_table_expressions
+                .iter()
+                .flat_map(|e| [&e.multiplicity, &e.values]),
+        )
+        .filter_map(|expr| expr.max_challenge_id())
+        .max()
+        .map_or(0, |id| id as usize + 1)
+    }
+
+    /// degree of the main selector sumcheck, i.e. the largest product of a selector with
+    /// a read/write/lookup record or a degree > 1 zero check monomial
+    pub fn selector_degree(&self) -> usize {
+        let has_records = !(self.r_expressions.is_empty()
+            && self.w_expressions.is_empty()
+            && self.lk_expressions.is_empty());
+        let record_degree = if has_records { SEL_DEGREE } else { 1 };
+        if self.assert_zero_sumcheck_expressions.is_empty() {
+            record_degree
+        } else {
+            // + 1 from sel_non_lc_zero_sumcheck
+            record_degree.max(self.max_non_lc_degree + 1)
+        }
+    }
+
+    /// index and registered name of the first degree 1 zero constraint that does not
+    /// vanish on the witness evaluations `wits_in_evals`, if any
+    pub fn find_violated_zero(
+        &self,
+        wits_in_evals: &[E],
+        challenges: &[E],
+    ) -> Option<(usize, &str)> {
+        self.assert_zero_expressions
+            .iter()
+            .position(|expr| eval_by_expr(wits_in_evals, challenges, expr) != E::ZERO)
+            .map(|i| (i, self.assert_zero_expressions_namespace_map[i].as_str()))
+    }
+
+    /// value of the dummy items padding the lookup tower, i.e. the lookup record of an all
+    /// zero item. prover padding and verifier logup balance both take it from here
+    pub fn dummy_lookup_item(&self, challenges: &[E]) -> E {
+        eval_by_expr(&[], challenges, &self.chip_record_alpha)
+    }
+
+    /// format `expr` with registered witness names in place of their ids,
+    /// falling back to `WitIn(id)` for unnamed witnesses
+    pub fn fmt_expr(&self, expr: &Expression<E>) -> String {
+        fmt::expr_with_names(expr, &mut vec![], false, &|wit_in| {
+            self.witin_namespace_map
+                .get(wit_in as usize)
+                .cloned()
+                .unwrap_or_else(|| format!("WitIn({})", wit_in))
+        })
+    }
+
+    /// render the constraint system as a Graphviz DOT digraph. witnesses and fixed columns
+    /// are nodes, and every r/w/lk/assert-zero expression is a cluster holding a root node
+    /// that points into its operation tree. structurally equal subexpressions are declared
+    /// once and shared by every expression using them
+    pub fn to_dot(&self) -> String {
+        let mut dot = DotGraph {
+            ids: HashMap::new(),
+            nodes: String::new(),
+            edges: String::new(),
+        };
+        for (id, name) in self.witin_namespace_map.iter().enumerate() {
+            dot.declare(format!("w{id}"), name, "box");
+        }
+        for (id, name) in self.fixed_namespace_map.iter().enumerate() {
+            dot.declare(format!("f{id}"), name, "box3d");
+        }
+
+        let kinds = [
+            ("r", &self.r_expressions, &self.r_expressions_namespace_map),
+            ("w", &self.w_expressions, &self.w_expressions_namespace_map),
+            (
+                "lk",
+                &self.lk_expressions,
+                &self.lk_expressions_namespace_map,
+            ),
+            (
+                "zero",
+                &self.assert_zero_expressions,
+                &self.assert_zero_expressions_namespace_map,
+            ),
+            (
+                "zero_sumcheck",
+                &self.assert_zero_sumcheck_expressions,
+                &self.assert_zero_sumcheck_expressions_namespace_map,
+            ),
+        ];
+        let mut clusters = String::new();
+        for (kind, exprs, names) in kinds {
+            for (i, (expr, name)) in exprs.iter().zip_eq(names).enumerate() {
+                let root = format!("{kind}{i}");
+                writeln!(clusters, "  subgraph \"cluster_{root}\" {{").unwrap();
+                writeln!(clusters, "    label=\"{}\";", dot_escape(name)).unwrap();
+                writeln!(
+                    clusters,
+                    "    {root} [label=\"{kind}\", shape=doubleoctagon];"
+                )
+                .unwrap();
+                writeln!(clusters, "  }}").unwrap();
+                let child = dot.node(expr, self);
+                writeln!(dot.edges, "  {root} -> {child};").unwrap();
+            }
+        }
+        format!(
+            "digraph constraint_system {{\n{}{clusters}{}}}\n",
+            dot.nodes, dot.edges
+        )
+    }
+
+    /// stable 32 byte hash of the constraint structure: every expression in order, the
+    /// max zero check degree and the column counts. unlike the fixed commitment it does not
+    /// depend on any fixed data, so it tells whether a verifying key still matches a circuit
+    pub fn digest(&self) -> [u8; 32] {
+        let table_exprs =
+            |tables: &[SetTableExpression<E>]| tables.iter().map(|table| &table.expr).collect_vec();
+        let encoded = bincode::serialize(&(
+            (
+                &self.r_expressions,
+                &self.w_expressions,
+                table_exprs(&self.r_table_expressions),
+                table_exprs(&self.w_table_expressions),
+                &self.lk_expressions,
+                &self.lk_table_expressions,
+                &self.assert_zero_expressions,
+                &self.assert_zero_sumcheck_expressions,
+            ),
+            self.max_non_lc_degree,
+            self.num_witin,
+            self.num_fixed,
+        ))
+        .expect("constraint system is serializable");
+
+        let mut transcript = BasicTranscript::<E>::new(b"constraint_system_digest");
+        transcript.append_message(&(encoded.len() as u64).to_le_bytes());
+        transcript.append_message(&encoded);
+        let limbs =
+            std::iter::repeat_with(|| transcript.read_challenge().elements.to_canonical_u64_vec())
+                .flatten();
+        let mut digest = [0u8; 32];
+        for (bytes, limb) in digest.chunks_mut(8).zip(limbs) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        digest
+    }
+
+    /// expressions added, removed or changed in `other` compared to `self`, per kind.
+    /// expressions are aligned along the longest common subsequence of structurally equal
+    /// ones, and unmatched expressions in between two aligned ones are paired up as changes
+    pub fn diff(&self, other: &Self) -> ConstraintSystemDiff {
+        ConstraintSystemDiff {
+            r_expressions: diff_expressions(
+                &self.r_expressions,
+                &self.r_expressions_namespace_map,
+                &other.r_expressions,
+                &other.r_expressions_namespace_map,
+            ),
+            w_expressions: diff_expressions(
+                &self.w_expressions,
+                &self.w_expressions_namespace_map,
+                &other.w_expressions,
+                &other.w_expressions_namespace_map,
+            ),
+            lk_expressions: diff_expressions(
+                &self.lk_expressions,
+                &self.lk_expressions_namespace_map,
+                &other.lk_expressions,
+                &other.lk_expressions_namespace_map,
+            ),
+            lk_table_expressions: diff_expressions(
+                &self.lk_table_expressions,
+                &self.lk_table_expressions_namespace_map,
+                &other.lk_table_expressions,
+                &other.lk_table_expressions_namespace_map,
+            ),
+            assert_zero_expressions: diff_expressions(
+                &self.assert_zero_expressions,
+                &self.assert_zero_expressions_namespace_map,
+                &other.assert_zero_expressions,
+                &other.assert_zero_expressions_namespace_map,
+            ),
+            assert_zero_sumcheck_expressions: diff_expressions(
+                &self.assert_zero_sumcheck_expressions,
+                &self.assert_zero_sumcheck_expressions_namespace_map,
+                &other.assert_zero_sumcheck_expressions,
+                &other.assert_zero_sumcheck_expressions_namespace_map,
+            ),
+        }
+    }
+}
+
+/// a difference in one kind of expressions between two constraint systems, see
+/// [`ConstraintSystem::diff`]. indices and names refer to the system they exist in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpressionChange {
+    /// expression `index` of the other system has no counterpart in this one
+    Added { index: usize, name: String },
+    /// expression `index` of this system has no counterpart in the other one
+    Removed { index: usize, name: String },
+    /// expression `index` of this system became expression `other_index` of the other one
+    Changed {
+        index: usize,
+        other_index: usize,
+        name: String,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintSystemDiff {
+    pub r_expressions: Vec<ExpressionChange>,
+    pub w_expressions: Vec<ExpressionChange>,
+    pub lk_expressions: Vec<ExpressionChange>,
+    pub lk_table_expressions: Vec<ExpressionChange>,
+    pub assert_zero_expressions: Vec<ExpressionChange>,
+    pub assert_zero_sumcheck_expressions: Vec<ExpressionChange>,
+}
+
+impl ConstraintSystemDiff {
+    pub fn is_empty(&self) -> bool {
+        self.r_expressions.is_empty()
+            && self.w_expressions.is_empty()
+            && self.lk_expressions.is_empty()
+            && self.lk_table_expressions.is_empty()
+            && self.assert_zero_expressions.is_empty()
+            && self.assert_zero_sumcheck_expressions.is_empty()
+    }
+}
+
+fn diff_expressions<T: PartialEq>(
+    ours: &[T],
+    our_names: &[String],
+    theirs: &[T],
+    their_names: &[String],
+) -> Vec<ExpressionChange> {
+    let (n, m) = (ours.len(), theirs.len());
+    // lcs[i][j]: longest common subsequence of ours[i..] and theirs[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if ours[i] == theirs[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let (mut removed, mut added) = (vec![], vec![]);
+    let mut flush =
+        |removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+            for (index, other_index) in removed.iter().zip(added.iter()) {
+                changes.push(ExpressionChange::Changed {
+                    index: *index,
+                    other_index: *other_index,
+                    name: their_names[*other_index].clone(),
+                });
+            }
+            let num_changed = removed.len().min(added.len());
+            changes.extend(removed.drain(..).skip(num_changed).map(|index| {
+                ExpressionChange::Removed {
+                    index,
+                    name: our_names[index].clone(),
+                }
+            }));
+            changes.extend(added.drain(..).skip(num_changed).map(|index| {
+                ExpressionChange::Added {
+                    index,
+                    name: their_names[index].clone(),
+                }
+            }));
+        };
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && ours[i] == theirs[j] {
+            flush(&mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+    flush(&mut removed, &mut added);
+    changes
 }
 
 #[cfg(test)]
@@ -479,6 +921,79 @@ impl<E: ExtensionField> ConstraintSystem<E> {
     }
 }
 
+/// node and edge declarations of [`ConstraintSystem::to_dot`]
+struct DotGraph<'e, E: ExtensionField> {
+    ids: HashMap<&'e Expression<E>, String>,
+    nodes: String,
+    edges: String,
+}
+
+impl<'e, E: ExtensionField> DotGraph<'e, E> {
+    fn declare(&mut self, id: String, label: &str, shape: &str) {
+        writeln!(
+            self.nodes,
+            "  {id} [label=\"{}\", shape={shape}];",
+            dot_escape(label)
+        )
+        .unwrap();
+    }
+
+    /// id of the node rendering `expr`, declaring it and its operands on first use
+    fn node(&mut self, expr: &'e Expression<E>, cs: &ConstraintSystem<E>) -> String {
+        match expr {
+            Expression::WitIn(id) => return format!("w{id}"),
+            Expression::Fixed(Fixed(id)) => return format!("f{id}"),
+            _ => {}
+        }
+        if let Some(id) = self.ids.get(expr) {
+            return id.clone();
+        }
+        let id = format!("n{}", self.ids.len());
+        self.ids.insert(expr, id.clone());
+        let operands: Vec<&Expression<E>> = match expr {
+            Expression::Instance(instance) => {
+                let label = cs
+                    .instance_name_map
+                    .get(instance)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{instance:?}"));
+                self.declare(id.clone(), &label, "box");
+                vec![]
+            }
+            Expression::Constant(_) | Expression::Challenge(..) => {
+                self.declare(
+                    id.clone(),
+                    &fmt::expr(expr, &mut vec![], false),
+                    "plaintext",
+                );
+                vec![]
+            }
+            Expression::Sum(a, b) => {
+                self.declare(id.clone(), "+", "circle");
+                vec![a.as_ref(), b.as_ref()]
+            }
+            Expression::Product(a, b) => {
+                self.declare(id.clone(), "*", "circle");
+                vec![a.as_ref(), b.as_ref()]
+            }
+            Expression::ScaledSum(x, a, b) => {
+                self.declare(id.clone(), "a * x + b", "circle");
+                vec![x.as_ref(), a.as_ref(), b.as_ref()]
+            }
+            Expression::WitIn(_) | Expression::Fixed(_) => unreachable!(),
+        };
+        for operand in operands {
+            let operand = self.node(operand, cs);
+            writeln!(self.edges, "  {id} -> {operand};").unwrap();
+        }
+        id
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 #[derive(Debug)]
 pub struct CircuitBuilder<'a, E: ExtensionField> {
     pub(crate) cs: &'a mut ConstraintSystem<E>,
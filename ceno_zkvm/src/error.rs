@@ -1,3 +1,5 @@
+use std::fmt;
+
 use mpcs::Error;
 
 #[derive(Debug)]
@@ -5,6 +7,16 @@ pub enum UtilError {
     UIntError(String),
 }
 
+impl fmt::Display for UtilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UtilError::UIntError(msg) => write!(f, "uint error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for UtilError {}
+
 #[derive(Debug)]
 pub enum ZKVMError {
     CircuitError,
@@ -14,7 +26,24 @@ pub enum ZKVMError {
     VKNotFound(String),
     FixedTraceNotFound(String),
     VerifyError(String),
+    /// a circuit proven or verified on its own has no instance
+    EmptyCircuit(String),
     PCSError(Error),
+    DegreeTooHigh {
+        got: usize,
+        max: usize,
+    },
+    NonBaseFieldValue(String),
+    ConstraintSystemMismatch(String),
+    WitnessCountMismatch {
+        expected: usize,
+        got: usize,
+    },
+    WitnessLengthMismatch {
+        witness_index: usize,
+        expected: usize,
+        got: usize,
+    },
 }
 
 impl From<UtilError> for ZKVMError {
@@ -22,3 +51,73 @@ impl From<UtilError> for ZKVMError {
         Self::UtilError(error)
     }
 }
+
+impl fmt::Display for ZKVMError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZKVMError::CircuitError => write!(f, "circuit error"),
+            ZKVMError::UtilError(err) => write!(f, "{err}"),
+            ZKVMError::WitnessNotFound(name) => write!(f, "witness not found for circuit {name}"),
+            ZKVMError::InvalidWitness(msg) => write!(f, "invalid witness: {msg}"),
+            ZKVMError::VKNotFound(name) => write!(f, "vk not found for circuit {name}"),
+            ZKVMError::FixedTraceNotFound(name) => {
+                write!(f, "fixed trace not found for circuit {name}")
+            }
+            ZKVMError::VerifyError(msg) => write!(f, "verify error: {msg}"),
+            ZKVMError::EmptyCircuit(name) => write!(f, "circuit {name} has no instances"),
+            ZKVMError::PCSError(err) => write!(f, "pcs error: {err}"),
+            ZKVMError::DegreeTooHigh { got, max } => {
+                write!(f, "expression degree {got} exceeds max degree {max}")
+            }
+            ZKVMError::NonBaseFieldValue(value) => {
+                write!(f, "{value} is not a base field value")
+            }
+            ZKVMError::ConstraintSystemMismatch(msg) => {
+                write!(f, "constraint system mismatch: {msg}")
+            }
+            ZKVMError::WitnessCountMismatch { expected, got } => {
+                write!(f, "expected {expected} witnesses, got {got}")
+            }
+            ZKVMError::WitnessLengthMismatch {
+                witness_index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "witness {witness_index} has {got} evaluations, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ZKVMError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ZKVMError::UtilError(err) => Some(err),
+            ZKVMError::PCSError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use mpcs::Error;
+
+    use super::ZKVMError;
+
+    #[test]
+    fn test_pcs_error_source() {
+        let err = ZKVMError::PCSError(Error::MerkleRootMismatch);
+        assert_eq!(err.to_string(), "pcs error: merkle root mismatch");
+
+        let source = err.source().expect("pcs error must have a source");
+        assert!(matches!(
+            source.downcast_ref::<Error>(),
+            Some(Error::MerkleRootMismatch)
+        ));
+        assert!(ZKVMError::CircuitError.source().is_none());
+    }
+}
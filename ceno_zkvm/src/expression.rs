@@ -13,6 +13,7 @@ use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 
 use multilinear_extensions::virtual_poly_v2::ArcMultilinearExtension;
+use serde::Serialize;
 
 use crate::{
     circuit_builder::CircuitBuilder,
@@ -20,7 +21,7 @@ use crate::{
     structs::{ChallengeId, RAMType, WitnessId},
 };
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 pub enum Expression<E: ExtensionField> {
     /// WitIn(Id)
     WitIn(WitnessId),
@@ -41,6 +42,38 @@ pub enum Expression<E: ExtensionField> {
     Challenge(ChallengeId, usize, E, E),
 }
 
+/// a pass over an [`Expression`] tree, driven by [`Expression::accept`].
+/// leaves are ignored and inner nodes visit their children in order by default,
+/// so a pass only overrides the nodes it cares about
+pub trait ExpressionVisitor<E: ExtensionField> {
+    fn visit_witin(&mut self, _id: WitnessId) {}
+
+    fn visit_fixed(&mut self, _fixed: &Fixed) {}
+
+    fn visit_instance(&mut self, _instance: &Instance) {}
+
+    fn visit_constant(&mut self, _constant: &E::BaseField) {}
+
+    fn visit_challenge(&mut self, _id: ChallengeId, _pow: usize, _scalar: &E, _offset: &E) {}
+
+    fn visit_sum(&mut self, a: &Expression<E>, b: &Expression<E>) {
+        a.accept(self);
+        b.accept(self);
+    }
+
+    fn visit_product(&mut self, a: &Expression<E>, b: &Expression<E>) {
+        a.accept(self);
+        b.accept(self);
+    }
+
+    /// `ScaledSum(x, a, b)`, i.e. `a * x + b`
+    fn visit_scaled_sum(&mut self, x: &Expression<E>, a: &Expression<E>, b: &Expression<E>) {
+        x.accept(self);
+        a.accept(self);
+        b.accept(self);
+    }
+}
+
 /// this is used as finite state machine state
 /// for differentiate an expression is in monomial form or not
 enum MonomialState {
@@ -65,6 +98,229 @@ impl<E: ExtensionField> Expression<E> {
         }
     }
 
+    /// degree in the single witness `wit`, treating every other witness as a constant
+    pub fn degree_in(&self, wit: WitnessId) -> usize {
+        match self {
+            Expression::WitIn(id) => (*id == wit) as usize,
+            Expression::Fixed(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_)
+            | Expression::Challenge(..) => 0,
+            Expression::Sum(a_expr, b_expr) => max(a_expr.degree_in(wit), b_expr.degree_in(wit)),
+            Expression::Product(a_expr, b_expr) => a_expr.degree_in(wit) + b_expr.degree_in(wit),
+            Expression::ScaledSum(x, a, b) => {
+                max(a.degree_in(wit) + x.degree_in(wit), b.degree_in(wit))
+            }
+        }
+    }
+
+    /// degree 1 in the witness and fixed columns, i.e. an affine combination of them
+    /// with constant, challenge or instance coefficients
+    pub fn is_linear(&self) -> bool {
+        self.degree() == 1
+    }
+
+    /// value of this expression if it only consists of constants, `None` as soon as a
+    /// witness, fixed, instance or challenge is involved
+    pub fn as_constant(&self) -> Option<E::BaseField> {
+        match self {
+            Expression::Constant(c) => Some(*c),
+            Expression::Sum(a, b) => Some(a.as_constant()? + b.as_constant()?),
+            Expression::Product(a, b) => Some(a.as_constant()? * b.as_constant()?),
+            Expression::ScaledSum(x, a, b) => {
+                Some(x.as_constant()? * a.as_constant()? + b.as_constant()?)
+            }
+            Expression::WitIn(_)
+            | Expression::Fixed(_)
+            | Expression::Instance(_)
+            | Expression::Challenge(..) => None,
+        }
+    }
+
+    /// highest power a challenge is raised to within this expression
+    pub fn max_challenge_power(&self) -> usize {
+        match self {
+            Expression::Challenge(_, pow, _, _) => *pow,
+            Expression::Sum(a_expr, b_expr) | Expression::Product(a_expr, b_expr) => {
+                max(a_expr.max_challenge_power(), b_expr.max_challenge_power())
+            }
+            Expression::ScaledSum(x, a, b) => max(
+                x.max_challenge_power(),
+                max(a.max_challenge_power(), b.max_challenge_power()),
+            ),
+            Expression::Fixed(_)
+            | Expression::WitIn(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_) => 0,
+        }
+    }
+
+    /// highest challenge id referenced by this expression, if any
+    pub fn max_challenge_id(&self) -> Option<ChallengeId> {
+        match self {
+            Expression::Challenge(id, _, _, _) => Some(*id),
+            Expression::Sum(a_expr, b_expr) | Expression::Product(a_expr, b_expr) => {
+                max(a_expr.max_challenge_id(), b_expr.max_challenge_id())
+            }
+            Expression::ScaledSum(x, a, b) => max(
+                x.max_challenge_id(),
+                max(a.max_challenge_id(), b.max_challenge_id()),
+            ),
+            Expression::Fixed(_)
+            | Expression::WitIn(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_) => None,
+        }
+    }
+
+    /// rewrite every witness id through `f`, e.g. to shift ids when merging constraint systems
+    pub fn remap_witnesses(&self, f: &impl Fn(WitnessId) -> WitnessId) -> Expression<E> {
+        match self {
+            Expression::WitIn(id) => Expression::WitIn(f(*id)),
+            Expression::Sum(a, b) => Expression::Sum(
+                Box::new(a.remap_witnesses(f)),
+                Box::new(b.remap_witnesses(f)),
+            ),
+            Expression::Product(a, b) => Expression::Product(
+                Box::new(a.remap_witnesses(f)),
+                Box::new(b.remap_witnesses(f)),
+            ),
+            Expression::ScaledSum(x, a, b) => Expression::ScaledSum(
+                Box::new(x.remap_witnesses(f)),
+                Box::new(a.remap_witnesses(f)),
+                Box::new(b.remap_witnesses(f)),
+            ),
+            Expression::Fixed(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_)
+            | Expression::Challenge(..) => self.clone(),
+        }
+    }
+
+    /// dispatch this node to the matching `visit_*` method of `visitor`
+    pub fn accept<V: ExpressionVisitor<E> + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Expression::WitIn(id) => visitor.visit_witin(*id),
+            Expression::Fixed(fixed) => visitor.visit_fixed(fixed),
+            Expression::Instance(instance) => visitor.visit_instance(instance),
+            Expression::Constant(constant) => visitor.visit_constant(constant),
+            Expression::Challenge(id, pow, scalar, offset) => {
+                visitor.visit_challenge(*id, *pow, scalar, offset)
+            }
+            Expression::Sum(a, b) => visitor.visit_sum(a, b),
+            Expression::Product(a, b) => visitor.visit_product(a, b),
+            Expression::ScaledSum(x, a, b) => visitor.visit_scaled_sum(x, a, b),
+        }
+    }
+
+    /// witness ids referenced by this expression, deduplicated in order of first occurrence
+    pub fn used_witnesses(&self) -> Vec<WitnessId> {
+        struct UsedWitnesses(Vec<WitnessId>);
+        impl<E: ExtensionField> ExpressionVisitor<E> for UsedWitnesses {
+            fn visit_witin(&mut self, id: WitnessId) {
+                if !self.0.contains(&id) {
+                    self.0.push(id);
+                }
+            }
+        }
+
+        let mut visitor = UsedWitnesses(vec![]);
+        self.accept(&mut visitor);
+        visitor.0
+    }
+
+    /// canonical sign form: negations sit on leaves and constants only, as produced by `Neg`,
+    /// with `-(-x)` collapsed to `x` and the signs of two negated factors cancelled
+    pub fn normalize_signs(&self) -> Expression<E> {
+        match self {
+            Expression::Sum(a, b) => {
+                Expression::Sum(Box::new(a.normalize_signs()), Box::new(b.normalize_signs()))
+            }
+            Expression::Product(a, b) => {
+                let (a, b) = (a.normalize_signs(), b.normalize_signs());
+                match (a.as_negation(), b.as_negation()) {
+                    (Some(a), Some(b)) => {
+                        Expression::Product(Box::new(a.clone()), Box::new(b.clone()))
+                    }
+                    _ => Expression::Product(Box::new(a), Box::new(b)),
+                }
+            }
+            Expression::ScaledSum(x, a, b) => {
+                let (x, a, b) = (
+                    x.normalize_signs(),
+                    a.normalize_signs(),
+                    b.normalize_signs(),
+                );
+                match (x.as_negation(), &a, &b) {
+                    // a * (-y) + b = (-a) * y + b
+                    (Some(y), _, _) => {
+                        Expression::ScaledSum(Box::new(y.clone()), Box::new(-a), Box::new(b))
+                            .normalize_signs()
+                    }
+                    (None, Expression::Constant(a), Expression::Constant(b))
+                        if *a == E::BaseField::ONE && *b == E::BaseField::ZERO =>
+                    {
+                        x
+                    }
+                    (None, _, _) => Expression::ScaledSum(Box::new(x), Box::new(a), Box::new(b)),
+                }
+            }
+            Expression::WitIn(_)
+            | Expression::Fixed(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_)
+            | Expression::Challenge(..) => self.clone(),
+        }
+    }
+
+    /// `x` if this expression is `-x`, i.e. `ScaledSum(x, -1, 0)`
+    fn as_negation(&self) -> Option<&Expression<E>> {
+        match self {
+            Expression::ScaledSum(x, a, b)
+                if **a == Expression::Constant(-E::BaseField::ONE) && **b == Expression::ZERO =>
+            {
+                Some(x)
+            }
+            _ => None,
+        }
+    }
+
+    /// fold the known challenges `values[id]` into the expression, leaving witnesses,
+    /// fixed, instances and challenges with `id >= values.len()` symbolic.
+    /// a bound challenge becomes a `Constant` if it lands in the base field, otherwise a
+    /// `Challenge` of power 0 and zero scalar which carries the value as its offset
+    pub fn bind_challenges(&self, values: &[E]) -> Expression<E> {
+        match self {
+            Expression::Challenge(id, pow, scalar, offset) if (*id as usize) < values.len() => {
+                let value = values[*id as usize].pow([*pow as u64]) * scalar + offset;
+                let (base, ext) = value.as_bases().split_first().unwrap();
+                if ext.iter().all(|limb| *limb == E::BaseField::ZERO) {
+                    Expression::Constant(*base)
+                } else {
+                    Expression::Challenge(*id, 0, E::ZERO, value)
+                }
+            }
+            Expression::Sum(a, b) => Expression::Sum(
+                Box::new(a.bind_challenges(values)),
+                Box::new(b.bind_challenges(values)),
+            ),
+            Expression::Product(a, b) => Expression::Product(
+                Box::new(a.bind_challenges(values)),
+                Box::new(b.bind_challenges(values)),
+            ),
+            Expression::ScaledSum(x, a, b) => Expression::ScaledSum(
+                Box::new(x.bind_challenges(values)),
+                Box::new(a.bind_challenges(values)),
+                Box::new(b.bind_challenges(values)),
+            ),
+            Expression::WitIn(_)
+            | Expression::Fixed(_)
+            | Expression::Instance(_)
+            | Expression::Constant(_)
+            | Expression::Challenge(..) => self.clone(),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn evaluate<T>(
         &self,
@@ -725,10 +981,10 @@ pub struct WitIn {
     pub id: WitnessId,
 }
 
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize)]
 pub struct Fixed(pub usize);
 
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize)]
 pub struct Instance(pub usize);
 
 impl WitIn {
@@ -866,61 +1122,122 @@ pub mod fmt {
         wtns: &mut Vec<WitnessId>,
         add_parens_sum: bool,
     ) -> String {
-        match expression {
-            Expression::WitIn(wit_in) => {
-                if !wtns.contains(wit_in) {
-                    wtns.push(*wit_in);
-                }
-                format!("WitIn({})", wit_in)
+        expr_with_names(expression, wtns, add_parens_sum, &|wit_in| {
+            format!("WitIn({})", wit_in)
+        })
+    }
+
+    /// same as [`expr`], but witnesses are rendered through `wit_name`
+    pub fn expr_with_names<E: ExtensionField>(
+        expression: &Expression<E>,
+        wtns: &mut Vec<WitnessId>,
+        add_parens_sum: bool,
+        wit_name: &impl Fn(WitnessId) -> String,
+    ) -> String {
+        let mut formatter = ExprFormatter {
+            out: String::new(),
+            wtns,
+            add_parens_sum,
+            wit_name,
+        };
+        expression.accept(&mut formatter);
+        formatter.out
+    }
+
+    struct ExprFormatter<'a, F> {
+        out: String,
+        wtns: &'a mut Vec<WitnessId>,
+        // whether the node visited next must put a top level sum into parens
+        add_parens_sum: bool,
+        wit_name: &'a F,
+    }
+
+    impl<F> ExprFormatter<'_, F> {
+        fn child<E: ExtensionField>(&mut self, expression: &Expression<E>, add_parens_sum: bool)
+        where
+            F: Fn(WitnessId) -> String,
+        {
+            self.add_parens_sum = add_parens_sum;
+            expression.accept(self);
+        }
+
+        fn open_sum(&mut self) -> bool {
+            let parens = self.add_parens_sum;
+            if parens {
+                self.out.push('(');
             }
-            Expression::Challenge(id, pow, scaler, offset) => {
-                if *pow == 1 && *scaler == 1.into() && *offset == 0.into() {
-                    format!("Challenge({})", id)
-                } else {
-                    let mut s = String::new();
-                    if *scaler != 1.into() {
-                        write!(s, "{}*", field(scaler)).unwrap();
-                    }
-                    write!(s, "Challenge({})", id,).unwrap();
-                    if *pow > 1 {
-                        write!(s, "^{}", pow).unwrap();
-                    }
-                    if *offset != 0.into() {
-                        write!(s, "+{}", field(offset)).unwrap();
-                    }
-                    s
-                }
+            parens
+        }
+
+        fn close_sum(&mut self, parens: bool) {
+            if parens {
+                self.out.push(')');
             }
-            Expression::Constant(constant) => {
-                base_field::<E::BaseField>(constant, true).to_string()
+        }
+    }
+
+    impl<E: ExtensionField, F: Fn(WitnessId) -> String> ExpressionVisitor<E> for ExprFormatter<'_, F> {
+        fn visit_witin(&mut self, id: WitnessId) {
+            if !self.wtns.contains(&id) {
+                self.wtns.push(id);
             }
-            Expression::Fixed(fixed) => format!("{:?}", fixed),
-            Expression::Instance(i) => format!("{:?}", i),
-            Expression::Sum(left, right) => {
-                let s = format!("{} + {}", expr(left, wtns, false), expr(right, wtns, false));
-                if add_parens_sum {
-                    format!("({})", s)
-                } else {
-                    s
+            self.out.push_str(&(self.wit_name)(id));
+        }
+
+        fn visit_fixed(&mut self, fixed: &Fixed) {
+            write!(self.out, "{:?}", fixed).unwrap();
+        }
+
+        fn visit_instance(&mut self, instance: &Instance) {
+            write!(self.out, "{:?}", instance).unwrap();
+        }
+
+        fn visit_constant(&mut self, constant: &E::BaseField) {
+            self.out
+                .push_str(&base_field::<E::BaseField>(constant, true));
+        }
+
+        fn visit_challenge(&mut self, id: ChallengeId, pow: usize, scaler: &E, offset: &E) {
+            let s = &mut self.out;
+            if pow == 1 && *scaler == 1.into() && *offset == 0.into() {
+                write!(s, "Challenge({})", id).unwrap();
+            } else {
+                if *scaler != 1.into() {
+                    write!(s, "{}*", field(scaler)).unwrap();
                 }
-            }
-            Expression::Product(left, right) => {
-                format!("{} * {}", expr(left, wtns, true), expr(right, wtns, true))
-            }
-            Expression::ScaledSum(x, a, b) => {
-                let s = format!(
-                    "{} * {} + {}",
-                    expr(a, wtns, true),
-                    expr(x, wtns, true),
-                    expr(b, wtns, false)
-                );
-                if add_parens_sum {
-                    format!("({})", s)
-                } else {
-                    s
+                write!(s, "Challenge({})", id,).unwrap();
+                if pow > 1 {
+                    write!(s, "^{}", pow).unwrap();
+                }
+                if *offset != 0.into() {
+                    write!(s, "+{}", field(offset)).unwrap();
                 }
             }
         }
+
+        fn visit_sum(&mut self, a: &Expression<E>, b: &Expression<E>) {
+            let parens = self.open_sum();
+            self.child(a, false);
+            self.out.push_str(" + ");
+            self.child(b, false);
+            self.close_sum(parens);
+        }
+
+        fn visit_product(&mut self, a: &Expression<E>, b: &Expression<E>) {
+            self.child(a, true);
+            self.out.push_str(" * ");
+            self.child(b, true);
+        }
+
+        fn visit_scaled_sum(&mut self, x: &Expression<E>, a: &Expression<E>, b: &Expression<E>) {
+            let parens = self.open_sum();
+            self.child(a, true);
+            self.out.push_str(" * ");
+            self.child(x, true);
+            self.out.push_str(" + ");
+            self.child(b, false);
+            self.close_sum(parens);
+        }
     }
 
     pub fn field<E: ExtensionField>(field: &E) -> String {
@@ -989,12 +1306,59 @@ pub mod fmt {
 }
 
 #[cfg(test)]
-mod tests {
-    use goldilocks::GoldilocksExt2;
+impl<E: ExtensionField + 'static> Expression<E> {
+    /// strategy producing random expressions of up to `depth` nested operations over
+    /// `num_witins` witnesses and `num_challenges` challenges (both must be non-zero).
+    /// nodes are combined through the `Add/Sub/Mul/Neg` impls to exercise their simplification.
+    pub fn arbitrary(
+        depth: u32,
+        num_witins: WitnessId,
+        num_challenges: ChallengeId,
+    ) -> impl proptest::strategy::Strategy<Value = Expression<E>> {
+        use proptest::prelude::*;
+
+        let leaf = prop_oneof![
+            (0..num_witins).prop_map(Expression::WitIn),
+            any::<u64>().prop_map(|c| Expression::Constant(E::BaseField::from(c))),
+            (0..num_challenges, 1..=2usize, any::<u64>(), any::<u64>()).prop_map(
+                |(id, pow, scalar, offset)| {
+                    Expression::Challenge(
+                        id,
+                        pow,
+                        E::from(E::BaseField::from(scalar)),
+                        E::from(E::BaseField::from(offset)),
+                    )
+                }
+            ),
+        ];
+        leaf.prop_recursive(depth, 1 << depth, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a + b),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a - b),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a * b),
+                inner.prop_map(|a| -a),
+            ]
+        })
+    }
+}
 
-    use crate::circuit_builder::{CircuitBuilder, ConstraintSystem};
+#[cfg(test)]
+mod tests {
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+    use itertools::Itertools;
+    use proptest::{prelude::any, prop_assert_eq, proptest};
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+
+    use crate::{
+        circuit_builder::{
+            CircuitBuilder, ConstraintSystem, ConstraintSystemDiff, ExpressionChange,
+        },
+        error::ZKVMError,
+        scheme::utils::eval_by_expr,
+        structs::{ChallengeId, WitnessId},
+    };
 
-    use super::{Expression, ToExpr, fmt};
+    use super::{Expression, ExpressionVisitor, ToExpr, fmt};
     use ff::Field;
 
     #[test]
@@ -1145,4 +1509,399 @@ mod tests {
         assert_eq!(s, "WitIn(0)");
         assert_eq!(wtns_acc, vec![0]);
     }
+
+    #[test]
+    fn test_degree_in() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let y = cb.create_witin(|| "y");
+        let z = cb.create_witin(|| "z");
+
+        let expr: Expression<E> = x.expr() * y.expr() * x.expr() + y.expr();
+        assert_eq!(expr.degree(), 3);
+        assert_eq!(expr.degree_in(x.id), 2);
+        assert_eq!(expr.degree_in(y.id), 1);
+        assert_eq!(expr.degree_in(z.id), 0);
+    }
+
+    #[test]
+    fn test_expression_visitor_counts_nodes() {
+        #[derive(Default)]
+        struct NodeCounter {
+            leaves: usize,
+            inner: usize,
+        }
+        impl ExpressionVisitor<GoldilocksExt2> for NodeCounter {
+            fn visit_witin(&mut self, _id: WitnessId) {
+                self.leaves += 1;
+            }
+
+            fn visit_constant(&mut self, _constant: &Goldilocks) {
+                self.leaves += 1;
+            }
+
+            fn visit_challenge(
+                &mut self,
+                _id: ChallengeId,
+                _pow: usize,
+                _scalar: &GoldilocksExt2,
+                _offset: &GoldilocksExt2,
+            ) {
+                self.leaves += 1;
+            }
+
+            fn visit_product(
+                &mut self,
+                a: &Expression<GoldilocksExt2>,
+                b: &Expression<GoldilocksExt2>,
+            ) {
+                self.inner += 1;
+                a.accept(self);
+                b.accept(self);
+            }
+        }
+
+        type E = GoldilocksExt2;
+        let challenge = Expression::<E>::Challenge(0, 1, E::ONE, E::ZERO);
+        // (WitIn(0) * WitIn(1)) + (Challenge(0) * WitIn(0) + 3)
+        let expr = Expression::Sum(
+            Box::new(Expression::Product(
+                Box::new(Expression::WitIn(0)),
+                Box::new(Expression::WitIn(1)),
+            )),
+            Box::new(Expression::ScaledSum(
+                Box::new(Expression::WitIn(0)),
+                Box::new(challenge),
+                Box::new(Expression::Constant(3.into())),
+            )),
+        );
+        let mut counter = NodeCounter::default();
+        expr.accept(&mut counter);
+        // sums are walked through by default, only products are counted
+        assert_eq!((counter.leaves, counter.inner), (5, 1));
+        assert_eq!(expr.used_witnesses(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_fmt_expr_with_witness_names() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let counter = cb.create_witin(|| "counter");
+
+        let expr: Expression<E> = counter.expr() + 1;
+        let s = cs.fmt_expr(&expr);
+        assert!(s.contains("counter"), "{s}");
+        assert!(!s.contains("WitIn(0)"), "{s}");
+
+        // unregistered ids fall back to the raw witness id
+        let s = cs.fmt_expr(&Expression::<E>::WitIn(1));
+        assert_eq!(s, "WitIn(1)");
+    }
+
+    #[test]
+    fn test_num_challenges() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        assert_eq!(cs.num_challenges(), 0);
+
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let y = cb.create_witin(|| "y");
+        cb.require_zero(
+            || "uses challenge 0",
+            x.expr() - Expression::Challenge(0, 1, E::ONE, E::ZERO),
+        )
+        .unwrap();
+        cb.require_zero(
+            || "uses challenge 2",
+            y.expr() * Expression::Challenge(2, 2, E::ONE, E::ZERO),
+        )
+        .unwrap();
+        // challenge 1 is unused but still has to be sampled
+        assert_eq!(cs.num_challenges(), 3);
+    }
+
+    #[test]
+    fn test_selector_degree() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        cb.require_zero(|| "x == 1", x.expr() - 1).unwrap();
+        // no records and no degree > 1 constraints: only the selector itself
+        assert_eq!(cs.selector_degree(), 1);
+
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        cb.require_zero(|| "x is bit", x.expr() * (x.expr() - 1))
+            .unwrap();
+        assert_eq!(cs.selector_degree(), 3);
+    }
+
+    #[test]
+    fn test_as_constant() {
+        type E = GoldilocksExt2;
+        type B = goldilocks::Goldilocks;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+
+        let c = |v: u64| Expression::<E>::Constant(B::from(v));
+        assert_eq!(((c(2) + c(3)) * c(4)).as_constant(), Some(B::from(20u64)));
+        assert_eq!((x.expr() + 1).as_constant(), None);
+        assert_eq!(
+            (c(2) * Expression::Challenge(0, 1, E::ONE, E::ZERO)).as_constant(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bind_challenges() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let mut rng = ChaChaRng::seed_from_u64(7);
+        let (alpha, beta, s, o) = (
+            E::random(&mut rng),
+            E::random(&mut rng),
+            E::random(&mut rng),
+            E::random(&mut rng),
+        );
+
+        // x * (s * alpha^2 + o) + beta
+        let expr = x.expr() * Expression::Challenge(0, 2, s, o)
+            + Expression::Challenge(1, 1, E::ONE, E::ZERO);
+        let bound = expr.bind_challenges(&[alpha]);
+        assert_eq!(bound.max_challenge_power(), 1);
+
+        // the residual only depends on the unbound challenge
+        let wits = [E::random(&mut rng)];
+        assert_eq!(
+            eval_by_expr(&wits, &[E::random(&mut rng), beta], &bound),
+            eval_by_expr(&wits, &[alpha, beta], &expr)
+        );
+
+        // base field values fold into constants
+        let c: Expression<E> = Expression::Challenge(0, 1, E::from(3u64), E::from(4u64));
+        assert!(matches!(
+            c.bind_challenges(&[E::from(5u64)]),
+            Expression::Constant(v) if v == goldilocks::Goldilocks::from(19u64)
+        ));
+    }
+
+    #[test]
+    fn test_constraint_system_diff() {
+        type E = GoldilocksExt2;
+        let build = |extra_lookup: bool| {
+            let mut cs = ConstraintSystem::new(|| "test_root");
+            let mut cb = CircuitBuilder::<E>::new(&mut cs);
+            let x = cb.create_witin(|| "x");
+            let y = cb.create_witin(|| "y");
+            cb.assert_ux::<_, _, 16>(|| "x_u16", x.expr()).unwrap();
+            if extra_lookup {
+                cb.assert_ux::<_, _, 8>(|| "y_u8", y.expr()).unwrap();
+            }
+            cb.assert_ux::<_, _, 5>(|| "y_u5", y.expr()).unwrap();
+            cb.require_zero(|| "x_y", x.expr() * y.expr()).unwrap();
+            cs
+        };
+        let (cs, extended) = (build(false), build(true));
+
+        assert!(cs.diff(&cs).is_empty());
+        let diff = cs.diff(&extended);
+        assert_eq!(diff.lk_expressions.len(), 1);
+        assert!(matches!(
+            &diff.lk_expressions[0],
+            ExpressionChange::Added { index: 1, name } if name.contains("y_u8")
+        ));
+        assert!(
+            ConstraintSystemDiff {
+                lk_expressions: vec![],
+                ..diff.clone()
+            }
+            .is_empty()
+        );
+        assert!(matches!(&extended.diff(&cs).lk_expressions[..], [
+            ExpressionChange::Removed { index: 1, .. }
+        ]));
+    }
+
+    #[test]
+    fn test_constraint_system_digest() {
+        type E = GoldilocksExt2;
+        let build = |swap: bool| {
+            let mut cs = ConstraintSystem::new(|| "test_root");
+            let mut cb = CircuitBuilder::<E>::new(&mut cs);
+            let x = cb.create_witin(|| "x");
+            let y = cb.create_witin(|| "y");
+            // two independent constraints, registered in either order
+            let constraints = [x.expr() - 1, y.expr() - 2];
+            let order = if swap { [1, 0] } else { [0, 1] };
+            for i in order {
+                cb.require_zero(|| format!("c{i}"), constraints[i].clone())
+                    .unwrap();
+            }
+            cs
+        };
+
+        assert_eq!(build(false).digest(), build(false).digest());
+        assert_ne!(build(false).digest(), build(true).digest());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        type E = GoldilocksExt2;
+        type B = goldilocks::Goldilocks;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let y = cb.create_witin(|| "y \"quoted\"");
+        let shared = Expression::Sum(Box::new(x.expr()), Box::new(y.expr()));
+        for c in [1u64, 2] {
+            let expr = Expression::Sum(
+                Box::new(shared.clone()),
+                Box::new(Expression::Constant(B::from(c))),
+            );
+            cb.require_zero(|| format!("x + y == -{c}"), expr).unwrap();
+        }
+        cb.require_zero(|| "x is bit", x.expr() * (x.expr() - 1))
+            .unwrap();
+
+        let dot = cs.to_dot();
+        assert!(dot.starts_with("digraph"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert!(dot.contains(r#"label="y \"quoted\"""#), "{dot}");
+
+        let declared = dot
+            .lines()
+            .filter_map(|line| line.trim().split_once(" ["))
+            .map(|(id, _)| id)
+            .collect_vec();
+        assert!(declared.iter().all_unique(), "{dot}");
+        let edges = dot
+            .lines()
+            .filter_map(|line| line.trim().trim_end_matches(';').split_once(" -> "))
+            .collect_vec();
+        for (from, to) in &edges {
+            assert!(declared.contains(from), "{from} not declared in {dot}");
+            assert!(declared.contains(to), "{to} not declared in {dot}");
+        }
+
+        // `x + y` is declared once, with an inbound edge from each constraint using it
+        let operands = |id: &str| {
+            edges
+                .iter()
+                .filter(|(from, _)| *from == id)
+                .map(|(_, to)| *to)
+                .collect_vec()
+        };
+        let shared_ids = declared
+            .iter()
+            .filter(|id| operands(id) == ["w0", "w1"])
+            .collect_vec();
+        assert_eq!(shared_ids.len(), 1, "{dot}");
+        let inbound = edges
+            .iter()
+            .filter(|(_, to)| *to == **shared_ids[0])
+            .count();
+        assert_eq!(inbound, 2, "{dot}");
+    }
+
+    #[test]
+    fn test_find_violated_zero() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let y = cb.create_witin(|| "y");
+        cb.require_zero(|| "x == 1", x.expr() - 1).unwrap();
+        cb.require_zero(|| "y == x", y.expr() - x.expr()).unwrap();
+
+        assert_eq!(cs.find_violated_zero(&[E::ONE, E::ONE], &[]), None);
+        let (index, name) = cs
+            .find_violated_zero(&[E::ONE, E::from(2u64)], &[])
+            .expect("violated constraint");
+        assert_eq!(index, 1);
+        assert!(name.ends_with("y == x"), "{name}");
+    }
+
+    #[test]
+    fn test_require_zero_degree_too_high() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        cs.set_max_degree(3);
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+
+        let expr: Expression<E> = x.expr() * x.expr() * x.expr() * x.expr() * x.expr();
+        assert_eq!(expr.degree(), 5);
+        let result = cb.require_zero(|| "degree 5", expr);
+        assert!(matches!(
+            result,
+            Err(ZKVMError::DegreeTooHigh { got: 5, max: 3 })
+        ));
+        assert!(cs.assert_zero_sumcheck_expressions.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_signs() {
+        type E = GoldilocksExt2;
+        let mut cs = ConstraintSystem::new(|| "test_root");
+        let mut cb = CircuitBuilder::<E>::new(&mut cs);
+        let x = cb.create_witin(|| "x");
+        let y = cb.create_witin(|| "y");
+
+        // -(-x) is ScaledSum(x, 1, 0) until normalized
+        let double_neg: Expression<E> = -(-x.expr());
+        assert_ne!(double_neg, x.expr());
+        assert_eq!(double_neg.normalize_signs(), x.expr());
+
+        // the signs of two negated factors cancel
+        let product = Expression::Product(Box::new(-x.expr()), Box::new(-y.expr()));
+        assert_eq!(
+            product.normalize_signs(),
+            Expression::Product(Box::new(x.expr()), Box::new(y.expr()))
+        );
+        // while a single one stays on its leaf
+        let product: Expression<E> = -(x.expr() * y.expr());
+        assert_eq!(product.normalize_signs(), product);
+    }
+
+    proptest! {
+        #[test]
+        fn test_normalize_signs_preserves_evaluation(
+            expr in Expression::<GoldilocksExt2>::arbitrary(4, 3, 2),
+            seed in any::<[u8; 32]>(),
+        ) {
+            let mut rng = ChaChaRng::from_seed(seed);
+            let witnesses = (0..3).map(|_| GoldilocksExt2::random(&mut rng)).collect_vec();
+            let challenges = (0..2).map(|_| GoldilocksExt2::random(&mut rng)).collect_vec();
+
+            let eval = |expr: &Expression<GoldilocksExt2>| eval_by_expr(&witnesses, &challenges, expr);
+            prop_assert_eq!(eval(&expr), eval(&expr.normalize_signs()));
+            let neg = -expr.clone();
+            prop_assert_eq!(eval(&neg), eval(&neg.normalize_signs()));
+            // double negation cancels
+            prop_assert_eq!((-neg).normalize_signs(), expr.normalize_signs());
+        }
+
+        #[test]
+        fn test_monomial_form_preserves_evaluation(
+            expr in Expression::<GoldilocksExt2>::arbitrary(4, 3, 2),
+            seed in any::<[u8; 32]>(),
+        ) {
+            let mut rng = ChaChaRng::from_seed(seed);
+            let witnesses = (0..3).map(|_| GoldilocksExt2::random(&mut rng)).collect_vec();
+            let challenges = (0..2).map(|_| GoldilocksExt2::random(&mut rng)).collect_vec();
+
+            let monomials = expr.to_monomial_form();
+            prop_assert_eq!(
+                eval_by_expr(&witnesses, &challenges, &expr),
+                eval_by_expr(&witnesses, &challenges, &monomials)
+            );
+        }
+    }
 }
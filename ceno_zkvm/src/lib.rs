@@ -18,6 +18,8 @@ mod keygen;
 pub mod state;
 pub mod stats;
 pub mod structs;
+#[cfg(test)]
+pub(crate) mod test_utils;
 mod uint;
 mod utils;
 mod virtual_polys;
@@ -1,12 +1,14 @@
 use ff_ext::ExtensionField;
 use itertools::Itertools;
 use mpcs::PolynomialCommitmentScheme;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::{collections::BTreeMap, fmt::Debug};
 use sumcheck::structs::IOPProverMessage;
 
 use crate::structs::TowerProofs;
 
+use constants::NUM_FANIN;
+
 pub mod constants;
 pub mod prover;
 pub mod utils;
@@ -16,20 +18,20 @@ pub mod mock_prover;
 #[cfg(test)]
 mod tests;
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
 pub struct ZKVMOpcodeProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     // TODO support >1 opcodes
     pub num_instances: usize,
+    // number of dummy lookup items padding the logup tower
+    pub num_padded_lk_items: usize,
 
     // product constraints
-    pub record_r_out_evals: Vec<E>,
-    pub record_w_out_evals: Vec<E>,
+    pub record_r_out_evals: [E; NUM_FANIN],
+    pub record_w_out_evals: [E; NUM_FANIN],
 
-    // logup sum at layer 1
-    pub lk_p1_out_eval: E,
-    pub lk_p2_out_eval: E,
-    pub lk_q1_out_eval: E,
-    pub lk_q2_out_eval: E,
+    // logup sum at layer 1, [p1, p2, q1, q2]
+    pub lk_out_evals: [E; 4],
 
     pub tower_proof: TowerProofs<E>,
 
@@ -42,9 +44,22 @@ pub struct ZKVMOpcodeProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>
     pub wits_commit: PCS::Commitment,
     pub wits_opening_proof: PCS::Proof,
     pub wits_in_evals: Vec<E>,
+    // extension field witnesses, when committed apart from the base field ones in wits_commit
+    pub ext_wits: Option<ExtWitsOpening<E, PCS>>,
+}
+
+/// commitment and opening of the extension field witness columns of an opcode circuit
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
+pub struct ExtWitsOpening<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
+    /// ids of the witness columns in `commit`, strictly increasing
+    pub wit_ids: Vec<usize>,
+    pub commit: PCS::Commitment,
+    pub opening_proof: PCS::Proof,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
 pub struct ZKVMTableProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     // tower evaluation at layer 1
     pub r_out_evals: Vec<[E; 2]>,
@@ -54,6 +69,8 @@ pub struct ZKVMTableProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>
     pub same_r_sumcheck_proofs: Option<Vec<IOPProverMessage<E>>>,
     pub rw_in_evals: Vec<E>,
     pub lk_in_evals: Vec<E>,
+    /// multiplicities of the lookup tables declaring a multiplicity bit width, in order
+    pub bounded_lk_multiplicities: Vec<Vec<E::BaseField>>,
 
     pub tower_proof: TowerProofs<E>,
 
@@ -115,7 +132,11 @@ impl PublicValues<u32> {
 /// Map circuit names to
 /// - an opcode or table proof,
 /// - an index unique across both types.
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "E::BaseField: Serialize",
+    deserialize = "E::BaseField: DeserializeOwned, E: DeserializeOwned"
+))]
 pub struct ZKVMProof<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     // TODO preserve in serde only for auxiliary public input
     // other raw value can be construct by verifier directly.
@@ -159,4 +180,76 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProof<E, PCS> {
     pub fn num_circuits(&self) -> usize {
         self.opcode_proofs.len() + self.table_proofs.len()
     }
+
+    /// serialized byte size of each proof component, summed across opcode and table proofs
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        let mut breakdown = ProofSizeBreakdown {
+            public_inputs: serialized_size(&self.raw_pi),
+            evaluations: serialized_size(&self.pi_evals),
+            ..Default::default()
+        };
+        for (_, proof) in self.opcode_proofs.values() {
+            breakdown.tower_proofs += serialized_size(&proof.tower_proof);
+            breakdown.main_sel_sumcheck_proofs += serialized_size(&proof.main_sel_sumcheck_proofs);
+            breakdown.pcs_commitments += serialized_size(&proof.wits_commit);
+            breakdown.pcs_opening_proofs += serialized_size(&proof.wits_opening_proof);
+            if let Some(ext_wits) = &proof.ext_wits {
+                // the column ids tell which witnesses the commitment covers
+                breakdown.pcs_commitments +=
+                    serialized_size(&ext_wits.wit_ids) + serialized_size(&ext_wits.commit);
+                breakdown.pcs_opening_proofs += serialized_size(&ext_wits.opening_proof);
+            }
+            breakdown.evaluations += serialized_size(&proof.record_r_out_evals)
+                + serialized_size(&proof.record_w_out_evals)
+                + serialized_size(&proof.lk_out_evals)
+                + serialized_size(&proof.r_records_in_evals)
+                + serialized_size(&proof.w_records_in_evals)
+                + serialized_size(&proof.lk_records_in_evals)
+                + serialized_size(&proof.wits_in_evals);
+        }
+        for (_, proof) in self.table_proofs.values() {
+            breakdown.tower_proofs += serialized_size(&proof.tower_proof);
+            breakdown.main_sel_sumcheck_proofs += serialized_size(&proof.same_r_sumcheck_proofs);
+            breakdown.pcs_commitments += serialized_size(&proof.wits_commit);
+            breakdown.pcs_opening_proofs += serialized_size(&proof.wits_opening_proof)
+                + serialized_size(&proof.fixed_opening_proof);
+            breakdown.evaluations += serialized_size(&proof.r_out_evals)
+                + serialized_size(&proof.w_out_evals)
+                + serialized_size(&proof.lk_out_evals)
+                + serialized_size(&proof.rw_in_evals)
+                + serialized_size(&proof.lk_in_evals)
+                + serialized_size(&proof.bounded_lk_multiplicities)
+                + serialized_size(&proof.fixed_in_evals)
+                + serialized_size(&proof.wits_in_evals);
+        }
+        breakdown
+    }
+}
+
+/// Serialized byte counts of a [`ZKVMProof`] per component.
+/// Circuit names, indices and other framing are not attributed to any component,
+/// so `total()` is slightly below the size of the serialized proof.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofSizeBreakdown {
+    pub tower_proofs: usize,
+    pub main_sel_sumcheck_proofs: usize,
+    pub pcs_commitments: usize,
+    pub pcs_opening_proofs: usize,
+    pub evaluations: usize,
+    pub public_inputs: usize,
+}
+
+impl ProofSizeBreakdown {
+    pub fn total(&self) -> usize {
+        self.tower_proofs
+            + self.main_sel_sumcheck_proofs
+            + self.pcs_commitments
+            + self.pcs_opening_proofs
+            + self.evaluations
+            + self.public_inputs
+    }
+}
+
+fn serialized_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    bincode::serialized_size(value).expect("proof component is serializable") as usize
 }
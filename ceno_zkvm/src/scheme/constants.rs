@@ -2,7 +2,13 @@ pub(crate) const MIN_PAR_SIZE: usize = 64;
 pub(crate) const MAINCONSTRAIN_SUMCHECK_BATCH_SIZE: usize = 3; // read/write/lookup
 pub(crate) const SEL_DEGREE: usize = 2;
 
+/// alpha, beta
+pub const MIN_NUM_CHALLENGES: usize = 2;
+
 pub const NUM_FANIN: usize = 2;
 pub const NUM_FANIN_LOGUP: usize = 2;
 
 pub const MAX_NUM_VARIABLES: usize = 24;
+
+/// upper bound of the instances a single circuit proof may claim
+pub const MAX_NUM_INSTANCES: usize = 1 << MAX_NUM_VARIABLES;
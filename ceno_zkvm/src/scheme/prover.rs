@@ -1,15 +1,17 @@
 use ff_ext::ExtensionField;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    array,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     sync::Arc,
 };
 
 use ff::Field;
-use itertools::{Itertools, enumerate, izip};
+use itertools::{Itertools, chain, enumerate, izip};
 use mpcs::PolynomialCommitmentScheme;
 use multilinear_extensions::{
-    mle::{IntoMLE, MultilinearExtension},
-    util::ceil_log2,
+    mle::{DenseMultilinearExtension, FieldType, IntoMLE, MultilinearExtension},
+    util::{ceil_log2, max_usable_threads},
     virtual_poly::build_eq_x_r_vec,
     virtual_poly_v2::ArcMultilinearExtension,
 };
@@ -27,31 +29,271 @@ use crate::{
     scheme::{
         constants::{MAINCONSTRAIN_SUMCHECK_BATCH_SIZE, NUM_FANIN, NUM_FANIN_LOGUP},
         utils::{
-            infer_tower_logup_witness, infer_tower_product_witness, interleaving_mles_to_mles,
-            wit_infer_by_expr,
+            ParallelThresholds, eval_by_expr, eval_by_expr_with_fixed, infer_opcode_tower_witness,
+            infer_tower_logup_witness, infer_tower_product_witness, wit_infer_by_expr,
+            wit_infer_linear,
         },
     },
     structs::{
-        Point, ProvingKey, TowerProofs, TowerProver, TowerProverSpec, ZKVMProvingKey, ZKVMWitnesses,
+        Point, ProvingKey, TowerProofs, TowerProver, TowerProverSpec, WitnessColumn,
+        ZKVMProvingKey, ZKVMWitnesses,
+    },
+    utils::{
+        get_challenge_pows, next_pow2_instance_padding, optimal_sumcheck_threads,
+        proper_num_threads_for,
     },
-    utils::{get_challenge_pows, next_pow2_instance_padding, optimal_sumcheck_threads},
     virtual_polys::VirtualPolynomials,
+    witness::RowMajorMatrix,
 };
 
-use super::{PublicValues, ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof};
+use super::{
+    ExtWitsOpening, PublicValues, ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof,
+    verifier::{absorb_up_to_fork, write_wits_commitments},
+};
 
 type ResultCreateTableProof<E, PCS> = (ZKVMTableProof<E, PCS>, HashMap<usize, E>);
 
+/// extension field witness columns of an opcode circuit, committed apart from the base field
+/// ones by [`ZKVMProver::commit_wits_by_field`]
+pub struct ExtWitsCommit<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
+    /// ids of the committed witness columns, strictly increasing
+    pub wit_ids: Vec<usize>,
+    pub commit: PCS::CommitmentWithWitness,
+}
+
 pub struct ZKVMProver<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     pub pk: ZKVMProvingKey<E, PCS>,
+    pub(crate) thresholds: ParallelThresholds,
+    /// commit the witness of opcode circuits mixing base and extension field columns in
+    /// one batch per field type, see [`Self::commit_wits_by_field`], instead of a single
+    /// batch lifted to the extension field
+    pub(crate) split_wits_by_field: bool,
 }
 
 impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
     pub fn new(pk: ZKVMProvingKey<E, PCS>) -> Self {
-        ZKVMProver { pk }
+        Self::new_with_thresholds(pk, ParallelThresholds::from_env())
+    }
+
+    pub fn new_with_thresholds(pk: ZKVMProvingKey<E, PCS>, thresholds: ParallelThresholds) -> Self {
+        ZKVMProver {
+            pk,
+            thresholds,
+            split_wits_by_field: false,
+        }
+    }
+
+    pub fn with_split_wits_by_field(mut self, split_wits_by_field: bool) -> Self {
+        self.split_wits_by_field = split_wits_by_field;
+        self
+    }
+
+    /// fixed commitments of the circuits with fixed columns, sorted by circuit name
+    fn fixed_commits(&self) -> impl Iterator<Item = &PCS::Commitment> {
+        self.pk
+            .circuit_pks
+            .values()
+            .filter_map(|pk| pk.vk.fixed_commit.as_ref())
+    }
+
+    /// commit to the witness columns of an opcode circuit in one batch per field type, since
+    /// a PCS batch must share a single field type. base field columns go to the returned
+    /// commitment and extension field ones to the [`ExtWitsCommit`], written to `transcript`
+    /// in that order. columns sharing one field type are committed in a single batch
+    pub fn commit_wits_by_field(
+        pp: &PCS::ProverParam,
+        witnesses: &[DenseMultilinearExtension<E>],
+        transcript: &mut impl Transcript<E>,
+    ) -> Result<(PCS::CommitmentWithWitness, Option<ExtWitsCommit<E, PCS>>), ZKVMError> {
+        let (ext_wit_ids, base_wit_ids): (Vec<usize>, Vec<usize>) = (0..witnesses.len())
+            .partition(|&i| matches!(witnesses[i].evaluations, FieldType::Ext(_)));
+        if ext_wit_ids.is_empty() || base_wit_ids.is_empty() {
+            let commit = PCS::batch_commit_and_write(pp, witnesses, transcript)
+                .map_err(ZKVMError::PCSError)?;
+            return Ok((commit, None));
+        }
+        let mut batch_commit = |wit_ids: &[usize]| {
+            let mles = wit_ids.iter().map(|&i| witnesses[i].clone()).collect_vec();
+            PCS::batch_commit_and_write(pp, &mles, transcript).map_err(ZKVMError::PCSError)
+        };
+        let base_commit = batch_commit(&base_wit_ids)?;
+        let ext_commit = batch_commit(&ext_wit_ids)?;
+        Ok((
+            base_commit,
+            Some(ExtWitsCommit {
+                wit_ids: ext_wit_ids,
+                commit: ext_commit,
+            }),
+        ))
+    }
+
+    /// commit to the witness columns of a circuit in a single batch. a PCS batch must share
+    /// one field type, thus base field columns mixed with extension field ones are committed
+    /// lifted to the extension field, while the prover keeps working on them in the base field
+    fn batch_commit_witnesses(
+        pp: &PCS::ProverParam,
+        witnesses: &[DenseMultilinearExtension<E>],
+        transcript: &mut impl Transcript<E>,
+    ) -> Result<PCS::CommitmentWithWitness, ZKVMError> {
+        let is_mixed = !witnesses
+            .iter()
+            .map(|w| matches!(w.evaluations, FieldType::Ext(_)))
+            .all_equal();
+        let witnesses = if is_mixed {
+            Cow::Owned(
+                witnesses
+                    .iter()
+                    .map(|w| {
+                        DenseMultilinearExtension::from_evaluations_ext_vec(
+                            w.num_vars,
+                            w.evaluations.iter_ext().collect(),
+                        )
+                    })
+                    .collect_vec(),
+            )
+        } else {
+            Cow::Borrowed(witnesses)
+        };
+        PCS::batch_commit_and_write(pp, &witnesses, transcript).map_err(ZKVMError::PCSError)
+    }
+
+    /// turn unpadded witness columns of `num_instances` rows into the mles
+    /// [`Self::create_opcode_proof`] expects, i.e. zero padded to
+    /// `next_pow2_instance_padding(num_instances)` evaluations.
+    /// base columns stay in the base field
+    pub fn pad_witnesses<'a>(
+        witnesses: Vec<WitnessColumn<E>>,
+        num_instances: usize,
+    ) -> Vec<ArcMultilinearExtension<'a, E>> {
+        let next_pow2_instances = next_pow2_instance_padding(num_instances);
+        witnesses
+            .into_iter()
+            .map(|witness| {
+                assert_eq!(witness.len(), num_instances);
+                let witness: ArcMultilinearExtension<E> =
+                    witness.into_padded_mle(next_pow2_instances).into();
+                witness
+            })
+            .collect()
+    }
+
+    /// estimate in bytes of the tower witness layers materialized while proving a circuit,
+    /// with opcode circuits of `num_instances` instances and table circuits at their fixed
+    /// length, so callers can fail fast instead of running out of memory. circuits are proven
+    /// one after another, thus the peak is the largest estimate among all circuits
+    pub fn estimate_peak_memory(&self, num_instances: usize) -> usize {
+        let log2_num_instances = ceil_log2(next_pow2_instance_padding(num_instances));
+        // layer i of a tower holds `num_mles` mles of 2^i evaluations,
+        // down to the last layer of 2^log2_leaves evaluations in total
+        let tower_size = |log2_leaves: usize, num_mles: usize| num_mles * ((1 << log2_leaves) - 1);
+        self.pk
+            .circuit_pks
+            .values()
+            .map(|pk| pk.get_cs())
+            .map(|cs| {
+                let is_opcode_circuit = cs.lk_table_expressions.is_empty()
+                    && cs.r_table_expressions.is_empty()
+                    && cs.w_table_expressions.is_empty();
+                // logup layers hold [p1, p2, q1, q2]
+                let num_evals = if is_opcode_circuit {
+                    let opcode_tower_size = |count: usize, num_mles: usize| {
+                        tower_size(log2_num_instances + ceil_log2(count), num_mles)
+                    };
+                    opcode_tower_size(cs.r_expressions.len(), NUM_FANIN)
+                        + opcode_tower_size(cs.w_expressions.len(), NUM_FANIN)
+                        + opcode_tower_size(cs.lk_expressions.len(), 2 * NUM_FANIN_LOGUP)
+                } else {
+                    chain!(&cs.r_table_expressions, &cs.w_table_expressions)
+                        .map(|rw| tower_size(ceil_log2(rw.table_spec.len), NUM_FANIN))
+                        .sum::<usize>()
+                        + cs.lk_table_expressions
+                            .iter()
+                            .map(|lk| tower_size(ceil_log2(lk.table_len), 2 * NUM_FANIN_LOGUP))
+                            .sum::<usize>()
+                };
+                num_evals * std::mem::size_of::<E>()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// debug check of the witness of opcode circuit `circuit_name` ahead of proving.
+    /// on each of the first `num_instances` rows every zero constraint must vanish and
+    /// every lookup must hit a row of the lookup tables registered in the proving key.
+    /// returns the first failing instance along with the name of the violated constraint.
+    /// read/write records only balance across circuits, thus they are not checked here
+    pub fn check_witness(
+        &self,
+        circuit_name: &str,
+        witnesses: &[ArcMultilinearExtension<'_, E>],
+        num_instances: usize,
+        challenges: &[E],
+    ) -> Result<(), (usize, String)> {
+        let cs = self
+            .pk
+            .circuit_pks
+            .get(circuit_name)
+            .unwrap_or_else(|| panic!("circuit {circuit_name} not found"))
+            .get_cs();
+        let value_at = |evals: &FieldType<E>, row: usize| match evals {
+            FieldType::Base(evals) => E::from(evals[row]),
+            FieldType::Ext(evals) => evals[row],
+            FieldType::Unreachable => unreachable!(),
+        };
+
+        // every value the lookup tables provide, evaluated over their fixed traces
+        let table_values = self
+            .pk
+            .circuit_pks
+            .values()
+            .filter_map(|pk| Some((pk.get_cs(), pk.fixed_traces.as_ref()?)))
+            .flat_map(|(cs, fixed)| {
+                cs.lk_table_expressions.iter().flat_map(move |table| {
+                    (0..table.table_len).map(move |row| {
+                        let fixed_row = fixed
+                            .iter()
+                            .map(|f| value_at(f.evaluations(), row))
+                            .collect_vec();
+                        eval_by_expr_with_fixed(&fixed_row, &[], challenges, &table.values)
+                            .to_canonical_u64_vec()
+                    })
+                })
+            })
+            .collect::<HashSet<_>>();
+
+        for instance in 0..num_instances {
+            let row = witnesses
+                .iter()
+                .map(|w| value_at(w.evaluations(), instance))
+                .collect_vec();
+            let violated_zero = izip!(
+                chain!(
+                    &cs.assert_zero_expressions,
+                    &cs.assert_zero_sumcheck_expressions
+                ),
+                chain!(
+                    &cs.assert_zero_expressions_namespace_map,
+                    &cs.assert_zero_sumcheck_expressions_namespace_map
+                )
+            )
+            .find(|(expr, _)| eval_by_expr(&row, challenges, expr) != E::ZERO);
+            let missed_lookup = || {
+                izip!(&cs.lk_expressions, &cs.lk_expressions_namespace_map).find(|(expr, _)| {
+                    !table_values
+                        .contains(&eval_by_expr(&row, challenges, expr).to_canonical_u64_vec())
+                })
+            };
+            if let Some((_, name)) = violated_zero.or_else(missed_lookup) {
+                return Err((instance, name.clone()));
+            }
+        }
+        Ok(())
     }
 
     /// create proof for zkvm execution
+    ///
+    /// circuits with zero instances are skipped: neither committed nor proven, they are absent
+    /// from the proof and contribute nothing to the verifier's product and logup checks
     #[tracing::instrument(
         skip_all,
         name = "ZKVM_create_proof",
@@ -64,14 +306,8 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
         pi: PublicValues<u32>,
         mut transcript: impl ForkableTranscript<E>,
     ) -> Result<ZKVMProof<E, PCS>, ZKVMError> {
-        let span = entered_span!("commit_to_fixed_commit", profiling_1 = true);
         let mut vm_proof = ZKVMProof::empty(pi);
 
-        // including raw public input to transcript
-        for v in vm_proof.raw_pi.iter().flatten() {
-            transcript.append_field_element(v);
-        }
-
         let pi: Vec<ArcMultilinearExtension<E>> = vm_proof
             .raw_pi
             .iter()
@@ -81,50 +317,52 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             })
             .collect();
 
-        // commit to fixed commitment
-        for pk in self.pk.circuit_pks.values() {
-            if let Some(fixed_commit) = &pk.vk.fixed_commit {
-                PCS::write_commitment(fixed_commit, &mut transcript)
-                    .map_err(ZKVMError::PCSError)?;
-            }
-        }
-        exit_span!(span);
-
-        // commit to main traces
         let mut commitments = BTreeMap::new();
+        let mut ext_commitments = BTreeMap::new();
         let mut wits = BTreeMap::new();
 
         let commit_to_traces_span = entered_span!("commit_to_traces", profiling_1 = true);
-        // commit to opcode circuits first and then commit to table circuits, sorted by name
-        for (circuit_name, witness) in witnesses.into_iter_sorted() {
-            let num_instances = witness.num_instances();
-            let span = entered_span!(
-                "commit to iteration",
-                circuit_name = circuit_name,
-                profiling_2 = true
-            );
-            let witness = match num_instances {
-                0 => vec![],
-                _ => {
-                    let witness = witness.into_mles();
-                    commitments.insert(
-                        circuit_name.clone(),
-                        PCS::batch_commit_and_write(&self.pk.pp, &witness, &mut transcript)
-                            .map_err(ZKVMError::PCSError)?,
+        // including raw public input and fixed commitments to transcript, then commit to main
+        // traces: opcode circuits first and then table circuits, sorted by name
+        let challenges = absorb_up_to_fork::<E, PCS, _>(
+            &vm_proof.raw_pi,
+            self.fixed_commits(),
+            self.pk.num_challenges(),
+            &mut transcript,
+            |transcript| {
+                for (circuit_name, num_instances, witness) in witnesses.into_mles_sorted() {
+                    let span = entered_span!(
+                        "commit to iteration",
+                        circuit_name = circuit_name,
+                        profiling_2 = true
                     );
-                    witness
+                    if num_instances > 0 {
+                        let split_wits_by_field = self.split_wits_by_field
+                            && self.pk.circuit_pks.get(&circuit_name).is_some_and(|pk| {
+                                let cs = pk.get_cs();
+                                cs.lk_table_expressions.is_empty()
+                                    && cs.r_table_expressions.is_empty()
+                                    && cs.w_table_expressions.is_empty()
+                            });
+                        let wits_commit = if split_wits_by_field {
+                            let (wits_commit, ext_wits_commit) =
+                                Self::commit_wits_by_field(&self.pk.pp, &witness, transcript)?;
+                            if let Some(ext_wits_commit) = ext_wits_commit {
+                                ext_commitments.insert(circuit_name.clone(), ext_wits_commit);
+                            }
+                            wits_commit
+                        } else {
+                            Self::batch_commit_witnesses(&self.pk.pp, &witness, transcript)?
+                        };
+                        commitments.insert(circuit_name.clone(), wits_commit);
+                    }
+                    exit_span!(span);
+                    wits.insert(circuit_name, (witness, num_instances));
                 }
-            };
-            exit_span!(span);
-            wits.insert(circuit_name, (witness, num_instances));
-        }
+                Ok(())
+            },
+        )?;
         exit_span!(commit_to_traces_span);
-
-        // squeeze two challenges from transcript
-        let challenges = [
-            transcript.read_challenge().elements,
-            transcript.read_challenge().elements,
-        ];
         tracing::debug!("challenges in prover: {:?}", challenges);
 
         let main_proofs_span = entered_span!("main_proofs", profiling_1 = true);
@@ -163,6 +401,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                     pk,
                     witness.into_iter().map(|w| w.into()).collect_vec(),
                     wits_commit,
+                    ext_commitments.remove(circuit_name),
                     &pi,
                     num_instances,
                     transcript,
@@ -204,7 +443,69 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
 
         Ok(vm_proof)
     }
+
+    /// re-prove opcode circuit `circuit_name` of `vm_proof` from its `witness` alone, e.g.
+    /// while iterating on a single circuit. the transcript state shared by all circuits is
+    /// replayed from `transcript`, in the state [`Self::create_proof`] received it, and the
+    /// public values and commitments in `vm_proof`, with the commitment to `witness` taking
+    /// the place of the circuit's own. the result replaces the circuit's proof in `vm_proof`,
+    /// which only verifies as long as the other circuits were proven on the same transcript
+    pub fn reprove_opcode_circuit(
+        &self,
+        circuit_name: &str,
+        witness: RowMajorMatrix<E::BaseField>,
+        vm_proof: &ZKVMProof<E, PCS>,
+        mut transcript: impl ForkableTranscript<E>,
+    ) -> Result<ZKVMOpcodeProof<E, PCS>, ZKVMError> {
+        let pk = self
+            .pk
+            .circuit_pks
+            .get(circuit_name)
+            .ok_or(ZKVMError::VKNotFound(circuit_name.to_string()))?;
+        if witness.num_instances() == 0 {
+            return Err(ZKVMError::EmptyCircuit(circuit_name.to_string()));
+        }
+        if !vm_proof.opcode_proofs.contains_key(circuit_name) {
+            return Err(ZKVMError::WitnessNotFound(circuit_name.to_string()));
+        }
+        let num_instances = witness.num_instances();
+        let witness = witness.into_mles();
+        let wits_commit = PCS::batch_commit(&self.pk.pp, &witness).map_err(ZKVMError::PCSError)?;
+
+        // replay the transcript up to the fork in the order of create_proof
+        let challenges = absorb_up_to_fork::<E, PCS, _>(
+            &vm_proof.raw_pi,
+            self.fixed_commits(),
+            self.pk.num_challenges(),
+            &mut transcript,
+            |transcript| {
+                let wits_commit = PCS::get_pure_commitment(&wits_commit);
+                write_wits_commitments(vm_proof, Some((circuit_name, &wits_commit)), transcript)
+            },
+        )?;
+
+        let index = self.pk.transcript_index(circuit_name).unwrap();
+        let mut transcripts = transcript.fork(self.pk.circuit_pks.len());
+        let pi: Vec<ArcMultilinearExtension<E>> = vm_proof
+            .raw_pi
+            .iter()
+            .map(|p| p.to_vec().into_mle().into())
+            .collect_vec();
+        self.create_opcode_proof(
+            circuit_name,
+            &self.pk.pp,
+            pk,
+            witness.into_iter().map(|w| w.into()).collect_vec(),
+            wits_commit,
+            None,
+            &pi,
+            num_instances,
+            &mut transcripts[index],
+            &challenges,
+        )
+    }
     /// create proof giving witness and num_instances
+    /// a circuit without instance has nothing to prove and returns [`ZKVMError::EmptyCircuit`]
     /// major flow break down into
     /// 1: witness layer inferring from input -> output
     /// 2: proof (sumcheck reduce) from output to input
@@ -217,23 +518,39 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
         circuit_pk: &ProvingKey<E, PCS>,
         witnesses: Vec<ArcMultilinearExtension<'_, E>>,
         wits_commit: PCS::CommitmentWithWitness,
+        ext_wits_commit: Option<ExtWitsCommit<E, PCS>>,
         pi: &[ArcMultilinearExtension<'_, E>],
         num_instances: usize,
         transcript: &mut impl Transcript<E>,
-        challenges: &[E; 2],
+        challenges: &[E],
     ) -> Result<ZKVMOpcodeProof<E, PCS>, ZKVMError> {
+        if num_instances == 0 {
+            return Err(ZKVMError::EmptyCircuit(name.to_string()));
+        }
         let cs = circuit_pk.get_cs();
+        let thresholds = self.thresholds;
         let next_pow2_instances = next_pow2_instance_padding(num_instances);
         let log2_num_instances = ceil_log2(next_pow2_instances);
-        let (chip_record_alpha, _) = (challenges[0], challenges[1]);
+        let dummy_lookup_item = cs.dummy_lookup_item(challenges);
 
         // sanity check
-        assert_eq!(witnesses.len(), cs.num_witin as usize);
-        assert!(
-            witnesses
-                .iter()
-                .all(|v| { v.evaluations().len() == next_pow2_instances })
-        );
+        if witnesses.len() != cs.num_witin as usize {
+            return Err(ZKVMError::WitnessCountMismatch {
+                expected: cs.num_witin as usize,
+                got: witnesses.len(),
+            });
+        }
+        if let Some((witness_index, witness)) = witnesses
+            .iter()
+            .enumerate()
+            .find(|(_, v)| v.evaluations().len() != next_pow2_instances)
+        {
+            return Err(ZKVMError::WitnessLengthMismatch {
+                witness_index,
+                expected: next_pow2_instances,
+                got: witness.evaluations().len(),
+            });
+        }
 
         let wit_inference_span = entered_span!("wit_inference", profiling_3 = true);
         // main constraint: read/write record witness inference
@@ -244,8 +561,8 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             .chain(cs.w_expressions.par_iter())
             .chain(cs.lk_expressions.par_iter())
             .map(|expr| {
-                assert_eq!(expr.degree(), 1);
-                wit_infer_by_expr(&[], &witnesses, pi, challenges, expr)
+                assert!(expr.is_linear());
+                wit_infer_linear(&[], &witnesses, pi, challenges, expr, thresholds.wit_infer)
             })
             .collect();
         let (r_records_wit, w_lk_records_wit) = records_wit.split_at(cs.r_expressions.len());
@@ -263,52 +580,20 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             ceil_log2(w_counts_per_instance),
             ceil_log2(lk_counts_per_instance),
         );
-        // process last layer by interleaving all the read/write record respectively
-        // as last layer is the output of sel stage
-        let span = entered_span!("tower_witness_r_last_layer");
-        // TODO optimize last layer to avoid alloc new vector to save memory
-        let r_records_last_layer =
-            interleaving_mles_to_mles(r_records_wit, num_instances, NUM_FANIN, E::ONE);
-        assert_eq!(r_records_last_layer.len(), NUM_FANIN);
-        exit_span!(span);
-
-        // infer all tower witness after last layer
-        let span = entered_span!("tower_witness_r_layers");
-        let r_wit_layers = infer_tower_product_witness(
-            log2_num_instances + log2_r_count,
-            r_records_last_layer,
-            NUM_FANIN,
-        );
-        exit_span!(span);
-
-        let span = entered_span!("tower_witness_w_last_layer");
-        // TODO optimize last layer to avoid alloc new vector to save memory
-        let w_records_last_layer =
-            interleaving_mles_to_mles(w_records_wit, num_instances, NUM_FANIN, E::ONE);
-        assert_eq!(w_records_last_layer.len(), NUM_FANIN);
-        exit_span!(span);
-
-        let span = entered_span!("tower_witness_w_layers");
-        let w_wit_layers = infer_tower_product_witness(
-            log2_num_instances + log2_w_count,
-            w_records_last_layer,
-            NUM_FANIN,
+        // logup tower leaves not taken by a lookup record are padded with dummy items
+        let num_padded_lk_items =
+            (next_pow2_instances << log2_lk_count) - lk_counts_per_instance * num_instances;
+        let (r_wit_layers, w_wit_layers, lk_wit_layers) = infer_opcode_tower_witness(
+            r_records_wit,
+            w_records_wit,
+            lk_records_wit,
+            num_instances,
+            dummy_lookup_item,
+            &thresholds,
         );
-        exit_span!(span);
-
-        let span = entered_span!("tower_witness_lk_last_layer");
-        // TODO optimize last layer to avoid alloc new vector to save memory
-        let lk_records_last_layer =
-            interleaving_mles_to_mles(lk_records_wit, num_instances, NUM_FANIN, chip_record_alpha);
-        assert_eq!(lk_records_last_layer.len(), 2);
-        exit_span!(span);
-
-        let span = entered_span!("tower_witness_lk_layers");
-        let lk_wit_layers = infer_tower_logup_witness(None, lk_records_last_layer);
-        exit_span!(span);
         exit_span!(wit_inference_span);
 
-        if cfg!(test) {
+        if cfg!(any(test, feature = "sanity-check")) {
             // sanity check
             assert_eq!(lk_wit_layers.len(), log2_num_instances + log2_lk_count);
             assert_eq!(r_wit_layers.len(), log2_num_instances + log2_r_count);
@@ -341,19 +626,11 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
         // product constraint tower sumcheck
         let tower_span = entered_span!("tower");
         // final evals for verifier
-        let record_r_out_evals: Vec<E> = r_wit_layers[0]
-            .iter()
-            .map(|w| w.get_ext_field_vec()[0])
-            .collect();
-        let record_w_out_evals: Vec<E> = w_wit_layers[0]
-            .iter()
-            .map(|w| w.get_ext_field_vec()[0])
-            .collect();
-        let lk_p1_out_eval = lk_wit_layers[0][0].get_ext_field_vec()[0];
-        let lk_p2_out_eval = lk_wit_layers[0][1].get_ext_field_vec()[0];
-        let lk_q1_out_eval = lk_wit_layers[0][2].get_ext_field_vec()[0];
-        let lk_q2_out_eval = lk_wit_layers[0][3].get_ext_field_vec()[0];
-        assert!(record_r_out_evals.len() == NUM_FANIN && record_w_out_evals.len() == NUM_FANIN);
+        let record_r_out_evals: [E; NUM_FANIN] =
+            array::from_fn(|i| r_wit_layers[0][i].get_ext_field_vec()[0]);
+        let record_w_out_evals: [E; NUM_FANIN] =
+            array::from_fn(|i| w_wit_layers[0][i].get_ext_field_vec()[0]);
+        let lk_out_evals: [E; 4] = array::from_fn(|i| lk_wit_layers[0][i].get_ext_field_vec()[0]);
         let (rt_tower, tower_proof) = TowerProver::create_proof(
             vec![
                 TowerProverSpec {
@@ -398,7 +675,17 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             rt_tower[..log2_num_instances].to_vec(),
         );
 
-        let num_threads = optimal_sumcheck_threads(log2_num_instances);
+        // one term per record plus one padding term for each of read, write and lookup,
+        // and at least one term per zero check expression
+        let num_threads = proper_num_threads_for(
+            log2_num_instances,
+            r_counts_per_instance
+                + w_counts_per_instance
+                + lk_counts_per_instance
+                + 3
+                + cs.assert_zero_sumcheck_expressions.len(),
+            max_usable_threads(),
+        );
         let alpha_pow = get_challenge_pows(
             MAINCONSTRAIN_SUMCHECK_BATCH_SIZE + cs.assert_zero_sumcheck_expressions.len(),
             transcript,
@@ -466,7 +753,12 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             }
         };
 
-        let mut virtual_polys = VirtualPolynomials::<E>::new(num_threads, log2_num_instances);
+        // one term per record plus one padding term for each of read, write and lookup
+        let mut virtual_polys = VirtualPolynomials::<E>::with_capacity(
+            num_threads,
+            log2_num_instances,
+            r_counts_per_instance + w_counts_per_instance + lk_counts_per_instance + 3,
+        );
 
         let eq_r = build_eq_x_r_vec(&rt_r[..log2_r_count]);
         let eq_w = build_eq_x_r_vec(&rt_w[..log2_w_count]);
@@ -502,11 +794,11 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             // \sum_t (sel(rt, t) * (\sum_i alpha_lk* eq(rs, i) * record_w[i]))
             virtual_polys.add_mle_list(vec![&sel_lk, &lk_records_wit[i]], eq_lk[i] * alpha_lk);
         }
-        // \sum_t alpha_lk * sel(rt, t) * chip_record_alpha * (\sum_i (eq(rs, i)) - 1)
+        // \sum_t alpha_lk * sel(rt, t) * dummy_lookup_item * (\sum_i (eq(rs, i)) - 1)
         virtual_polys.add_mle_list(
             vec![&sel_lk],
             *alpha_lk
-                * chip_record_alpha
+                * dummy_lookup_item
                 * (eq_lk[lk_counts_per_instance..].iter().sum::<E>() - E::ONE),
         );
 
@@ -522,21 +814,27 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                 .zip_eq(cs.assert_zero_sumcheck_expressions_namespace_map.iter())
                 .zip_eq(alpha_pow_iter)
             {
-                // sanity check in debug build and output != instance index for zero check sumcheck poly
-                if cfg!(debug_assertions) {
+                // sanity check with `sanity-check` feature and output != instance index for zero check sumcheck poly
+                if cfg!(any(test, feature = "sanity-check")) {
                     let expected_zero_poly =
                         wit_infer_by_expr(&[], &witnesses, pi, challenges, expr);
-                    let top_100_errors = expected_zero_poly
-                        .get_base_field_vec()
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, v)| **v != E::BaseField::ZERO)
-                        .take(100)
-                        .collect_vec();
+                    let top_100_errors = match expected_zero_poly.evaluations() {
+                        FieldType::Base(evals) => evals
+                            .iter()
+                            .positions(|v| *v != E::BaseField::ZERO)
+                            .take(100)
+                            .collect_vec(),
+                        FieldType::Ext(evals) => evals
+                            .iter()
+                            .positions(|v| *v != E::ZERO)
+                            .take(100)
+                            .collect_vec(),
+                        FieldType::Unreachable => unreachable!(),
+                    };
                     if !top_100_errors.is_empty() {
                         return Err(ZKVMError::InvalidWitness(format!(
                             "degree > 1 zero check virtual poly: expr {name} != 0 on instance indexes: {}...",
-                            top_100_errors.into_iter().map(|(i, _)| i).join(",")
+                            top_100_errors.into_iter().join(",")
                         )));
                     }
                 }
@@ -551,6 +849,9 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             }
         }
 
+        // the verifier derives the sumcheck degree from the constraint system alone
+        #[cfg(any(test, feature = "sanity-check"))]
+        assert_eq!(virtual_polys.degree(), cs.selector_degree());
         tracing::debug!("main sel sumcheck start");
         let (main_sel_sumcheck_proofs, state) = IOPProverStateV2::prove_batch_polys(
             num_threads,
@@ -614,15 +915,57 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             name,
             witnesses.len()
         );
-        let wits_opening_proof = PCS::simple_batch_open(
-            pp,
-            &witnesses,
-            &wits_commit,
-            &input_open_point,
-            wits_in_evals.as_slice(),
-            transcript,
-        )
-        .map_err(ZKVMError::PCSError)?;
+        let (wits_opening_proof, ext_wits) = match ext_wits_commit {
+            None => (
+                PCS::simple_batch_open(
+                    pp,
+                    &witnesses,
+                    &wits_commit,
+                    &input_open_point,
+                    wits_in_evals.as_slice(),
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?,
+                None,
+            ),
+            Some(ExtWitsCommit { wit_ids, commit }) => {
+                let split = |ext: bool| -> (Vec<ArcMultilinearExtension<E>>, Vec<E>) {
+                    izip!(witnesses.iter().cloned(), wits_in_evals.iter().copied())
+                        .enumerate()
+                        .filter(|(i, _)| wit_ids.binary_search(i).is_ok() == ext)
+                        .map(|(_, wit_and_eval)| wit_and_eval)
+                        .unzip()
+                };
+                let (base_wits, base_evals) = split(false);
+                let (ext_wits, ext_evals) = split(true);
+                let base_opening_proof = PCS::simple_batch_open(
+                    pp,
+                    &base_wits,
+                    &wits_commit,
+                    &input_open_point,
+                    &base_evals,
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?;
+                let ext_opening_proof = PCS::simple_batch_open(
+                    pp,
+                    &ext_wits,
+                    &commit,
+                    &input_open_point,
+                    &ext_evals,
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?;
+                (
+                    base_opening_proof,
+                    Some(ExtWitsOpening {
+                        wit_ids,
+                        commit: PCS::get_pure_commitment(&commit),
+                        opening_proof: ext_opening_proof,
+                    }),
+                )
+            }
+        };
         tracing::info!(
             "[opcode {}] build opening proof took {:?}",
             name,
@@ -633,12 +976,10 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
 
         Ok(ZKVMOpcodeProof {
             num_instances,
+            num_padded_lk_items,
             record_r_out_evals,
             record_w_out_evals,
-            lk_p1_out_eval,
-            lk_p2_out_eval,
-            lk_q1_out_eval,
-            lk_q2_out_eval,
+            lk_out_evals,
             tower_proof,
             main_sel_sumcheck_proofs: main_sel_sumcheck_proofs.proofs,
             r_records_in_evals,
@@ -647,6 +988,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             wits_commit,
             wits_opening_proof,
             wits_in_evals,
+            ext_wits,
         })
     }
 
@@ -664,9 +1006,10 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
         wits_commit: PCS::CommitmentWithWitness,
         pi: &[ArcMultilinearExtension<'_, E>],
         transcript: &mut impl Transcript<E>,
-        challenges: &[E; 2],
+        challenges: &[E],
     ) -> Result<ResultCreateTableProof<E, PCS>, ZKVMError> {
         let cs = circuit_pk.get_cs();
+        let thresholds = self.thresholds;
         let fixed = circuit_pk
             .fixed_traces
             .as_ref()
@@ -678,7 +1021,12 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             })
             .unwrap_or_default();
         // sanity check
-        assert_eq!(witnesses.len(), cs.num_witin as usize);
+        if witnesses.len() != cs.num_witin as usize {
+            return Err(ZKVMError::WitnessCountMismatch {
+                expected: cs.num_witin as usize,
+                got: witnesses.len(),
+            });
+        }
         assert_eq!(fixed.len(), cs.num_fixed);
         // check all witness size are power of 2
         assert!(
@@ -713,8 +1061,15 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             )
             .chain(cs.lk_table_expressions.par_iter().map(|lk| &lk.values))
             .map(|expr| {
-                assert_eq!(expr.degree(), 1);
-                wit_infer_by_expr(&fixed, &witnesses, pi, challenges, expr)
+                assert!(expr.is_linear());
+                wit_infer_linear(
+                    &fixed,
+                    &witnesses,
+                    pi,
+                    challenges,
+                    expr,
+                    thresholds.wit_infer,
+                )
             })
             .collect();
         let max_log2_num_instance = records_wit.iter().map(|mle| mle.num_vars()).max().unwrap();
@@ -727,6 +1082,19 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
 
         exit_span!(record_span);
 
+        // reveal the multiplicities declaring a bit width ahead of the tower and sumcheck
+        // challenges, so the verifier can range check them and bind them to the committed witness
+        let bounded_lk_multiplicities = izip!(&cs.lk_table_expressions, lk_n_wit.iter())
+            .filter(|(lk, _)| lk.multiplicity_bits.is_some())
+            .map(|(_, multiplicity)| {
+                let values = multiplicity.get_base_field_vec().to_vec();
+                values
+                    .iter()
+                    .for_each(|v| transcript.append_field_element(v));
+                values
+            })
+            .collect_vec();
+
         // infer all tower witness after last layer
         let span = entered_span!("tower_witness_lk_last_layer");
         let mut r_set_last_layer = r_set_wit
@@ -781,25 +1149,35 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
             .into_iter()
             .zip(r_set_wit.iter())
             .map(|(last_layer, origin_mle)| {
-                infer_tower_product_witness(origin_mle.num_vars(), last_layer, NUM_FANIN)
+                infer_tower_product_witness(
+                    origin_mle.num_vars(),
+                    last_layer,
+                    NUM_FANIN,
+                    thresholds.tower,
+                )
             })
             .collect_vec();
         let w_wit_layers = w_set_last_layer
             .into_iter()
             .zip(w_set_wit.iter())
             .map(|(last_layer, origin_mle)| {
-                infer_tower_product_witness(origin_mle.num_vars(), last_layer, NUM_FANIN)
+                infer_tower_product_witness(
+                    origin_mle.num_vars(),
+                    last_layer,
+                    NUM_FANIN,
+                    thresholds.tower,
+                )
             })
             .collect_vec();
         let lk_wit_layers = lk_numerator_last_layer
             .into_iter()
             .zip(lk_denominator_last_layer)
-            .map(|(lk_n, lk_d)| infer_tower_logup_witness(Some(lk_n), lk_d))
+            .map(|(lk_n, lk_d)| infer_tower_logup_witness(Some(lk_n), lk_d, thresholds.tower))
             .collect_vec();
         exit_span!(span);
         exit_span!(wit_inference_span);
 
-        if cfg!(test) {
+        if cfg!(any(test, feature = "sanity-check")) {
             // sanity check
             assert_eq!(r_wit_layers.len(), cs.r_table_expressions.len());
             assert!(
@@ -1098,6 +1476,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProver<E, PCS> {
                 same_r_sumcheck_proofs,
                 rw_in_evals,
                 lk_in_evals,
+                bounded_lk_multiplicities,
                 tower_proof,
                 fixed_in_evals,
                 fixed_opening_proof,
@@ -1143,6 +1522,114 @@ impl<E: ExtensionField> TowerProofs<E> {
     pub fn logup_spec_size(&self) -> usize {
         self.logup_specs_eval.len()
     }
+
+    /// number of eval rounds recorded for each prod spec followed by each logup spec,
+    /// to be cross-checked against the verifier's number of layers - 1
+    pub fn expected_rounds(&self) -> Vec<usize> {
+        self.prod_specs_eval
+            .iter()
+            .chain(self.logup_specs_eval.iter())
+            .map(|spec_evals| spec_evals.len())
+            .collect()
+    }
+
+    /// check the shape of the proof against what the verifier expects.
+    /// `expected_rounds` holds the number of eval layers of each prod spec followed by
+    /// each logup spec, i.e. its number of layers - 1
+    pub fn validate(
+        &self,
+        num_prod_spec: usize,
+        num_logup_spec: usize,
+        num_fanin: usize,
+        expected_rounds: &[usize],
+    ) -> Result<(), ZKVMError> {
+        assert_eq!(expected_rounds.len(), num_prod_spec + num_logup_spec);
+        if self.prod_spec_size() != num_prod_spec || self.logup_spec_size() != num_logup_spec {
+            return Err(ZKVMError::VerifyError(format!(
+                "tower proof has {} prod and {} logup specs, expected {num_prod_spec} and {num_logup_spec}",
+                self.prod_spec_size(),
+                self.logup_spec_size(),
+            )));
+        }
+        let max_rounds = expected_rounds.iter().copied().max().unwrap_or_default();
+        if self.proofs.len() != max_rounds {
+            return Err(ZKVMError::VerifyError(format!(
+                "tower proof has {} sumcheck rounds, expected {max_rounds}",
+                self.proofs.len()
+            )));
+        }
+        let (prod_rounds, logup_rounds) = expected_rounds.split_at(num_prod_spec);
+        let specs = izip!(
+            std::iter::repeat(("prod", num_fanin)),
+            self.prod_specs_eval.iter().enumerate(),
+            prod_rounds
+        )
+        .chain(izip!(
+            // [p1, p2, q1, q2]
+            std::iter::repeat(("logup", 4)),
+            self.logup_specs_eval.iter().enumerate(),
+            logup_rounds
+        ));
+        for ((kind, num_evals), (spec_index, spec_evals), rounds) in specs {
+            if spec_evals.len() != *rounds {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{kind} spec {spec_index} has {} rounds of evals, expected {rounds}",
+                    spec_evals.len()
+                )));
+            }
+            if let Some((round, evals)) = spec_evals
+                .iter()
+                .enumerate()
+                .find(|(_, evals)| evals.len() != num_evals)
+            {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{kind} spec {spec_index} round {round} has {} evals, expected {num_evals}",
+                    evals.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// whether the per round points recorded by the prover are present. they are not
+    /// serialized, so a proof read back from bytes carries none
+    pub fn has_round_points(&self) -> bool {
+        chain!(&self.prod_specs_points, &self.logup_specs_points).any(|points| !points.is_empty())
+    }
+
+    /// compare `point`, the rt' the verifier derived after `round`, with the point recorded
+    /// by the prover for every spec still active in that round.
+    /// only proofs kept in memory since proving are supported: a deserialized proof carries
+    /// no points, see [`Self::has_round_points`], and passes unchecked
+    pub fn check_round_point(
+        &self,
+        round: usize,
+        point: &[E],
+        num_layers: &[usize],
+    ) -> Result<(), ZKVMError> {
+        let (prod_num_layers, logup_num_layers) = num_layers.split_at(self.prod_spec_size());
+        let specs = izip!(
+            std::iter::repeat("prod"),
+            self.prod_specs_points.iter().enumerate(),
+            prod_num_layers
+        )
+        .chain(izip!(
+            std::iter::repeat("logup"),
+            self.logup_specs_points.iter().enumerate(),
+            logup_num_layers
+        ));
+        for (kind, (spec_index, points), num_layers) in specs {
+            if round + 1 >= *num_layers {
+                continue;
+            }
+            if points.get(round).is_some_and(|expected| expected != point) {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{kind} spec {spec_index} round {round} point mismatch"
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Tower Prover
@@ -1154,9 +1641,16 @@ impl TowerProver {
         num_fanin: usize,
         transcript: &mut impl Transcript<E>,
     ) -> (Point<E>, TowerProofs<E>) {
-        // XXX to sumcheck batched product argument with logup, we limit num_product_fanin to 2
-        // TODO mayber give a better naming?
-        assert_eq!(num_fanin, 2);
+        assert!(
+            num_fanin >= 2 && num_fanin.is_power_of_two(),
+            "fanin {num_fanin} is not a power of two"
+        );
+        // logup layers are binary, i.e. [p1, p2, q1, q2], so a tower batching logup
+        // specs with product specs is limited to fanin 2
+        assert!(
+            logup_specs.is_empty() || num_fanin == 2,
+            "logup towers only support fanin 2"
+        );
 
         let mut proofs = TowerProofs::new(prod_specs.len(), logup_specs.len());
         let log_num_fanin = ceil_log2(num_fanin);
@@ -1,4 +1,8 @@
-use std::marker::PhantomData;
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
 
 use ark_std::test_rng;
 use ceno_emul::{
@@ -8,37 +12,59 @@ use ceno_emul::{
 };
 use ff::Field;
 use ff_ext::ExtensionField;
-use goldilocks::GoldilocksExt2;
-use itertools::Itertools;
+use goldilocks::{Goldilocks, GoldilocksExt2};
+use itertools::{Itertools, chain};
 use mpcs::{Basefold, BasefoldDefault, BasefoldRSParams, PolynomialCommitmentScheme};
 use multilinear_extensions::{
-    mle::IntoMLE, util::ceil_log2, virtual_poly_v2::ArcMultilinearExtension,
+    mle::{DenseMultilinearExtension, FieldType, IntoMLE},
+    util::ceil_log2,
+    virtual_poly_v2::ArcMultilinearExtension,
+};
+use tracing::{
+    Subscriber,
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{
+    Registry,
+    layer::{Context, Layer, SubscriberExt},
+};
+use transcript::{
+    BasicTranscript, BasicTranscriptWithStat, Challenge, ForkableTranscript, StatisticRecorder,
+    Transcript,
 };
-use transcript::{BasicTranscript, BasicTranscriptWithStat, StatisticRecorder, Transcript};
 
 use crate::{
-    circuit_builder::CircuitBuilder,
+    circuit_builder::{CircuitBuilder, ConstraintSystem},
     error::ZKVMError,
-    expression::{ToExpr, WitIn},
+    expression::{Expression, ToExpr, WitIn},
     instructions::{
         Instruction,
         riscv::{arith::AddInstruction, ecall::HaltInstruction},
     },
     set_val,
     structs::{
-        PointAndEval, RAMType::Register, TowerProver, TowerProverSpec, ZKVMConstraintSystem,
-        ZKVMFixedTraces, ZKVMWitnesses,
+        FixedCommitStore, Point, PointAndEval, RAMType::Register, TowerProofs, TowerProver,
+        TowerProverSpec, WitnessColumn, ZKVMBuilder, ZKVMConstraintSystem, ZKVMFixedTraces,
+        ZKVMVerifyingKey, ZKVMWitnesses,
     },
-    tables::{ProgramTableCircuit, U16TableCircuit},
+    tables::{ProgramTableCircuit, TableCircuit, U5TableCircuit, U8TableCircuit, U16TableCircuit},
+    test_utils::WitnessRng,
+    utils::next_pow2_instance_padding,
     witness::LkMultiplicity,
 };
 
 use super::{
-    PublicValues,
-    constants::{MAX_NUM_VARIABLES, NUM_FANIN},
+    PublicValues, ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof,
+    constants::{MAX_NUM_INSTANCES, MAX_NUM_VARIABLES, MIN_PAR_SIZE, NUM_FANIN, NUM_FANIN_LOGUP},
     prover::ZKVMProver,
-    utils::infer_tower_product_witness,
-    verifier::{TowerVerify, ZKVMVerifier},
+    utils::{
+        ParallelThresholds, eval_by_expr, infer_opcode_tower_witness, infer_tower_logup_witness,
+        infer_tower_product_witness, wit_infer_by_expr,
+    },
+    verifier::{
+        TowerVerify, TranscriptOp, VerifierOptions, ZKVMVerifier, absorb_up_to_fork,
+        fetch_fixed_commit, num_padded_lk_items, write_opcode_commitments, write_wits_commitments,
+    },
 };
 
 struct TestConfig {
@@ -77,10 +103,11 @@ impl<E: ExtensionField, const L: usize, const RW: usize> Instruction<E> for Test
     fn assign_instance(
         config: &Self::InstructionConfig,
         instance: &mut [E::BaseField],
-        _lk_multiplicity: &mut LkMultiplicity,
+        lk_multiplicity: &mut LkMultiplicity,
         _step: &StepRecord,
     ) -> Result<(), ZKVMError> {
         set_val!(instance, config.reg_id, E::BaseField::ONE);
+        (0..L).for_each(|_| lk_multiplicity.assert_ux::<16>(1));
 
         Ok(())
     }
@@ -147,6 +174,7 @@ fn test_rw_lk_expression_combination() {
                 prover.pk.circuit_pks.get(&name).unwrap(),
                 wits_in,
                 commit,
+                None,
                 &[],
                 num_instances,
                 &mut transcript,
@@ -191,128 +219,1885 @@ fn test_rw_lk_expression_combination() {
     test_rw_lk_expression_combination_inner::<17, 61>();
 }
 
-const PROGRAM_CODE: [ceno_emul::Instruction; 4] = [
-    encode_rv32(ADD, 4, 1, 4, 0),
-    encode_rv32(ECALL, 0, 0, 0, 0),
-    encode_rv32(ECALL, 0, 0, 0, 0),
-    encode_rv32(ECALL, 0, 0, 0, 0),
-];
+struct ZeroCheckTestConfig {
+    pub(crate) a: WitIn,
+    pub(crate) b: WitIn,
+}
 
-#[ignore = "this case is already tested in riscv_example as ecall_halt has only one instance"]
-#[test]
-fn test_single_add_instance_e2e() {
-    type E = GoldilocksExt2;
-    type Pcs = Basefold<GoldilocksExt2, BasefoldRSParams>;
+/// circuit with one degree = 1 zero constraint `a - b = 0`
+/// and one degree > 1 zero constraint `a * (a - 1) = 0`.
+/// `VIOLATE` assigns a witness breaking the degree = 1 constraint only.
+struct ZeroCheckTestCircuit<E: ExtensionField, const VIOLATE: bool> {
+    phantom: PhantomData<E>,
+}
 
-    // set up program
-    let program = Program::new(
-        CENO_PLATFORM.pc_base(),
-        CENO_PLATFORM.pc_base(),
-        PROGRAM_CODE.to_vec(),
-        Default::default(),
-    );
+impl<E: ExtensionField, const VIOLATE: bool> Instruction<E> for ZeroCheckTestCircuit<E, VIOLATE> {
+    type InstructionConfig = ZeroCheckTestConfig;
 
-    let pcs_param = Pcs::setup(1 << MAX_NUM_VARIABLES).expect("Basefold PCS setup");
-    let (pp, vp) = Pcs::trim(pcs_param, 1 << MAX_NUM_VARIABLES).expect("Basefold trim");
-    let mut zkvm_cs = ZKVMConstraintSystem::default();
-    // opcode circuits
-    let add_config = zkvm_cs.register_opcode_circuit::<AddInstruction<E>>();
-    let halt_config = zkvm_cs.register_opcode_circuit::<HaltInstruction<E>>();
-    let u16_range_config = zkvm_cs.register_table_circuit::<U16TableCircuit<E>>();
+    fn name() -> String {
+        "TEST_ZERO_CHECK".into()
+    }
 
-    let prog_config = zkvm_cs.register_table_circuit::<ProgramTableCircuit<E>>();
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let a = cb.create_witin(|| "a");
+        let b = cb.create_witin(|| "b");
+        let record = vec![1.into(), a.expr()];
+        cb.read_record(|| "read", Register, record.clone())?;
+        cb.write_record(|| "write", Register, record)?;
+        cb.assert_ux::<_, _, 16>(|| "a_in_range", a.expr())?;
 
-    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
-    zkvm_fixed_traces.register_opcode_circuit::<AddInstruction<E>>(&zkvm_cs);
-    zkvm_fixed_traces.register_opcode_circuit::<HaltInstruction<E>>(&zkvm_cs);
+        cb.require_equal(|| "a == b", a.expr(), b.expr())?;
+        cb.require_zero(|| "a is bit", a.expr() * (a.expr() - 1))?;
+        assert_eq!(cb.cs.assert_zero_expressions.len(), 1);
+        assert_eq!(cb.cs.assert_zero_sumcheck_expressions.len(), 1);
 
-    zkvm_fixed_traces.register_table_circuit::<U16TableCircuit<E>>(
-        &zkvm_cs,
-        &u16_range_config,
-        &(),
-    );
+        Ok(ZeroCheckTestConfig { a, b })
+    }
 
-    zkvm_fixed_traces.register_table_circuit::<ProgramTableCircuit<E>>(
-        &zkvm_cs,
-        &prog_config,
-        &program,
-    );
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        lk_multiplicity: &mut LkMultiplicity,
+        _step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        set_val!(instance, config.a, E::BaseField::ONE);
+        lk_multiplicity.assert_ux::<16>(1);
+        if VIOLATE {
+            set_val!(instance, config.b, E::BaseField::from(2));
+        } else {
+            set_val!(instance, config.b, E::BaseField::ONE);
+        }
+
+        Ok(())
+    }
+}
+
+struct GammaTestConfig {
+    a: WitIn,
+}
+
+/// circuit referencing a third challenge beyond alpha and beta
+struct GammaTestCircuit<E: ExtensionField> {
+    phantom: PhantomData<E>,
+}
+
+impl<E: ExtensionField> Instruction<E> for GammaTestCircuit<E> {
+    type InstructionConfig = GammaTestConfig;
+
+    fn name() -> String {
+        "TEST_GAMMA".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let gamma = Expression::Challenge(2, 1, E::ONE, E::ZERO);
+        let a = cb.create_witin(|| "a");
+        let record = vec![1.into(), a.expr() * gamma.clone()];
+        cb.read_record(|| "read", Register, record.clone())?;
+        cb.write_record(|| "write", Register, record)?;
+        cb.require_zero(|| "a == 1", (a.expr() - 1) * gamma)?;
+        assert_eq!(cb.cs.num_challenges(), 3);
+
+        Ok(GammaTestConfig { a })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        _lk_multiplicity: &mut LkMultiplicity,
+        _step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        set_val!(instance, config.a, E::BaseField::ONE);
+
+        Ok(())
+    }
+}
+
+struct MergeTestConfig {
+    a: WitIn,
+    b: WitIn,
+}
+
+/// circuit assembled from two separately built constraint systems
+struct MergeTestCircuit<E: ExtensionField> {
+    phantom: PhantomData<E>,
+}
+
+impl<E: ExtensionField> Instruction<E> for MergeTestCircuit<E> {
+    type InstructionConfig = MergeTestConfig;
+
+    fn name() -> String {
+        "TEST_MERGE".into()
+    }
+
+    fn construct_circuit(cb: &mut CircuitBuilder<E>) -> Result<Self::InstructionConfig, ZKVMError> {
+        let a = cb.create_witin(|| "a");
+        let record = vec![1.into(), a.expr()];
+        cb.read_record(|| "read", Register, record.clone())?;
+        cb.write_record(|| "write", Register, record)?;
+        cb.assert_ux::<_, _, 16>(|| "a_in_range", a.expr())?;
+
+        let mut gadget_cs = ConstraintSystem::new(|| "gadget");
+        let mut gadget_cb = CircuitBuilder::new(&mut gadget_cs);
+        let b = gadget_cb.create_witin(|| "b");
+        let record = vec![2.into(), b.expr()];
+        gadget_cb.read_record(|| "read", Register, record.clone())?;
+        gadget_cb.write_record(|| "write", Register, record)?;
+        gadget_cb.require_zero(|| "b is bit", b.expr() * (b.expr() - 1))?;
+
+        let offset = cb.cs.num_witin;
+        cb.cs.merge(gadget_cs)?;
+        assert_eq!(cb.cs.num_witin, 2);
+        assert_eq!(cb.cs.r_expressions.len(), 2);
+        assert_eq!(cb.cs.assert_zero_sumcheck_expressions.len(), 1);
+
+        Ok(MergeTestConfig {
+            a,
+            b: WitIn { id: b.id + offset },
+        })
+    }
+
+    fn assign_instance(
+        config: &Self::InstructionConfig,
+        instance: &mut [E::BaseField],
+        _lk_multiplicity: &mut LkMultiplicity,
+        _step: &StepRecord,
+    ) -> Result<(), ZKVMError> {
+        set_val!(instance, config.a, E::BaseField::ONE);
+        set_val!(instance, config.b, E::BaseField::ONE);
+
+        Ok(())
+    }
+}
+
+/// prove a single opcode circuit over `num_instances` default steps
+fn prove_opcode_circuit<C: Instruction<GoldilocksExt2>>(
+    num_instances: usize,
+) -> Result<
+    (
+        ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+        ZKVMOpcodeProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    ),
+    ZKVMError,
+> {
+    prove_opcode_circuit_with_witnesses::<C>(num_instances, |zkvm_cs, config| {
+        let mut zkvm_witness = ZKVMWitnesses::default();
+        zkvm_witness
+            .assign_opcode_circuit::<C>(zkvm_cs, config, vec![StepRecord::default(); num_instances])
+            .unwrap();
+        zkvm_witness
+            .into_iter_sorted()
+            .next()
+            .unwrap()
+            .1
+            .into_mles()
+            .into_iter()
+            .map(|v| v.into())
+            .collect_vec()
+    })
+}
+
+/// prove a single opcode circuit over `num_instances` instances, with the witnesses built by
+/// `witnesses` from the registered constraint system and the circuit config
+fn prove_opcode_circuit_with_witnesses<C: Instruction<GoldilocksExt2>>(
+    num_instances: usize,
+    witnesses: impl FnOnce(
+        &ZKVMConstraintSystem<GoldilocksExt2>,
+        &C::InstructionConfig,
+    ) -> Vec<ArcMultilinearExtension<'static, GoldilocksExt2>>,
+) -> Result<
+    (
+        ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+        ZKVMOpcodeProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    ),
+    ZKVMError,
+> {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // pcs setup
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+
+    // configure
+    let name = C::name();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    let config = zkvm_cs.register_opcode_circuit::<C>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<C>(&zkvm_cs);
 
+    // keygen
     let pk = zkvm_cs
         .clone()
         .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
-        .expect("keygen failed");
+        .unwrap();
     let vk = pk.get_vk();
 
-    // single instance
-    let mut vm = VMState::new(CENO_PLATFORM, program.clone().into());
-    let all_records = vm
-        .iter_until_halt()
-        .collect::<Result<Vec<StepRecord>, _>>()
-        .expect("vm exec failed")
-        .into_iter()
-        .collect::<Vec<_>>();
-    let mut add_records = vec![];
-    let mut halt_records = vec![];
-    all_records.into_iter().for_each(|record| {
-        let kind = record.insn().kind;
-        match kind {
-            ADD => add_records.push(record),
-            ECALL => {
-                if record.rs1().unwrap().value == Platform::ecall_halt() {
-                    halt_records.push(record);
-                }
-            }
-            _ => {}
-        }
-    });
-    assert_eq!(add_records.len(), 1);
-    assert_eq!(halt_records.len(), 1);
+    // generate witness
+    let wits_in = witnesses(&zkvm_cs, &config);
 
-    // proving
+    // get proof
     let prover = ZKVMProver::new(pk);
-    let verifier = ZKVMVerifier::new(vk);
-    let mut zkvm_witness = ZKVMWitnesses::default();
-    // assign opcode circuits
-    zkvm_witness
-        .assign_opcode_circuit::<AddInstruction<E>>(&zkvm_cs, &add_config, add_records)
-        .unwrap();
-    zkvm_witness
-        .assign_opcode_circuit::<HaltInstruction<E>>(&zkvm_cs, &halt_config, halt_records)
-        .unwrap();
-    zkvm_witness.finalize_lk_multiplicities();
-    zkvm_witness
-        .assign_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_range_config, &())
-        .unwrap();
-    zkvm_witness
-        .assign_table_circuit::<ProgramTableCircuit<E>>(&zkvm_cs, &prog_config, &program)
+    let mut transcript = BasicTranscript::new(b"test");
+    let dense_wits = wits_in
+        .iter()
+        .map(|w| DenseMultilinearExtension {
+            evaluations: w.evaluations().clone(),
+            num_vars: w.num_vars(),
+        })
+        .collect_vec();
+    let commit = Pcs::batch_commit_and_write(&prover.pk.pp, &dense_wits, &mut transcript).unwrap();
+    let challenges = (0..prover.pk.num_challenges())
+        .map(|_| transcript.read_challenge().elements)
+        .collect_vec();
+    let proof = prover.create_opcode_proof(
+        name.as_str(),
+        &prover.pk.pp,
+        prover.pk.circuit_pks.get(&name).unwrap(),
+        wits_in,
+        commit,
+        None,
+        &[],
+        num_instances,
+        &mut transcript,
+        &challenges,
+    )?;
+
+    Ok((vk, proof))
+}
+
+/// verify a proof from [`prove_opcode_circuit`] in isolation
+fn verify_opcode_circuit<C: Instruction<GoldilocksExt2>>(
+    vk: ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    proof: &ZKVMOpcodeProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+) -> Result<Point<GoldilocksExt2>, ZKVMError> {
+    verify_opcode_circuit_with_options::<C>(vk, proof, VerifierOptions::default())
+}
+
+fn verify_opcode_circuit_with_options<C: Instruction<GoldilocksExt2>>(
+    vk: ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    proof: &ZKVMOpcodeProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    options: VerifierOptions,
+) -> Result<Point<GoldilocksExt2>, ZKVMError> {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let name = C::name();
+    let verifier = ZKVMVerifier::new_with_options(vk, options);
+    let mut transcript = BasicTranscript::new(b"test");
+    write_opcode_commitments(proof, &mut transcript)?;
+    let challenges = (0..verifier.vk.num_challenges())
+        .map(|_| transcript.read_challenge().elements)
+        .collect_vec();
+    verifier.verify_opcode_proof(
+        name.as_str(),
+        &verifier.vk.vp,
+        verifier.vk.circuit_vks.get(&name).unwrap(),
+        proof,
+        &[],
+        &mut transcript,
+        NUM_FANIN,
+        &PointAndEval::default(),
+        &challenges,
+    )
+}
+
+/// prove the u5 table circuit on value 3 looked up twice, with its lookup table declaring
+/// `multiplicity_bits`
+fn prove_table_circuit(
+    multiplicity_bits: Option<usize>,
+) -> (
+    ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    ZKVMTableProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+) {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let name = U5TableCircuit::<E>::name();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    let config = zkvm_cs.register_table_circuit::<U5TableCircuit<E>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_table_circuit::<U5TableCircuit<E>>(&zkvm_cs, &config, &());
+    let mut pk = zkvm_cs
+        .clone()
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
         .unwrap();
+    pk.circuit_pks
+        .get_mut(&name)
+        .unwrap()
+        .vk
+        .cs
+        .lk_table_expressions[0]
+        .multiplicity_bits = multiplicity_bits;
+    let vk = pk.get_vk();
 
-    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
-    let transcript = BasicTranscript::new(b"riscv");
-    let zkvm_proof = prover
-        .create_proof(zkvm_witness, pi, transcript)
+    let mut lk_multiplicity = LkMultiplicity::default();
+    lk_multiplicity.assert_ux::<5>(3);
+    lk_multiplicity.assert_ux::<5>(3);
+    let wits_in = U5TableCircuit::<E>::assign_instances(
+        &config,
+        zkvm_cs.get_cs(&name).unwrap().num_witin as usize,
+        &lk_multiplicity.into_finalize_result(),
+        &(),
+    )
+    .unwrap()
+    .into_mles();
+
+    let prover = ZKVMProver::new(pk);
+    let mut transcript = BasicTranscript::new(b"test");
+    let commit = Pcs::batch_commit_and_write(&prover.pk.pp, &wits_in, &mut transcript).unwrap();
+    let challenges = (0..prover.pk.num_challenges())
+        .map(|_| transcript.read_challenge().elements)
+        .collect_vec();
+    let (proof, _) = prover
+        .create_table_proof(
+            &name,
+            &prover.pk.pp,
+            prover.pk.circuit_pks.get(&name).unwrap(),
+            wits_in.into_iter().map(|v| v.into()).collect_vec(),
+            commit,
+            &[],
+            &mut transcript,
+            &challenges,
+        )
         .expect("create_proof failed");
+    (vk, proof)
+}
 
-    let encoded_bin = bincode::serialize(&zkvm_proof).unwrap();
+/// verify a proof from [`prove_table_circuit`] in isolation
+fn verify_table_circuit(
+    vk: ZKVMVerifyingKey<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+    proof: &ZKVMTableProof<GoldilocksExt2, BasefoldDefault<GoldilocksExt2>>,
+) -> Result<Point<GoldilocksExt2>, ZKVMError> {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
 
-    let stat_recorder = StatisticRecorder::default();
-    {
-        let transcript = BasicTranscriptWithStat::new(&stat_recorder, b"riscv");
+    let name = U5TableCircuit::<E>::name();
+    let verifier = ZKVMVerifier::new(vk);
+    let mut transcript = BasicTranscript::new(b"test");
+    Pcs::write_commitment(&proof.wits_commit, &mut transcript).map_err(ZKVMError::PCSError)?;
+    let challenges = (0..verifier.vk.num_challenges())
+        .map(|_| transcript.read_challenge().elements)
+        .collect_vec();
+    verifier.verify_table_proof(
+        &name,
+        &verifier.vk.vp,
+        verifier.vk.circuit_vks.get(&name).unwrap(),
+        &verifier.vk,
+        proof,
+        &[],
+        &[],
+        &mut transcript,
+        NUM_FANIN_LOGUP,
+        &PointAndEval::default(),
+        &challenges,
+    )
+}
+
+fn prove_and_verify_opcode_circuit<C: Instruction<GoldilocksExt2>>(
+    num_instances: usize,
+) -> Result<Point<GoldilocksExt2>, ZKVMError> {
+    let (vk, proof) = prove_opcode_circuit::<C>(num_instances)?;
+    verify_opcode_circuit::<C>(vk, &proof)
+}
+
+#[test]
+fn test_verify_violated_linear_zero_expression() {
+    let result = prove_and_verify_opcode_circuit::<ZeroCheckTestCircuit<GoldilocksExt2, true>>(4);
+    assert!(
+        matches!(result, Err(ZKVMError::VerifyError(msg)) if msg.contains("zero expression")),
+        "verifier must reject a violated degree = 1 zero expression"
+    );
+}
+
+#[test]
+fn test_verify_zero_expressions_with_sumcheck() {
+    // valid witness also exercises the degree > 1 zero expression via the main sel sumcheck
+    prove_and_verify_opcode_circuit::<ZeroCheckTestCircuit<GoldilocksExt2, false>>(4)
+        .expect("verifier failed");
+}
+
+#[test]
+fn test_prove_verify_with_third_challenge() {
+    let (vk, proof) =
+        prove_opcode_circuit::<GammaTestCircuit<GoldilocksExt2>>(4).expect("create_proof failed");
+    assert_eq!(vk.num_challenges(), 3);
+    verify_opcode_circuit::<GammaTestCircuit<GoldilocksExt2>>(vk, &proof).expect("verifier failed");
+}
+
+#[test]
+fn test_prove_verify_merged_constraint_system() {
+    prove_and_verify_opcode_circuit::<MergeTestCircuit<GoldilocksExt2>>(4)
+        .expect("verifier failed");
+}
+
+#[test]
+fn test_selector_degree_matches_prover() {
+    fn check<C: Instruction<GoldilocksExt2>>(expected: usize) {
+        // the prover asserts its main sel sumcheck degree equals `selector_degree` in tests
+        let (vk, proof) = prove_opcode_circuit::<C>(4).expect("create_proof failed");
+        let cs = vk.circuit_vks.get(&C::name()).unwrap().get_cs();
+        assert_eq!(cs.selector_degree(), expected);
+        verify_opcode_circuit::<C>(vk, &proof).expect("verifier failed");
+    }
+
+    type E = GoldilocksExt2;
+    // records only
+    check::<TestCircuit<E, 2, 2>>(2);
+    // `a * (a - 1) = 0` adds a degree 3 term with the selector
+    check::<ZeroCheckTestCircuit<E, false>>(3);
+}
+
+#[test]
+fn test_num_padded_lk_items_matches_verifier() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // prove `L` lookups per instance end to end, so the verifier cross-checks the count
+    fn check<const L: usize>(num_instances: usize) {
+        let param = Pcs::setup(1 << 16).unwrap();
+        let (pp, vp) = Pcs::trim(param, 1 << 16).unwrap();
+        let mut zkvm_cs = ZKVMConstraintSystem::default();
+        let config = zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, L>>();
+        let u16_config = zkvm_cs.register_table_circuit::<U16TableCircuit<E>>();
+        let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+        zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, L>>(&zkvm_cs);
+        zkvm_fixed_traces.register_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_config, &());
+        let pk = zkvm_cs
+            .clone()
+            .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+            .unwrap();
+        let verifier = ZKVMVerifier::new(pk.get_vk());
+
+        let mut zkvm_witness = ZKVMWitnesses::default();
+        zkvm_witness
+            .assign_opcode_circuit::<TestCircuit<E, 2, L>>(&zkvm_cs, &config, vec![
+                StepRecord::default();
+                num_instances
+            ])
+            .unwrap();
+        zkvm_witness.finalize_lk_multiplicities();
+        zkvm_witness
+            .assign_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_config, &())
+            .unwrap();
+        let proof = ZKVMProver::new(pk)
+            .with_split_wits_by_field(split_wits_by_field)
+            .create_proof(
+                zkvm_witness,
+                PublicValues::new(0, 0, 0, 0, 0, vec![0]),
+                BasicTranscript::new(b"test"),
+            )
+            .expect("create_proof failed");
+
+        let (_, opcode_proof) = &proof.opcode_proofs[&TestCircuit::<E, 2, L>::name()];
+        assert_eq!(
+            opcode_proof.num_padded_lk_items,
+            num_padded_lk_items(L, num_instances)
+        );
         assert!(
             verifier
-                .verify_proof(zkvm_proof, transcript)
-                .expect("verify proof return with error"),
+                .verify_proof_halt(proof, BasicTranscript::new(b"test"), false)
+                .expect("verify_proof failed")
         );
     }
-    println!(
-        "encoded zkvm proof size: {}, hash_num: {}",
-        encoded_bin.len(),
-        stat_recorder.into_inner().field_appended_num
-    );
+
+    // 5 instances are padded to 8, and 3 lookups per instance to 4
+    assert_eq!(num_padded_lk_items(1, 5), 8 - 5);
+    assert_eq!(num_padded_lk_items(2, 5), 2 * 8 - 2 * 5);
+    assert_eq!(num_padded_lk_items(3, 5), 4 * 8 - 3 * 5);
+    check::<1>(5);
+    check::<2>(5);
+    check::<3>(5);
 }
 
-/// test various product argument size, starting from minimal leaf size 2
+#[test]
+fn test_estimate_peak_memory() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    let num_instances = 5;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 3>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 3>>(&zkvm_cs);
+    let pk = zkvm_cs.key_gen::<Pcs>(pp, vp, zkvm_fixed_traces).unwrap();
+    let prover = ZKVMProver::new(pk);
+
+    // count the evaluations of the actual tower witness of 2 reads, 2 writes and 3 lookups
+    let mut rng = test_rng();
+    let mut records = |count: usize| -> Vec<ArcMultilinearExtension<E>> {
+        (0..count)
+            .map(|_| {
+                (0..next_pow2_instance_padding(num_instances))
+                    .map(|_| E::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect_vec()
+    };
+    let (r_records, w_records, lk_records) = (records(2), records(2), records(3));
+    let (r_layers, w_layers, lk_layers) = infer_opcode_tower_witness(
+        &r_records,
+        &w_records,
+        &lk_records,
+        num_instances,
+        E::ONE,
+        &ParallelThresholds::default(),
+    );
+    let allocated = [r_layers, w_layers, lk_layers]
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|mle| mle.evaluations().len() * std::mem::size_of::<E>())
+        .sum::<usize>();
+
+    // the estimate only needs to be close enough to fail fast on
+    let estimate = prover.estimate_peak_memory(num_instances);
+    assert!(
+        estimate.abs_diff(allocated) * 10 <= allocated,
+        "estimate {estimate} too far from {allocated}"
+    );
+
+    // a table circuit outweighing the opcode circuit sets the peak, whatever `num_instances`
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 3>>();
+    let u8_config = zkvm_cs.register_table_circuit::<U8TableCircuit<E>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 3>>(&zkvm_cs);
+    zkvm_fixed_traces.register_table_circuit::<U8TableCircuit<E>>(&zkvm_cs, &u8_config, &());
+    let pk = zkvm_cs.key_gen::<Pcs>(pp, vp, zkvm_fixed_traces).unwrap();
+    let prover = ZKVMProver::new(pk);
+
+    // the u8 table has a single logup tower over its 2^8 rows
+    let mut half = || -> ArcMultilinearExtension<E> {
+        (0..1 << 7)
+            .map(|_| E::random(&mut rng))
+            .collect_vec()
+            .into_mle()
+            .into()
+    };
+    let (p, q) = (vec![half(), half()], vec![half(), half()]);
+    let allocated = infer_tower_logup_witness(Some(p), q, MIN_PAR_SIZE)
+        .iter()
+        .flatten()
+        .map(|mle| mle.evaluations().len() * std::mem::size_of::<E>())
+        .sum::<usize>();
+    let estimate = prover.estimate_peak_memory(num_instances);
+    assert!(
+        estimate.abs_diff(allocated) * 10 <= allocated,
+        "estimate {estimate} too far from {allocated}"
+    );
+}
+
+#[test]
+fn test_dummy_lookup_item_agrees() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    let num_instances = 5;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 3>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 3>>(&zkvm_cs);
+    let pk = zkvm_cs.key_gen::<Pcs>(pp, vp, zkvm_fixed_traces).unwrap();
+    let vk = pk.get_vk();
+
+    let name = TestCircuit::<E, 2, 3>::name();
+    let mut rng = test_rng();
+    let challenges = [E::random(&mut rng), E::random(&mut rng)];
+    let prover_item = pk.circuit_pks[&name]
+        .get_cs()
+        .dummy_lookup_item(&challenges);
+    let verifier_cs = vk.circuit_vks[&name].get_cs();
+    assert_eq!(prover_item, verifier_cs.dummy_lookup_item(&challenges));
+    // the lookup record of an all zero item
+    let zero_record = verifier_cs.rlc_chip_record(vec![Expression::Constant(Goldilocks::ZERO)]);
+    assert_eq!(prover_item, eval_by_expr(&[], &challenges, &zero_record));
+
+    // the prover pads the logup tower leaves with exactly the items the verifier discounts
+    let mut records = |count: usize| -> Vec<ArcMultilinearExtension<E>> {
+        (0..count)
+            .map(|_| {
+                (0..next_pow2_instance_padding(num_instances))
+                    .map(|_| E::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect_vec()
+    };
+    let (r_records, w_records, lk_records) = (records(2), records(2), records(3));
+    let (_, _, lk_layers) = infer_opcode_tower_witness(
+        &r_records,
+        &w_records,
+        &lk_records,
+        num_instances,
+        prover_item,
+        &ParallelThresholds::default(),
+    );
+    let num_dummy_items = lk_layers.last().unwrap()[2..]
+        .iter()
+        .flat_map(|q| q.get_ext_field_vec())
+        .filter(|&&q| q == prover_item)
+        .count();
+    assert_eq!(num_dummy_items, num_padded_lk_items(3, num_instances));
+}
+
+#[test]
+fn test_check_witness() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let prover = |with_table: bool| {
+        let param = Pcs::setup(1 << 16).unwrap();
+        let (pp, vp) = Pcs::trim(param, 1 << 16).unwrap();
+        let mut zkvm_cs = ZKVMConstraintSystem::default();
+        let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+        zkvm_cs.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>();
+        zkvm_fixed_traces.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>(&zkvm_cs);
+        if with_table {
+            let config = zkvm_cs.register_table_circuit::<U16TableCircuit<E>>();
+            zkvm_fixed_traces.register_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &config, &());
+        }
+        ZKVMProver::new(zkvm_cs.key_gen::<Pcs>(pp, vp, zkvm_fixed_traces).unwrap())
+    };
+    // columns a and b of 4 instances
+    let witness = |a: [u64; 4], b: [u64; 4]| -> Vec<ArcMultilinearExtension<E>> {
+        [a, b]
+            .into_iter()
+            .map(|column| column.map(Goldilocks::from).to_vec().into_mle().into())
+            .collect()
+    };
+    let name = ZeroCheckTestCircuit::<E, false>::name();
+    let mut rng = test_rng();
+
+    let prover_with_table = prover(true);
+    let challenges = (0..prover_with_table.pk.num_challenges())
+        .map(|_| E::random(&mut rng))
+        .collect_vec();
+    let check = |prover: &ZKVMProver<E, Pcs>, a, b| {
+        prover.check_witness(&name, &witness(a, b), 4, &challenges)
+    };
+    assert_eq!(
+        check(&prover_with_table, [1, 0, 1, 1], [1, 0, 1, 1]),
+        Ok(())
+    );
+
+    let (instance, constraint) = check(&prover_with_table, [1, 0, 1, 1], [1, 0, 2, 1]).unwrap_err();
+    assert_eq!(instance, 2);
+    assert!(constraint.contains("a == b"), "{constraint}");
+
+    let (instance, constraint) = check(&prover_with_table, [1, 2, 1, 1], [1, 2, 1, 1]).unwrap_err();
+    assert_eq!(instance, 1);
+    assert!(constraint.contains("a is bit"), "{constraint}");
+
+    // without the u16 table the range check has nothing to look up in
+    let (instance, constraint) = check(&prover(false), [1, 0, 1, 1], [1, 0, 1, 1]).unwrap_err();
+    assert_eq!(instance, 0);
+    assert!(constraint.contains("a_in_range"), "{constraint}");
+}
+
+#[test]
+fn test_pad_witnesses() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    type C = TestCircuit<E, 2, 2>;
+
+    // 3 unpadded instances with every witness (i.e. reg_id) set to 1
+    let num_instances = 3;
+    let (vk, proof) = prove_opcode_circuit_with_witnesses::<C>(num_instances, |zkvm_cs, _| {
+        let num_witin = zkvm_cs.get_cs(&C::name()).unwrap().num_witin as usize;
+        let columns = vec![WitnessColumn::Base(vec![Goldilocks::ONE; num_instances]); num_witin];
+        let wits_in = ZKVMProver::<E, Pcs>::pad_witnesses(columns, num_instances);
+        assert!(
+            wits_in
+                .iter()
+                .all(|w| w.num_vars() == 2 && w.evaluations().len() == 4)
+        );
+        wits_in
+    })
+    .expect("create_proof failed");
+    verify_opcode_circuit::<C>(vk, &proof).expect("verifier failed");
+}
+
+#[test]
+fn test_pad_witnesses_mixed_field_types() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let mut rng = WitnessRng::<E>::new(b"test_pad_witnesses_mixed_field_types");
+    let mut cs = ConstraintSystem::<E>::new(|| "test");
+    let mut cb = CircuitBuilder::new(&mut cs);
+    let a = cb.create_witin(|| "a");
+    let b = cb.create_witin(|| "b");
+
+    let num_instances = 3;
+    let a_vals = rng.base_vec(num_instances);
+    let b_vals = rng.ext_vec(num_instances);
+    let mixed = ZKVMProver::<E, Pcs>::pad_witnesses(
+        vec![
+            WitnessColumn::Base(a_vals.clone()),
+            WitnessColumn::Ext(b_vals.clone()),
+        ],
+        num_instances,
+    );
+    let all_ext = ZKVMProver::<E, Pcs>::pad_witnesses(
+        vec![
+            WitnessColumn::Ext(a_vals.into_iter().map(E::from).collect_vec()),
+            WitnessColumn::Ext(b_vals),
+        ],
+        num_instances,
+    );
+    assert!(matches!(mixed[0].evaluations(), FieldType::Base(_)));
+    assert!(matches!(mixed[1].evaluations(), FieldType::Ext(_)));
+
+    // base only terms stay in the base field
+    let base_only = wit_infer_by_expr(&[], &mixed, &[], &[], &(a.expr() * a.expr()));
+    assert!(matches!(base_only.evaluations(), FieldType::Base(_)));
+
+    // and are promoted only once combined with an extension column
+    let expr: Expression<E> = a.expr() * a.expr() + a.expr() * b.expr();
+    let res = wit_infer_by_expr(&[], &mixed, &[], &[], &expr);
+    let expected = wit_infer_by_expr(&[], &all_ext, &[], &[], &expr);
+    assert_eq!(res.get_ext_field_vec(), expected.get_ext_field_vec());
+}
+
+#[test]
+fn test_split_wits_commit_by_field() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    type C = ZeroCheckTestCircuit<E, false>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let name = C::name();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<C>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<C>(&zkvm_cs);
+    let pk = zkvm_cs.key_gen::<Pcs>(pp, vp, zkvm_fixed_traces).unwrap();
+    let vk = pk.get_vk();
+    let prover = ZKVMProver::new(pk);
+
+    let num_instances = 4;
+    let prove = |columns: Vec<WitnessColumn<E>>| {
+        let wits_in = columns
+            .into_iter()
+            .map(|column| column.into_padded_mle(next_pow2_instance_padding(num_instances)))
+            .collect_vec();
+        let mut transcript = BasicTranscript::new(b"test");
+        let (commit, ext_commit) =
+            ZKVMProver::<E, Pcs>::commit_wits_by_field(&prover.pk.pp, &wits_in, &mut transcript)
+                .unwrap();
+        let wits_in = wits_in.into_iter().map(|w| w.into()).collect_vec();
+        let challenges = (0..prover.pk.num_challenges())
+            .map(|_| transcript.read_challenge().elements)
+            .collect_vec();
+        prover
+            .create_opcode_proof(
+                name.as_str(),
+                &prover.pk.pp,
+                prover.pk.circuit_pks.get(&name).unwrap(),
+                wits_in,
+                commit,
+                ext_commit,
+                &[],
+                num_instances,
+                &mut transcript,
+                &challenges,
+            )
+            .expect("create_proof failed")
+    };
+    let ones = vec![Goldilocks::ONE; num_instances];
+    let ext_ones = vec![E::ONE; num_instances];
+
+    // columns of a single field type share one batch
+    let proof = prove(vec![
+        WitnessColumn::Ext(ext_ones.clone()),
+        WitnessColumn::Ext(ext_ones.clone()),
+    ]);
+    assert!(proof.ext_wits.is_none());
+    verify_opcode_circuit::<C>(vk.clone(), &proof).expect("verifier failed");
+
+    // mixed columns are committed in a base and an extension batch
+    let mut proof = prove(vec![
+        WitnessColumn::Base(ones),
+        WitnessColumn::Ext(ext_ones),
+    ]);
+    assert_eq!(proof.ext_wits.as_ref().unwrap().wit_ids, vec![1]);
+    verify_opcode_circuit::<C>(vk.clone(), &proof).expect("verifier failed");
+
+    // out of range, empty or all columns covering extension witness ids are rejected
+    for wit_ids in [vec![2], vec![], vec![0, 1]] {
+        proof.ext_wits.as_mut().unwrap().wit_ids = wit_ids;
+        let result = verify_opcode_circuit::<C>(vk.clone(), &proof);
+        assert!(
+            matches!(&result, Err(ZKVMError::VerifyError(msg)) if msg.contains("extension witness ids")),
+            "verifier must reject malformed extension witness ids, got {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_create_proof_mixed_field_witnesses() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    type C = ZeroCheckTestCircuit<E, false>;
+
+    let num_instances = 4;
+    // prove with the columns in `ext_wit_ids` given in the extension field
+    let prove = |ext_wit_ids: &[usize], split_wits_by_field: bool| {
+        let param = Pcs::setup(1 << 16).unwrap();
+        let (pp, vp) = Pcs::trim(param, 1 << 16).unwrap();
+        let mut zkvm_cs = ZKVMConstraintSystem::default();
+        let config = zkvm_cs.register_opcode_circuit::<C>();
+        let u16_config = zkvm_cs.register_table_circuit::<U16TableCircuit<E>>();
+        let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+        zkvm_fixed_traces.register_opcode_circuit::<C>(&zkvm_cs);
+        zkvm_fixed_traces.register_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_config, &());
+        let pk = zkvm_cs
+            .clone()
+            .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+            .unwrap();
+        let verifier = ZKVMVerifier::new(pk.get_vk());
+
+        let num_witin = zkvm_cs.get_cs(&C::name()).unwrap().num_witin as usize;
+        let (witness, lk_multiplicity) = C::assign_instances(&config, num_witin, vec![
+            StepRecord::default();
+            num_instances
+        ])
+        .unwrap();
+        let columns = (0..num_witin)
+            .map(|i| {
+                let column = witness.iter_rows().map(|row| row[i]).collect_vec();
+                if ext_wit_ids.contains(&i) {
+                    WitnessColumn::Ext(column.into_iter().map(E::from).collect_vec())
+                } else {
+                    WitnessColumn::Base(column)
+                }
+            })
+            .collect_vec();
+        let mut zkvm_witness = ZKVMWitnesses::default();
+        zkvm_witness
+            .assign_opcode_columns::<C>(&zkvm_cs, columns, lk_multiplicity)
+            .unwrap();
+        zkvm_witness.finalize_lk_multiplicities();
+        zkvm_witness
+            .assign_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_config, &())
+            .unwrap();
+
+        let proof = ZKVMProver::new(pk)
+            .with_split_wits_by_field(split_wits_by_field)
+            .create_proof(
+                zkvm_witness,
+                PublicValues::new(0, 0, 0, 0, 0, vec![0]),
+                BasicTranscript::new(b"test"),
+            )
+            .expect("create_proof failed");
+        assert!(
+            verifier
+                .verify_proof_halt(proof.clone(), BasicTranscript::new(b"test"), false)
+                .expect("verify_proof failed")
+        );
+        proof
+    };
+
+    // a base column `a` alongside an extension column `b` proves exactly as both in the
+    // extension field
+    let mixed = prove(&[1], false);
+    let all_ext = prove(&[0, 1], false);
+    assert_eq!(
+        bincode::serialize(&mixed).unwrap(),
+        bincode::serialize(&all_ext).unwrap()
+    );
+    assert!(mixed.opcode_proofs[&C::name()].1.ext_wits.is_none());
+
+    // split by field type, the extension column is committed in a batch of its own
+    let split = prove(&[1], true);
+    let (_, opcode_proof) = &split.opcode_proofs[&C::name()];
+    assert_eq!(opcode_proof.ext_wits.as_ref().unwrap().wit_ids, vec![1]);
+    // while a single field type witness stays in one batch
+    let split = prove(&[0, 1], true);
+    assert!(split.opcode_proofs[&C::name()].1.ext_wits.is_none());
+}
+
+#[test]
+fn test_create_proof_rejects_missing_witness() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+    type C = ZeroCheckTestCircuit<E, false>;
+
+    let num_instances = 4;
+    let mut num_witin = 0;
+    let result = prove_opcode_circuit_with_witnesses::<C>(num_instances, |zkvm_cs, _| {
+        num_witin = zkvm_cs.get_cs(&C::name()).unwrap().num_witin as usize;
+        let columns = vec![WitnessColumn::Base(vec![Goldilocks::ONE; num_instances]); num_witin];
+        let mut wits_in = ZKVMProver::<E, Pcs>::pad_witnesses(columns, num_instances);
+        wits_in.pop();
+        wits_in
+    });
+    assert!(matches!(
+        result,
+        Err(ZKVMError::WitnessCountMismatch { expected, got }) if expected == num_witin && got == num_witin - 1
+    ));
+}
+
+#[test]
+fn test_opening_point_matches_verifier() {
+    type E = GoldilocksExt2;
+
+    fn opening_point_matches_verifier<C: Instruction<E>>(num_instances: usize) {
+        let (vk, proof) = prove_opcode_circuit::<C>(num_instances).expect("create_proof failed");
+        let mut transcript = BasicTranscript::new(b"test");
+        write_opcode_commitments(&proof, &mut transcript).unwrap();
+        let challenges = (0..vk.num_challenges())
+            .map(|_| transcript.read_challenge().elements)
+            .collect_vec();
+        let cs = vk.circuit_vks.get(&C::name()).unwrap().get_cs();
+        let opening_point = proof
+            .opening_point(cs, &challenges, &mut transcript)
+            .expect("opening_point failed");
+
+        let verified_point = verify_opcode_circuit::<C>(vk, &proof).expect("verifier failed");
+        assert_eq!(opening_point, verified_point);
+    }
+
+    opening_point_matches_verifier::<ZeroCheckTestCircuit<E, false>>(4);
+    // a single instance with at most one record of each kind
+    opening_point_matches_verifier::<TestCircuit<E, 1, 1>>(1);
+}
+
+#[test]
+fn test_verify_rejects_truncated_record_evals() {
+    type E = GoldilocksExt2;
+    let (vk, mut proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    proof.r_records_in_evals.pop();
+
+    let result = verify_opcode_circuit::<TestCircuit<E, 2, 2>>(vk, &proof);
+    assert!(
+        matches!(result, Err(ZKVMError::VerifyError(msg)) if msg.contains("r_records_in_evals")),
+        "verifier must reject truncated r_records_in_evals"
+    );
+}
+
+#[test]
+fn test_verify_rejects_absurd_num_instances() {
+    type E = GoldilocksExt2;
+    let (vk, mut proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    for num_instances in [MAX_NUM_INSTANCES + 1, usize::MAX] {
+        proof.num_instances = num_instances;
+        let result = verify_opcode_circuit::<TestCircuit<E, 2, 2>>(vk.clone(), &proof);
+        assert!(
+            matches!(&result, Err(ZKVMError::VerifyError(msg)) if msg.contains("instances")),
+            "{result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_verifier_skip_pcs() {
+    type E = GoldilocksExt2;
+    let (vk, mut proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    // same witness, but opened at the point of another circuit's transcript
+    let (_, other_proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 3>>(1 << 4).expect("create_proof failed");
+    proof.wits_opening_proof = other_proof.wits_opening_proof;
+
+    let verify = |skip_pcs: bool| {
+        verify_opcode_circuit_with_options::<TestCircuit<E, 2, 2>>(
+            vk.clone(),
+            &proof,
+            VerifierOptions { skip_pcs },
+        )
+    };
+    assert!(verify(true).is_ok());
+    assert!(matches!(verify(false), Err(ZKVMError::PCSError(_))));
+}
+
+#[test]
+fn test_verify_table_rejects_tampered_multiplicity() {
+    type E = GoldilocksExt2;
+
+    let name = U5TableCircuit::<E>::name();
+    let (vk, mut proof) = prove_table_circuit(None);
+    // the multiplicity is the only witness of the table
+    proof.wits_in_evals[0] += E::ONE;
+
+    let result = verify_table_circuit(vk, &proof);
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg))
+            if msg.contains(&name) && msg.contains("p(x)")),
+        "verifier must reject a tampered table multiplicity, got {result:?}"
+    );
+}
+
+#[test]
+fn test_verify_table_multiplicity_bits() {
+    type E = GoldilocksExt2;
+
+    let name = U5TableCircuit::<E>::name();
+    // value 3 is looked up twice, so its multiplicity takes 2 bits
+    let prove_and_verify = |multiplicity_bits: Option<usize>| {
+        let (vk, proof) = prove_table_circuit(multiplicity_bits);
+        assert_eq!(
+            proof.bounded_lk_multiplicities.len(),
+            multiplicity_bits.is_some() as usize
+        );
+        verify_table_circuit(vk, &proof)
+    };
+
+    assert!(prove_and_verify(None).is_ok());
+    assert!(prove_and_verify(Some(2)).is_ok());
+    let result = prove_and_verify(Some(1));
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg))
+            if msg.contains(&name) && msg.contains("multiplicity 2 exceeds 1 bits")),
+        "verifier must reject an out of range multiplicity, got {result:?}"
+    );
+
+    // in range, but not the committed multiplicities, or only a prefix of them
+    let (vk, proof) = prove_table_circuit(Some(2));
+    let mut tampered = proof.clone();
+    tampered.bounded_lk_multiplicities[0][3] = Goldilocks::ONE;
+    assert!(verify_table_circuit(vk.clone(), &tampered).is_err());
+    let mut truncated = proof;
+    let num_leaves = truncated.bounded_lk_multiplicities[0].len();
+    truncated.bounded_lk_multiplicities[0].truncate(num_leaves / 2);
+    let result = verify_table_circuit(vk, &truncated);
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg))
+            if msg.contains(&name) && msg.contains("multiplicities, expected")),
+        "verifier must reject truncated multiplicities, got {result:?}"
+    );
+}
+
+#[test]
+fn test_opcode_proof_serde_roundtrip() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let (vk, proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    let encoded_bin = bincode::serialize(&proof).unwrap();
+    let decoded: ZKVMOpcodeProof<E, Pcs> = bincode::deserialize(&encoded_bin).unwrap();
+    assert_eq!(bincode::serialize(&decoded).unwrap(), encoded_bin);
+    assert_eq!(decoded.record_r_out_evals, proof.record_r_out_evals);
+    assert_eq!(decoded.record_w_out_evals, proof.record_w_out_evals);
+    assert_eq!(decoded.lk_out_evals, proof.lk_out_evals);
+    verify_opcode_circuit::<TestCircuit<E, 2, 2>>(vk, &decoded).expect("verifier failed");
+}
+
+#[test]
+fn test_proof_size_breakdown() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let name = TestCircuit::<E, 2, 2>::name();
+    let (_, opcode_proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    // a table revealing its multiplicities, one per table row
+    let (_, table_proof) = prove_table_circuit(Some(2));
+    assert!(!table_proof.bounded_lk_multiplicities[0].is_empty());
+    let mut proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0]));
+    proof.opcode_proofs.insert(name, (0, opcode_proof));
+    proof
+        .table_proofs
+        .insert(U5TableCircuit::<E>::name(), (1, table_proof));
+
+    let breakdown = proof.size_breakdown();
+    assert!(breakdown.tower_proofs > 0);
+    assert!(breakdown.main_sel_sumcheck_proofs > 0);
+    assert!(breakdown.pcs_opening_proofs > 0);
+    assert!(breakdown.evaluations > 0);
+
+    // the remainder is map framing, circuit names, indices, num_instances and hints
+    let serialized = bincode::serialized_size(&proof).unwrap() as usize;
+    assert!(breakdown.total() <= serialized);
+    assert!(serialized - breakdown.total() < 128);
+}
+
+/// records the names of all spans created while active
+#[derive(Clone, Default)]
+struct SpanNameRecorder(Arc<Mutex<Vec<String>>>);
+
+impl<S: Subscriber> Layer<S> for SpanNameRecorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(attrs.metadata().name().to_string());
+    }
+}
+
+#[test]
+fn test_verifier_spans() {
+    type E = GoldilocksExt2;
+
+    let (vk, proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+
+    let recorder = SpanNameRecorder::default();
+    let subscriber = Registry::default().with(recorder.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        verify_opcode_circuit::<TestCircuit<E, 2, 2>>(vk, &proof).expect("verifier failed");
+    });
+
+    let span_names = recorder.0.lock().unwrap();
+    for expected in ["verify::tower", "verify::main_sel", "verify::pcs"] {
+        assert!(
+            span_names.iter().any(|name| name == expected),
+            "span {expected} not found in {span_names:?}"
+        );
+    }
+}
+
+#[test]
+fn test_tower_witness_layer_spans() {
+    type E = GoldilocksExt2;
+    let mut rng = test_rng();
+    let mut random_mles = |num_vars: usize| -> Vec<ArcMultilinearExtension<E>> {
+        (0..2)
+            .map(|_| {
+                (0..1 << num_vars)
+                    .map(|_| E::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect_vec()
+    };
+    let (prod_last_layer, logup_q) = (random_mles(4), random_mles(3));
+
+    let recorder = SpanNameRecorder::default();
+    let subscriber = Registry::default().with(recorder.clone());
+    let (prod_layers, logup_layers) = tracing::subscriber::with_default(subscriber, || {
+        (
+            infer_tower_product_witness(5, prod_last_layer, 2, MIN_PAR_SIZE),
+            infer_tower_logup_witness(None, logup_q, MIN_PAR_SIZE),
+        )
+    });
+
+    // one span per inferred layer, the last layer is the input
+    let span_names = recorder.0.lock().unwrap();
+    let count = |name: &str| span_names.iter().filter(|n| *n == name).count();
+    assert_eq!(count("tower_product_layer"), prod_layers.len() - 1);
+    assert_eq!(count("tower_logup_layer"), logup_layers.len() - 1);
+}
+
+#[test]
+fn test_verify_rejects_duplicated_transcript_index() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // vk with two opcode circuits
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 2>>();
+    zkvm_cs.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs);
+    zkvm_fixed_traces.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>(&zkvm_cs);
+    let vk = zkvm_cs
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .unwrap()
+        .get_vk();
+
+    // both circuits claim transcript 0
+    let (_, opcode_proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    let mut proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0]));
+    proof
+        .opcode_proofs
+        .insert(TestCircuit::<E, 2, 2>::name(), (0, opcode_proof.clone()));
+    proof
+        .opcode_proofs
+        .insert(ZeroCheckTestCircuit::<E, false>::name(), (0, opcode_proof));
+
+    let verifier = ZKVMVerifier::new(vk);
+    let result = verifier.verify_proof_halt(proof, BasicTranscript::new(b"test"), false);
+    assert!(
+        matches!(result, Err(ZKVMError::VerifyError(msg)) if msg.contains("transcript index")),
+        "verifier must reject a duplicated transcript index"
+    );
+}
+
+#[test]
+fn test_verify_rejects_circuit_proven_twice() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let name = TestCircuit::<E, 2, 2>::name();
+    let (vk, opcode_proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    let (_, table_proof) = prove_table_circuit(None);
+    let index = vk.transcript_index(&name).unwrap();
+
+    // the opcode circuit also claims a table proof on its own transcript
+    let mut proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0]));
+    proof
+        .opcode_proofs
+        .insert(name.clone(), (index, opcode_proof));
+    proof
+        .table_proofs
+        .insert(name.clone(), (index, table_proof));
+
+    let verifier = ZKVMVerifier::new(vk);
+    let result = verifier.verify_proof_halt(proof, BasicTranscript::new(b"test"), false);
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg))
+            if msg.contains(&name) && msg.contains("proven as a table circuit")),
+        "verifier must reject a circuit proven twice, got {result:?}"
+    );
+}
+
+#[test]
+fn test_verifier_transcript_write_order() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 2>>();
+    zkvm_cs.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>();
+    let u8_config = zkvm_cs.register_table_circuit::<U8TableCircuit<E>>();
+    let u5_config = zkvm_cs.register_table_circuit::<U5TableCircuit<E>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs);
+    zkvm_fixed_traces.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>(&zkvm_cs);
+    zkvm_fixed_traces.register_table_circuit::<U8TableCircuit<E>>(&zkvm_cs, &u8_config, &());
+    zkvm_fixed_traces.register_table_circuit::<U5TableCircuit<E>>(&zkvm_cs, &u5_config, &());
+    let vk = zkvm_cs
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .unwrap()
+        .get_vk();
+
+    // only the table circuits carry fixed commitments, sorted by name
+    let fixed_names = vk
+        .fixed_commit_order()
+        .map(|(name, _)| name.clone())
+        .collect_vec();
+    assert_eq!(fixed_names, vec![
+        U5TableCircuit::<E>::name(),
+        U8TableCircuit::<E>::name()
+    ]);
+
+    let (_, test_proof) =
+        prove_opcode_circuit::<TestCircuit<E, 2, 2>>(1 << 4).expect("create_proof failed");
+    let (_, zero_check_proof) =
+        prove_opcode_circuit::<ZeroCheckTestCircuit<E, false>>(4).expect("create_proof failed");
+    let mut proof = ZKVMProof::<E, Pcs>::empty(PublicValues::new(0, 0, 0, 0, 0, vec![0]));
+    proof.opcode_proofs.insert(
+        ZeroCheckTestCircuit::<E, false>::name(),
+        (3, zero_check_proof),
+    );
+    proof
+        .opcode_proofs
+        .insert(TestCircuit::<E, 2, 2>::name(), (2, test_proof));
+
+    // canonical order: raw public inputs, fixed commits, then opcode wits commits, then table
+    // wits commits
+    let mut expected = BasicTranscript::new(b"test");
+    for v in proof.raw_pi.iter().flatten() {
+        expected.append_field_element(v);
+    }
+    for name in [U5TableCircuit::<E>::name(), U8TableCircuit::<E>::name()] {
+        let fixed_commit = vk.circuit_vks[&name].fixed_commit.as_ref().unwrap();
+        Pcs::write_commitment(fixed_commit, &mut expected).unwrap();
+    }
+    for name in [
+        TestCircuit::<E, 2, 2>::name(),
+        ZeroCheckTestCircuit::<E, false>::name(),
+    ] {
+        Pcs::write_commitment(&proof.opcode_proofs[&name].1.wits_commit, &mut expected).unwrap();
+    }
+
+    let mut transcript = BasicTranscript::new(b"test");
+    let challenges = absorb_up_to_fork::<E, Pcs, _>(
+        &proof.raw_pi,
+        vk.fixed_commit_order().map(|(_, commit)| commit),
+        1,
+        &mut transcript,
+        |transcript| write_wits_commitments(&proof, None, transcript),
+    )
+    .expect("write commitments failed");
+    assert_eq!(challenges, vec![expected.read_challenge().elements]);
+}
+
+/// logs the id of the transcript on every absorb, squeeze or fork. id 0 is the main transcript,
+/// fork `i` gets id `i + 1`
+#[derive(Clone)]
+struct RecordingTranscript<E: ExtensionField> {
+    inner: BasicTranscript<E>,
+    id: usize,
+    log: Arc<Mutex<Vec<usize>>>,
+}
+
+impl<E: ExtensionField> RecordingTranscript<E> {
+    fn record(&self) {
+        self.log.lock().unwrap().push(self.id);
+    }
+}
+
+impl<E: ExtensionField> Transcript<E> for RecordingTranscript<E> {
+    fn append_field_elements(&mut self, elements: &[E::BaseField]) {
+        self.record();
+        self.inner.append_field_elements(elements)
+    }
+
+    fn append_field_element_ext(&mut self, element: &E) {
+        self.record();
+        self.inner.append_field_element_ext(element)
+    }
+
+    fn read_challenge(&mut self) -> Challenge<E> {
+        self.record();
+        self.inner.read_challenge()
+    }
+
+    fn read_field_element_exts(&self) -> Vec<E> {
+        self.inner.read_field_element_exts()
+    }
+
+    fn read_field_element(&self) -> E::BaseField {
+        self.inner.read_field_element()
+    }
+
+    fn send_challenge(&self, challenge: E) {
+        self.inner.send_challenge(challenge)
+    }
+
+    fn commit_rolling(&mut self) {
+        self.inner.commit_rolling()
+    }
+}
+
+impl<E: ExtensionField> ForkableTranscript<E> for RecordingTranscript<E> {
+    fn fork(self, n: usize) -> Vec<Self> {
+        self.record();
+        self.inner
+            .clone()
+            .fork(n)
+            .into_iter()
+            .enumerate()
+            .map(|(i, inner)| RecordingTranscript {
+                inner,
+                id: i + 1,
+                log: self.log.clone(),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_transcript_script_matches_verifier() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    let config = zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 2>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs);
+    let pk = zkvm_cs
+        .clone()
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .unwrap();
+    let vk = pk.get_vk();
+
+    let mut zkvm_witness = ZKVMWitnesses::default();
+    zkvm_witness
+        .assign_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs, &config, vec![
+            StepRecord::default();
+            1 << 4
+        ])
+        .unwrap();
+    let proof = ZKVMProver::new(pk)
+        .create_proof(
+            zkvm_witness,
+            PublicValues::new(0, 0, 0, 0, 0, vec![0]),
+            BasicTranscript::new(b"test"),
+        )
+        .expect("create_proof failed");
+
+    let verifier = ZKVMVerifier::new(vk);
+    let script = verifier.transcript_script(&proof).unwrap();
+    assert!(matches!(
+        script.last(),
+        Some(TranscriptOp::VerifyCircuit { index: 0, .. })
+    ));
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let transcript = RecordingTranscript {
+        inner: BasicTranscript::new(b"test"),
+        id: 0,
+        log: log.clone(),
+    };
+    // without table circuits the logup sum does not balance, but only once every circuit
+    // proof has been verified
+    let _ = verifier.verify_proof_halt(proof, transcript, false);
+
+    let log = log.lock().unwrap();
+    let main_interactions = log.iter().filter(|&&id| id == 0).count();
+    assert_eq!(
+        script
+            .iter()
+            .map(TranscriptOp::num_interactions)
+            .sum::<usize>(),
+        main_interactions
+    );
+    let forks_used = log
+        .iter()
+        .filter(|&&id| id > 0)
+        .map(|id| id - 1)
+        .unique()
+        .collect_vec();
+    let forks_scripted = script
+        .iter()
+        .filter_map(|op| match op {
+            TranscriptOp::VerifyCircuit { index, .. } => Some(*index),
+            _ => None,
+        })
+        .collect_vec();
+    assert_eq!(forks_used, forks_scripted);
+}
+
+#[test]
+fn test_vk_circuit_order_is_deterministic() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    // register the same circuits in opposite orders
+    let key_gen = |tables_first: bool| {
+        let param = Pcs::setup(1 << 13).unwrap();
+        let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+        let mut zkvm_cs = ZKVMConstraintSystem::default();
+        let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+        let mut register_tables = |zkvm_cs: &mut ZKVMConstraintSystem<E>| {
+            let u8_config = zkvm_cs.register_table_circuit::<U8TableCircuit<E>>();
+            let u5_config = zkvm_cs.register_table_circuit::<U5TableCircuit<E>>();
+            zkvm_fixed_traces.register_table_circuit::<U8TableCircuit<E>>(zkvm_cs, &u8_config, &());
+            zkvm_fixed_traces.register_table_circuit::<U5TableCircuit<E>>(zkvm_cs, &u5_config, &());
+        };
+        if tables_first {
+            register_tables(&mut zkvm_cs);
+        }
+        zkvm_cs.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>();
+        zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 2>>();
+        if !tables_first {
+            register_tables(&mut zkvm_cs);
+        }
+        zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs);
+        zkvm_fixed_traces.register_opcode_circuit::<ZeroCheckTestCircuit<E, false>>(&zkvm_cs);
+        zkvm_cs
+            .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+            .unwrap()
+            .get_vk()
+    };
+    let (vk_a, vk_b) = (key_gen(true), key_gen(false));
+
+    let names = |vk: &ZKVMVerifyingKey<E, Pcs>| vk.circuit_vks.keys().cloned().collect_vec();
+    assert_eq!(names(&vk_a), names(&vk_b));
+    assert!(names(&vk_a).is_sorted());
+
+    let fixed_order = |vk: &ZKVMVerifyingKey<E, Pcs>| {
+        vk.fixed_commit_order()
+            .map(|(name, _)| name.clone())
+            .collect_vec()
+    };
+    assert_eq!(fixed_order(&vk_a), fixed_order(&vk_b));
+}
+
+#[test]
+fn test_zkvm_builder_circuit_order() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut builder = ZKVMBuilder::<E>::default();
+    builder.register_opcode_circuit::<HaltInstruction<E>>(ECALL);
+    builder.register_opcode_circuit::<AddInstruction<E>>(ADD);
+    builder.register_table_circuit::<U8TableCircuit<E>>(&());
+    assert_eq!(
+        builder.circuit_name(ADD),
+        Some(&AddInstruction::<E>::name())
+    );
+    assert_eq!(
+        builder.circuit_name(ECALL),
+        Some(&HaltInstruction::<E>::name())
+    );
+
+    let (pk, vk) = builder.key_gen::<Pcs>(pp, vp).unwrap();
+    let pk_names = pk.circuit_pks.keys().cloned().collect_vec();
+    let vk_names = vk.circuit_vks.keys().cloned().collect_vec();
+    assert_eq!(pk_names, vk_names);
+    assert_eq!(pk_names.len(), 3);
+}
+
+struct EmptyFixedCommitStore;
+
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> FixedCommitStore<E, PCS>
+    for EmptyFixedCommitStore
+{
+    fn fixed_commit(&self, _name: &str) -> Option<Cow<'_, PCS::Commitment>> {
+        None
+    }
+}
+
+#[test]
+fn test_fixed_commit_store_miss() {
+    type E = GoldilocksExt2;
+    type Pcs = BasefoldDefault<E>;
+
+    let param = Pcs::setup(1 << 13).unwrap();
+    let (pp, vp) = Pcs::trim(param, 1 << 13).unwrap();
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    zkvm_cs.register_opcode_circuit::<TestCircuit<E, 2, 2>>();
+    let u5_config = zkvm_cs.register_table_circuit::<U5TableCircuit<E>>();
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<TestCircuit<E, 2, 2>>(&zkvm_cs);
+    zkvm_fixed_traces.register_table_circuit::<U5TableCircuit<E>>(&zkvm_cs, &u5_config, &());
+    let vk = zkvm_cs
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .unwrap()
+        .get_vk();
+
+    let u5_name = U5TableCircuit::<E>::name();
+    let u5_cs = vk.circuit_vks[&u5_name].get_cs();
+    let fixed_commit = fetch_fixed_commit(&u5_name, u5_cs, &vk).expect("in-memory store");
+    assert!(fixed_commit.is_some());
+    let result = fetch_fixed_commit::<E, Pcs>(&u5_name, u5_cs, &EmptyFixedCommitStore);
+    assert!(matches!(result, Err(ZKVMError::VKNotFound(name)) if name == u5_name));
+
+    // circuits without fixed columns never consult the store
+    let test_name = TestCircuit::<E, 2, 2>::name();
+    let test_cs = vk.circuit_vks[&test_name].get_cs();
+    let result = fetch_fixed_commit::<E, Pcs>(&test_name, test_cs, &EmptyFixedCommitStore);
+    assert!(matches!(result, Ok(None)));
+}
+
+#[test]
+fn test_verify_proof_with_fixed_commit_store() {
+    let (prover, verifier, zkvm_witness) = single_add_instance_fixture();
+    let proof = prover
+        .create_proof(
+            zkvm_witness,
+            PublicValues::new(0, 0, 0, 0, 0, vec![0]),
+            BasicTranscript::new(b"riscv"),
+        )
+        .expect("create_proof failed");
+
+    // a vk without fixed commitments, which come from the store instead
+    let mut vk = verifier.vk.clone();
+    vk.circuit_vks
+        .values_mut()
+        .for_each(|circuit_vk| circuit_vk.fixed_commit = None);
+    let store_verifier = ZKVMVerifier::new(vk.clone()).with_fixed_commit_store(verifier.vk);
+    assert!(
+        store_verifier
+            .verify_proof(proof.clone(), BasicTranscript::new(b"riscv"))
+            .expect("verify_proof failed")
+    );
+
+    let empty_store_verifier = ZKVMVerifier::new(vk).with_fixed_commit_store(EmptyFixedCommitStore);
+    let result = empty_store_verifier.verify_proof(proof, BasicTranscript::new(b"riscv"));
+    assert!(
+        matches!(&result, Err(ZKVMError::VKNotFound(_))),
+        "a fixed commitment missing from the store must be reported, got {result:?}"
+    );
+}
+
+const PROGRAM_CODE: [ceno_emul::Instruction; 4] = [
+    encode_rv32(ADD, 4, 1, 4, 0),
+    encode_rv32(ECALL, 0, 0, 0, 0),
+    encode_rv32(ECALL, 0, 0, 0, 0),
+    encode_rv32(ECALL, 0, 0, 0, 0),
+];
+
+/// prover, verifier and witness of the single add instance program, with the add and
+/// halt opcode circuits and the u16 range and program tables
+fn single_add_instance_fixture() -> (
+    ZKVMProver<GoldilocksExt2, Basefold<GoldilocksExt2, BasefoldRSParams>>,
+    ZKVMVerifier<GoldilocksExt2, Basefold<GoldilocksExt2, BasefoldRSParams>>,
+    ZKVMWitnesses<GoldilocksExt2>,
+) {
+    add_and_halt_fixture(true)
+}
+
+/// the add and halt circuits assigned from a run of `PROGRAM_CODE`, where the add
+/// circuit is left without instance unless `with_add_instance`
+fn add_and_halt_fixture(
+    with_add_instance: bool,
+) -> (
+    ZKVMProver<GoldilocksExt2, Basefold<GoldilocksExt2, BasefoldRSParams>>,
+    ZKVMVerifier<GoldilocksExt2, Basefold<GoldilocksExt2, BasefoldRSParams>>,
+    ZKVMWitnesses<GoldilocksExt2>,
+) {
+    type E = GoldilocksExt2;
+    type Pcs = Basefold<GoldilocksExt2, BasefoldRSParams>;
+
+    // set up program
+    let program = Program::new(
+        CENO_PLATFORM.pc_base(),
+        CENO_PLATFORM.pc_base(),
+        PROGRAM_CODE.to_vec(),
+        Default::default(),
+    );
+
+    let pcs_param = Pcs::setup(1 << MAX_NUM_VARIABLES).expect("Basefold PCS setup");
+    let (pp, vp) = Pcs::trim(pcs_param, 1 << MAX_NUM_VARIABLES).expect("Basefold trim");
+    let mut zkvm_cs = ZKVMConstraintSystem::default();
+    // opcode circuits
+    let add_config = zkvm_cs.register_opcode_circuit::<AddInstruction<E>>();
+    let halt_config = zkvm_cs.register_opcode_circuit::<HaltInstruction<E>>();
+    let u16_range_config = zkvm_cs.register_table_circuit::<U16TableCircuit<E>>();
+
+    let prog_config = zkvm_cs.register_table_circuit::<ProgramTableCircuit<E>>();
+
+    let mut zkvm_fixed_traces = ZKVMFixedTraces::default();
+    zkvm_fixed_traces.register_opcode_circuit::<AddInstruction<E>>(&zkvm_cs);
+    zkvm_fixed_traces.register_opcode_circuit::<HaltInstruction<E>>(&zkvm_cs);
+
+    zkvm_fixed_traces.register_table_circuit::<U16TableCircuit<E>>(
+        &zkvm_cs,
+        &u16_range_config,
+        &(),
+    );
+
+    zkvm_fixed_traces.register_table_circuit::<ProgramTableCircuit<E>>(
+        &zkvm_cs,
+        &prog_config,
+        &program,
+    );
+
+    let pk = zkvm_cs
+        .clone()
+        .key_gen::<Pcs>(pp, vp, zkvm_fixed_traces)
+        .expect("keygen failed");
+    let vk = pk.get_vk();
+
+    // single instance
+    let mut vm = VMState::new(CENO_PLATFORM, program.clone().into());
+    let all_records = vm
+        .iter_until_halt()
+        .collect::<Result<Vec<StepRecord>, _>>()
+        .expect("vm exec failed")
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mut add_records = vec![];
+    let mut halt_records = vec![];
+    all_records.into_iter().for_each(|record| {
+        let kind = record.insn().kind;
+        match kind {
+            ADD => add_records.push(record),
+            ECALL => {
+                if record.rs1().unwrap().value == Platform::ecall_halt() {
+                    halt_records.push(record);
+                }
+            }
+            _ => {}
+        }
+    });
+    assert_eq!(add_records.len(), 1);
+    assert_eq!(halt_records.len(), 1);
+    if !with_add_instance {
+        add_records.clear();
+    }
+
+    // proving
+    let prover = ZKVMProver::new(pk);
+    let verifier = ZKVMVerifier::new(vk);
+    let mut zkvm_witness = ZKVMWitnesses::default();
+    // assign opcode circuits
+    zkvm_witness
+        .assign_opcode_circuit::<AddInstruction<E>>(&zkvm_cs, &add_config, add_records)
+        .unwrap();
+    zkvm_witness
+        .assign_opcode_circuit::<HaltInstruction<E>>(&zkvm_cs, &halt_config, halt_records)
+        .unwrap();
+    zkvm_witness.finalize_lk_multiplicities();
+    zkvm_witness
+        .assign_table_circuit::<U16TableCircuit<E>>(&zkvm_cs, &u16_range_config, &())
+        .unwrap();
+    zkvm_witness
+        .assign_table_circuit::<ProgramTableCircuit<E>>(&zkvm_cs, &prog_config, &program)
+        .unwrap();
+
+    (prover, verifier, zkvm_witness)
+}
+
+#[ignore = "this case is already tested in riscv_example as ecall_halt has only one instance"]
+#[test]
+fn test_single_add_instance_e2e() {
+    type E = GoldilocksExt2;
+    type Pcs = Basefold<GoldilocksExt2, BasefoldRSParams>;
+
+    let (prover, verifier, zkvm_witness) = single_add_instance_fixture();
+    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
+    let transcript = BasicTranscript::new(b"riscv");
+    let zkvm_proof = prover
+        .create_proof(zkvm_witness, pi, transcript)
+        .expect("create_proof failed");
+
+    let encoded_bin = bincode::serialize(&zkvm_proof).unwrap();
+    // verify the proof as read back from bytes
+    let zkvm_proof: ZKVMProof<E, Pcs> = bincode::deserialize(&encoded_bin).unwrap();
+
+    let stat_recorder = StatisticRecorder::default();
+    {
+        let transcript = BasicTranscriptWithStat::new(&stat_recorder, b"riscv");
+        assert!(
+            verifier
+                .verify_proof(zkvm_proof, transcript)
+                .expect("verify proof return with error"),
+        );
+    }
+    println!(
+        "encoded zkvm proof size: {}, hash_num: {}",
+        encoded_bin.len(),
+        stat_recorder.into_inner().field_appended_num
+    );
+}
+
+#[test]
+fn test_reprove_single_opcode_circuit() {
+    type E = GoldilocksExt2;
+
+    let (prover, verifier, zkvm_witness) = single_add_instance_fixture();
+    let name = AddInstruction::<E>::name();
+    let add_witness = zkvm_witness.get_opcode_witness(&name).unwrap();
+    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
+    let proof = prover
+        .create_proof(
+            zkvm_witness.clone(),
+            pi.clone(),
+            BasicTranscript::new(b"riscv"),
+        )
+        .expect("create_proof failed");
+    assert_eq!(
+        prover.pk.transcript_index(&name),
+        Some(proof.opcode_proofs[&name].0)
+    );
+
+    // re-proving the add circuit alone slots into the full proof
+    let add_proof = prover
+        .reprove_opcode_circuit(&name, add_witness, &proof, BasicTranscript::new(b"riscv"))
+        .expect("reprove failed");
+    let mut spliced = proof.clone();
+    spliced.opcode_proofs.get_mut(&name).unwrap().1 = add_proof;
+    assert!(
+        verifier
+            .verify_proof(spliced, BasicTranscript::new(b"riscv"))
+            .expect("spliced proof must verify")
+    );
+
+    // while the add proof of a run on another transcript is stale
+    let other = prover
+        .create_proof(zkvm_witness, pi, BasicTranscript::new(b"other"))
+        .expect("create_proof failed");
+    let mut stale = proof;
+    stale.opcode_proofs.get_mut(&name).unwrap().1 = other.opcode_proofs[&name].1.clone();
+    assert!(
+        verifier
+            .verify_proof(stale, BasicTranscript::new(b"riscv"))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_verify_proof_with_public_inputs() {
+    type E = GoldilocksExt2;
+
+    let (prover, verifier, zkvm_witness) = single_add_instance_fixture();
+    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
+    let proof = prover
+        .create_proof(zkvm_witness, pi.clone(), BasicTranscript::new(b"riscv"))
+        .expect("create_proof failed");
+    let verify = |proof, public_inputs: &[Vec<Goldilocks>]| {
+        verifier.verify_proof_with_public_inputs(
+            proof,
+            public_inputs,
+            BasicTranscript::new(b"riscv"),
+        )
+    };
+
+    assert!(verify(proof.clone(), &pi.to_vec::<E>()).expect("verify proof return with error"));
+
+    // the verifier expects another public io than the one proven
+    let other_pi = PublicValues::new(0, 0, 0, 0, 0, vec![1]);
+    assert!(verify(proof.clone(), &other_pi.to_vec::<E>()).is_err());
+
+    // the same values split across other entries than the expected ones
+    let mut resplit = proof.clone();
+    let public_io = resplit.raw_pi.pop().unwrap();
+    resplit.raw_pi.last_mut().unwrap().extend(public_io);
+    resplit.raw_pi.push(vec![]);
+    assert!(verify(resplit, &pi.to_vec::<E>()).is_err());
+
+    // public inputs rewritten to the expected ones, evaluations included, pass the checks
+    // on the proof itself and are only caught by the transcript the prover absorbed them in
+    let mut forged = proof;
+    forged.raw_pi = other_pi.to_vec::<E>();
+    for (eval, raw) in forged.pi_evals.iter_mut().zip(&forged.raw_pi) {
+        *eval = E::from(raw[0]);
+    }
+    assert!(verify(forged, &other_pi.to_vec::<E>()).is_err());
+}
+
+#[test]
+fn test_zero_instance_circuit() {
+    type E = GoldilocksExt2;
+
+    let name = AddInstruction::<E>::name();
+    let (prover, verifier, zkvm_witness) = add_and_halt_fixture(false);
+    let empty_witness = zkvm_witness.get_opcode_witness(&name).unwrap();
+    assert_eq!(empty_witness.num_instances(), 0);
+    let pi = PublicValues::new(0, 0, 0, 0, 0, vec![0]);
+
+    // the circuit without instance is left out of the proof, which still verifies
+    let proof = prover
+        .create_proof(zkvm_witness, pi.clone(), BasicTranscript::new(b"riscv"))
+        .expect("create_proof failed");
+    assert!(!proof.opcode_proofs.contains_key(&name));
+    assert!(
+        verifier
+            .verify_proof(proof.clone(), BasicTranscript::new(b"riscv"))
+            .expect("verify proof return with error")
+    );
+
+    // while proving it on its own is an error
+    assert!(matches!(
+        prover.reprove_opcode_circuit(&name, empty_witness, &proof, BasicTranscript::new(b"riscv")),
+        Err(ZKVMError::EmptyCircuit(circuit)) if circuit == name
+    ));
+
+    // and so is a proof claiming zero instances
+    let (prover, verifier, zkvm_witness) = single_add_instance_fixture();
+    let mut proof = prover
+        .create_proof(zkvm_witness, pi, BasicTranscript::new(b"riscv"))
+        .expect("create_proof failed");
+    proof.opcode_proofs.get_mut(&name).unwrap().1.num_instances = 0;
+    assert!(matches!(
+        verifier.verify_proof(proof, BasicTranscript::new(b"riscv")),
+        Err(ZKVMError::EmptyCircuit(circuit)) if circuit == name
+    ));
+}
+
+/// test various product argument size, starting from minimal leaf size 2
 #[test]
 fn test_tower_proof_various_prod_size() {
     fn _test_tower_proof_prod_size_2(leaf_layer_size: usize) {
@@ -332,7 +2117,8 @@ fn test_tower_proof_various_prod_size() {
             first.to_vec().into_mle().into(),
             second.to_vec().into_mle().into(),
         ];
-        let layers = infer_tower_product_witness(num_vars, last_layer_splitted_fanin, 2);
+        let layers =
+            infer_tower_product_witness(num_vars, last_layer_splitted_fanin, 2, MIN_PAR_SIZE);
         let (rt_tower_p, tower_proof) = TowerProver::create_proof(
             vec![TowerProverSpec {
                 witness: layers.clone(),
@@ -371,3 +2157,258 @@ fn test_tower_proof_various_prod_size() {
         _test_tower_proof_prod_size_2(1 << leaf_layer_size);
     }
 }
+
+#[test]
+fn test_tower_proof_fanin_4() {
+    type E = GoldilocksExt2;
+    let mut rng = test_rng();
+    let num_vars = 6;
+    let leaf_layer: ArcMultilinearExtension<E> = (0..1 << num_vars)
+        .map(|_| E::random(&mut rng))
+        .collect_vec()
+        .into_mle()
+        .into();
+
+    // prove and verify the product of all leaves with a tower of fanin `num_fanin`
+    let prove_and_verify = |num_fanin: usize| {
+        let last_layer = leaf_layer
+            .get_ext_field_vec()
+            .chunks(leaf_layer.evaluations().len() / num_fanin)
+            .map(|chunk| chunk.to_vec().into_mle().into())
+            .collect_vec();
+        let layers = infer_tower_product_witness(num_vars, last_layer, num_fanin, MIN_PAR_SIZE);
+        assert_eq!(layers.len(), num_vars / ceil_log2(num_fanin));
+        let out_evals = layers[0]
+            .iter()
+            .flat_map(|mle| mle.get_ext_field_vec().to_vec())
+            .collect_vec();
+
+        let mut transcript = BasicTranscript::new(b"test_tower_proof");
+        let (rt_tower_p, tower_proof) = TowerProver::create_proof(
+            vec![TowerProverSpec { witness: layers }],
+            vec![],
+            num_fanin,
+            &mut transcript,
+        );
+        let mut transcript = BasicTranscript::new(b"test_tower_proof");
+        let (rt_tower_v, prod_point_and_eval, _, _) = TowerVerify::verify(
+            vec![out_evals.clone()],
+            vec![],
+            &tower_proof,
+            vec![num_vars],
+            num_fanin,
+            &mut transcript,
+        )
+        .expect("tower proof must verify");
+        assert_eq!(rt_tower_p, rt_tower_v);
+        assert_eq!(rt_tower_v.len(), num_vars);
+        assert_eq!(
+            leaf_layer.evaluate(&rt_tower_v),
+            prod_point_and_eval[0].eval
+        );
+        out_evals.into_iter().product::<E>()
+    };
+
+    let expected = leaf_layer
+        .get_ext_field_vec()
+        .iter()
+        .copied()
+        .product::<E>();
+    assert_eq!(prove_and_verify(2), expected);
+    assert_eq!(prove_and_verify(4), expected);
+}
+
+/// tower proof over two product specs and one logup spec of different sizes,
+/// so specs end at different rounds
+fn tower_proof_fixture() -> (
+    Vec<Vec<GoldilocksExt2>>,
+    Vec<Vec<GoldilocksExt2>>,
+    TowerProofs<GoldilocksExt2>,
+    Vec<usize>,
+) {
+    type E = GoldilocksExt2;
+    let mut rng = test_rng();
+    let mut random_mles = |num_mles: usize, num_vars: usize| -> Vec<ArcMultilinearExtension<E>> {
+        (0..num_mles)
+            .map(|_| {
+                (0..1 << num_vars)
+                    .map(|_| E::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect_vec()
+    };
+    let prod_layers = [3, 5]
+        .into_iter()
+        .map(|num_vars| {
+            infer_tower_product_witness(num_vars, random_mles(2, num_vars - 1), 2, MIN_PAR_SIZE)
+        })
+        .collect_vec();
+    let logup_layers =
+        infer_tower_logup_witness(Some(random_mles(2, 3)), random_mles(2, 3), MIN_PAR_SIZE);
+
+    let out_evals = |layers: &[Vec<ArcMultilinearExtension<E>>]| {
+        layers[0]
+            .iter()
+            .flat_map(|mle| mle.get_ext_field_vec().to_vec())
+            .collect_vec()
+    };
+    let prod_out_evals = prod_layers.iter().map(|l| out_evals(l)).collect_vec();
+    let logup_out_evals = vec![out_evals(&logup_layers)];
+    let num_variables = chain!(&prod_layers, [&logup_layers])
+        .map(|layers| layers.len())
+        .collect_vec();
+
+    let mut transcript = BasicTranscript::new(b"test_tower_proof");
+    let (_, tower_proof) = TowerProver::create_proof(
+        prod_layers
+            .into_iter()
+            .map(|witness| TowerProverSpec { witness })
+            .collect_vec(),
+        vec![TowerProverSpec {
+            witness: logup_layers,
+        }],
+        2,
+        &mut transcript,
+    );
+    (prod_out_evals, logup_out_evals, tower_proof, num_variables)
+}
+
+#[test]
+fn test_tower_verify_rejects_tampered_evals() {
+    type E = GoldilocksExt2;
+    let (prod_out_evals, logup_out_evals, tower_proof, num_variables) = tower_proof_fixture();
+    let verify = |tower_proof: &TowerProofs<E>| {
+        TowerVerify::verify(
+            prod_out_evals.clone(),
+            logup_out_evals.clone(),
+            tower_proof,
+            num_variables.clone(),
+            2,
+            &mut BasicTranscript::new(b"test_tower_proof"),
+        )
+    };
+    verify(&tower_proof).expect("valid tower proof must verify");
+
+    let assert_rejected = |tampered: &TowerProofs<E>| {
+        assert!(
+            matches!(
+                verify(tampered),
+                Err(ZKVMError::VerifyError(msg)) if msg == "mismatch tower evaluation"
+            ),
+            "tampered tower eval must be rejected"
+        );
+    };
+    for (spec_index, spec_evals) in tower_proof.prod_specs_eval.iter().enumerate() {
+        for (round, evals) in spec_evals.iter().enumerate() {
+            for i in 0..evals.len() {
+                let mut tampered = tower_proof.clone();
+                tampered.prod_specs_eval[spec_index][round][i] += E::ONE;
+                assert_rejected(&tampered);
+            }
+        }
+    }
+    for (spec_index, spec_evals) in tower_proof.logup_specs_eval.iter().enumerate() {
+        for (round, evals) in spec_evals.iter().enumerate() {
+            for i in 0..evals.len() {
+                let mut tampered = tower_proof.clone();
+                tampered.logup_specs_eval[spec_index][round][i] += E::ONE;
+                assert_rejected(&tampered);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tower_verify_rejects_mismatched_point() {
+    type E = GoldilocksExt2;
+    let (prod_out_evals, logup_out_evals, tower_proof, num_variables) = tower_proof_fixture();
+    let verify = |tower_proof: &TowerProofs<E>| {
+        TowerVerify::verify(
+            prod_out_evals.clone(),
+            logup_out_evals.clone(),
+            tower_proof,
+            num_variables.clone(),
+            2,
+            &mut BasicTranscript::new(b"test_tower_proof"),
+        )
+    };
+    let mut tampered = tower_proof.clone();
+    tampered.prod_specs_points[1][2][0] += E::ONE;
+    let result = verify(&tampered);
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg)) if msg == "prod spec 1 round 2 point mismatch"),
+        "{:?}",
+        result.err()
+    );
+
+    // points are not serialized, so a deserialized proof cannot be cross-checked
+    assert!(tampered.has_round_points());
+    let decoded: TowerProofs<E> =
+        bincode::deserialize(&bincode::serialize(&tampered).unwrap()).unwrap();
+    assert!(!decoded.has_round_points());
+    verify(&decoded).expect("deserialized tower proof must verify");
+}
+
+#[test]
+fn test_tower_verify_rejects_short_sumcheck_point() {
+    let (prod_out_evals, logup_out_evals, mut tower_proof, num_variables) = tower_proof_fixture();
+    // drop the last message so that round 2 would yield a point of 2 instead of 3 variables
+    tower_proof.proofs[2].pop();
+    let result = TowerVerify::verify(
+        prod_out_evals,
+        logup_out_evals,
+        &tower_proof,
+        num_variables,
+        2,
+        &mut BasicTranscript::new(b"test_tower_proof"),
+    );
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg)) if msg == "tower round 2 sumcheck has 2 messages, expected 3"),
+        "{:?}",
+        result.err()
+    );
+}
+
+#[test]
+fn test_tower_verify_rejects_empty_tower() {
+    type E = GoldilocksExt2;
+    let result = TowerVerify::verify::<E>(
+        vec![],
+        vec![],
+        &TowerProofs::new(0, 0),
+        vec![],
+        2,
+        &mut BasicTranscript::new(b"test_tower_proof"),
+    );
+    assert!(matches!(result, Err(ZKVMError::VerifyError(msg)) if msg == "empty tower"));
+}
+
+#[test]
+fn test_tower_proofs_validate_rejects_short_logup_evals() {
+    type E = GoldilocksExt2;
+    let (prod_out_evals, logup_out_evals, mut tower_proof, num_variables) = tower_proof_fixture();
+    let expected_rounds = num_variables.iter().map(|n| n - 1).collect_vec();
+    assert_eq!(tower_proof.expected_rounds(), expected_rounds);
+    tower_proof
+        .validate(2, 1, 2, &expected_rounds)
+        .expect("valid tower proof must pass validation");
+
+    tower_proof.logup_specs_eval[0][1].pop();
+    let result = tower_proof.validate(2, 1, 2, &expected_rounds);
+    assert!(
+        matches!(&result, Err(ZKVMError::VerifyError(msg)) if msg == "logup spec 0 round 1 has 3 evals, expected 4"),
+        "{result:?}"
+    );
+    // the verifier rejects the malformed proof up front instead of panicking on indexing
+    let result = TowerVerify::verify::<E>(
+        prod_out_evals,
+        logup_out_evals,
+        &tower_proof,
+        num_variables,
+        2,
+        &mut BasicTranscript::new(b"test_tower_proof"),
+    );
+    assert!(matches!(result, Err(ZKVMError::VerifyError(_))));
+}
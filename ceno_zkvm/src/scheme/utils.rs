@@ -18,10 +18,70 @@ use rayon::{
     prelude::ParallelSliceMut,
 };
 
+use sumcheck::macros::{entered_span, exit_span};
+
 use crate::{
-    expression::Expression, scheme::constants::MIN_PAR_SIZE, utils::next_pow2_instance_padding,
+    error::ZKVMError,
+    expression::Expression,
+    scheme::constants::{MIN_PAR_SIZE, NUM_FANIN},
+    utils::next_pow2_instance_padding,
 };
 
+/// minimal number of items per rayon task of the prover's parallel loops. the best values
+/// depend on the machine, so each can be overridden by env without recompiling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParallelThresholds {
+    /// interleaving record mles into the tower's last layer, `CENO_MIN_PAR_INTERLEAVE`
+    pub interleave: usize,
+    /// folding tower witness layers, `CENO_MIN_PAR_TOWER`
+    pub tower: usize,
+    /// inferring record witness from expressions, `CENO_MIN_PAR_WIT_INFER`
+    pub wit_infer: usize,
+}
+
+impl Default for ParallelThresholds {
+    fn default() -> Self {
+        Self {
+            interleave: MIN_PAR_SIZE,
+            tower: MIN_PAR_SIZE,
+            wit_infer: MIN_PAR_SIZE,
+        }
+    }
+}
+
+impl ParallelThresholds {
+    /// defaults, overridden by any of the env vars set to a valid number
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+        let default = Self::default();
+        Self {
+            interleave: var("CENO_MIN_PAR_INTERLEAVE", default.interleave),
+            tower: var("CENO_MIN_PAR_TOWER", default.tower),
+            wit_infer: var("CENO_MIN_PAR_WIT_INFER", default.wit_infer),
+        }
+    }
+
+    pub fn with_interleave(mut self, interleave: usize) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+    pub fn with_tower(mut self, tower: usize) -> Self {
+        self.tower = tower;
+        self
+    }
+
+    pub fn with_wit_infer(mut self, wit_infer: usize) -> Self {
+        self.wit_infer = wit_infer;
+        self
+    }
+}
+
 /// interleaving multiple mles into mles, and num_limbs indicate number of final limbs vector
 /// e.g input [[1,2],[3,4],[5,6],[7,8]], num_limbs=2,log2_per_instance_size=3
 /// output [[1,3,5,7,0,0,0,0],[2,4,6,8,0,0,0,0]]
@@ -30,6 +90,7 @@ pub(crate) fn interleaving_mles_to_mles<'a, E: ExtensionField>(
     num_instances: usize,
     num_limbs: usize,
     default: E,
+    min_par_size: usize,
 ) -> Vec<ArcMultilinearExtension<'a, E>> {
     assert!(num_limbs.is_power_of_two());
     assert!(!mles.is_empty());
@@ -64,7 +125,7 @@ pub(crate) fn interleaving_mles_to_mles<'a, E: ExtensionField>(
                             .unwrap_or(&[])
                             .par_iter()
                             .zip(evaluations.par_chunks_mut(per_instance_size))
-                            .with_min_len(MIN_PAR_SIZE)
+                            .with_min_len(min_par_size)
                             .for_each(|(value, instance)| {
                                 assert_eq!(instance.len(), per_instance_size);
                                 instance[i] = *value;
@@ -74,7 +135,7 @@ pub(crate) fn interleaving_mles_to_mles<'a, E: ExtensionField>(
                             .unwrap_or(&[])
                             .par_iter()
                             .zip(evaluations.par_chunks_mut(per_instance_size))
-                            .with_min_len(MIN_PAR_SIZE)
+                            .with_min_len(min_par_size)
                             .for_each(|(value, instance)| {
                                 assert_eq!(instance.len(), per_instance_size);
                                 instance[i] = E::from(*value);
@@ -88,7 +149,7 @@ pub(crate) fn interleaving_mles_to_mles<'a, E: ExtensionField>(
 }
 
 macro_rules! tower_mle_4 {
-    ($p1:ident, $p2:ident, $q1:ident, $q2:ident, $acc_p:ident, $acc_q:ident, $start_index:ident, $cur_len:ident) => {
+    ($p1:ident, $p2:ident, $q1:ident, $q2:ident, $acc_p:ident, $acc_q:ident, $start_index:ident, $cur_len:ident, $min_par_size:ident) => {
         $q1[$start_index..][..$cur_len]
             .par_iter()
             .zip($q2[$start_index..][..$cur_len].par_iter())
@@ -96,7 +157,7 @@ macro_rules! tower_mle_4 {
             .zip($p2[$start_index..][..$cur_len].par_iter())
             .zip($acc_p.par_iter_mut())
             .zip($acc_q.par_iter_mut())
-            .with_min_len(MIN_PAR_SIZE)
+            .with_min_len($min_par_size)
             .for_each(|(((((q1, q2), p1), p2), p_eval), q_eval)| {
                 *p_eval = *q1 * p2 + *q2 * p1;
                 *q_eval = *q1 * q2;
@@ -109,19 +170,23 @@ macro_rules! tower_mle_4 {
 pub(crate) fn infer_tower_logup_witness<'a, E: ExtensionField>(
     p_mles: Option<Vec<ArcMultilinearExtension<'a, E>>>,
     q_mles: Vec<ArcMultilinearExtension<'a, E>>,
+    min_par_size: usize,
 ) -> Vec<Vec<ArcMultilinearExtension<'a, E>>> {
-    if cfg!(test) {
+    if cfg!(any(test, feature = "sanity-check")) {
         assert_eq!(q_mles.len(), 2);
         assert!(q_mles.iter().map(|q| q.evaluations().len()).all_equal());
     }
     let num_vars = ceil_log2(q_mles[0].evaluations().len());
-    let mut wit_layers = (0..num_vars).fold(vec![(p_mles, q_mles)], |mut acc, _| {
+    let mut wit_layers = Vec::with_capacity(num_vars + 1);
+    wit_layers.push((p_mles, q_mles));
+    for layer in 0..num_vars {
         let (p, q): &(
             Option<Vec<ArcMultilinearExtension<E>>>,
             Vec<ArcMultilinearExtension<E>>,
-        ) = acc.last().unwrap();
+        ) = wit_layers.last().unwrap();
         let (q1, q2) = (&q[0], &q[1]);
         let cur_len = q1.evaluations().len() / 2;
+        let span = entered_span!("tower_logup_layer", layer = layer, size = cur_len);
         let (next_p, next_q): (
             Vec<ArcMultilinearExtension<E>>,
             Vec<ArcMultilinearExtension<E>>,
@@ -143,13 +208,33 @@ pub(crate) fn infer_tower_logup_witness<'a, E: ExtensionField>(
                             FieldType::Ext(p2),
                             FieldType::Ext(q1),
                             FieldType::Ext(q2),
-                        ) => tower_mle_4!(p1, p2, q1, q2, p_evals, q_evals, start_index, cur_len),
+                        ) => tower_mle_4!(
+                            p1,
+                            p2,
+                            q1,
+                            q2,
+                            p_evals,
+                            q_evals,
+                            start_index,
+                            cur_len,
+                            min_par_size
+                        ),
                         (
                             FieldType::Base(p1),
                             FieldType::Base(p2),
                             FieldType::Ext(q1),
                             FieldType::Ext(q2),
-                        ) => tower_mle_4!(p1, p2, q1, q2, p_evals, q_evals, start_index, cur_len),
+                        ) => tower_mle_4!(
+                            p1,
+                            p2,
+                            q1,
+                            q2,
+                            p_evals,
+                            q_evals,
+                            start_index,
+                            cur_len,
+                            min_par_size
+                        ),
                         _ => unreachable!(),
                     };
                 } else {
@@ -159,7 +244,7 @@ pub(crate) fn infer_tower_logup_witness<'a, E: ExtensionField>(
                             .zip(q2[start_index..][..cur_len].par_iter())
                             .zip(p_evals.par_iter_mut())
                             .zip(q_evals.par_iter_mut())
-                            .with_min_len(MIN_PAR_SIZE)
+                            .with_min_len(min_par_size)
                             .for_each(|(((q1, q2), p_res), q_res)| {
                                 // 1 / q1 + 1 / q2 = (q1+q2) / q1*q2
                                 // p is numerator and q is denominator
@@ -172,9 +257,9 @@ pub(crate) fn infer_tower_logup_witness<'a, E: ExtensionField>(
                 (p_evals.into_mle().into(), q_evals.into_mle().into())
             })
             .unzip(); // vec[vec[p1, p2], vec[q1, q2]]
-        acc.push((Some(next_p), next_q));
-        acc
-    });
+        exit_span!(span);
+        wit_layers.push((Some(next_p), next_q));
+    }
     wit_layers.reverse();
     wit_layers
         .into_iter()
@@ -196,73 +281,234 @@ pub(crate) fn infer_tower_logup_witness<'a, E: ExtensionField>(
         .collect_vec()
 }
 
+/// tower witness layers, from the output layer down to the last layer
+pub(crate) type TowerWitness<'a, E> = Vec<Vec<ArcMultilinearExtension<'a, E>>>;
+
+/// infer read, write and lookup tower witness of an opcode circuit from its record witness.
+/// the three towers are independent, thus they are built concurrently
+pub(crate) fn infer_opcode_tower_witness<'a, E: ExtensionField>(
+    r_records_wit: &[ArcMultilinearExtension<'_, E>],
+    w_records_wit: &[ArcMultilinearExtension<'_, E>],
+    lk_records_wit: &[ArcMultilinearExtension<'_, E>],
+    num_instances: usize,
+    dummy_lookup_item: E,
+    thresholds: &ParallelThresholds,
+) -> (
+    TowerWitness<'a, E>,
+    TowerWitness<'a, E>,
+    TowerWitness<'a, E>,
+) {
+    let log2_num_instances = ceil_log2(next_pow2_instance_padding(num_instances));
+    // process last layer by interleaving all the read/write record respectively
+    // as last layer is the output of sel stage
+    // each last layer is freshly allocated on purpose: it is kept as the first tower layer until
+    // the tower proof is done, so its buffer can't be handed back for the next circuit, nor shared
+    // across the r/w/lk towers built concurrently
+    let infer_r = || {
+        let span = entered_span!("tower_witness_r_last_layer");
+        let r_records_last_layer = interleaving_mles_to_mles(
+            r_records_wit,
+            num_instances,
+            NUM_FANIN,
+            E::ONE,
+            thresholds.interleave,
+        );
+        assert_eq!(r_records_last_layer.len(), NUM_FANIN);
+        exit_span!(span);
+
+        // infer all tower witness after last layer
+        let span = entered_span!("tower_witness_r_layers");
+        let r_wit_layers = infer_tower_product_witness(
+            log2_num_instances + ceil_log2(r_records_wit.len()),
+            r_records_last_layer,
+            NUM_FANIN,
+            thresholds.tower,
+        );
+        exit_span!(span);
+        r_wit_layers
+    };
+    let infer_w = || {
+        let span = entered_span!("tower_witness_w_last_layer");
+        let w_records_last_layer = interleaving_mles_to_mles(
+            w_records_wit,
+            num_instances,
+            NUM_FANIN,
+            E::ONE,
+            thresholds.interleave,
+        );
+        assert_eq!(w_records_last_layer.len(), NUM_FANIN);
+        exit_span!(span);
+
+        let span = entered_span!("tower_witness_w_layers");
+        let w_wit_layers = infer_tower_product_witness(
+            log2_num_instances + ceil_log2(w_records_wit.len()),
+            w_records_last_layer,
+            NUM_FANIN,
+            thresholds.tower,
+        );
+        exit_span!(span);
+        w_wit_layers
+    };
+    let infer_lk = || {
+        let span = entered_span!("tower_witness_lk_last_layer");
+        let lk_records_last_layer = interleaving_mles_to_mles(
+            lk_records_wit,
+            num_instances,
+            NUM_FANIN,
+            dummy_lookup_item,
+            thresholds.interleave,
+        );
+        assert_eq!(lk_records_last_layer.len(), 2);
+        exit_span!(span);
+
+        let span = entered_span!("tower_witness_lk_layers");
+        let lk_wit_layers =
+            infer_tower_logup_witness(None, lk_records_last_layer, thresholds.tower);
+        exit_span!(span);
+        lk_wit_layers
+    };
+
+    let (r_wit_layers, (w_wit_layers, lk_wit_layers)) =
+        rayon::join(infer_r, || rayon::join(infer_w, infer_lk));
+    (r_wit_layers, w_wit_layers, lk_wit_layers)
+}
+
 /// infer tower witness from last layer
 pub(crate) fn infer_tower_product_witness<E: ExtensionField>(
     num_vars: usize,
     last_layer: Vec<ArcMultilinearExtension<'_, E>>,
     num_product_fanin: usize,
+    min_par_size: usize,
 ) -> Vec<Vec<ArcMultilinearExtension<'_, E>>> {
     assert!(last_layer.len() == num_product_fanin);
     let log2_num_product_fanin = ceil_log2(num_product_fanin);
-    let mut wit_layers =
-        (0..(num_vars / log2_num_product_fanin) - 1).fold(vec![last_layer], |mut acc, _| {
-            let next_layer = acc.last().unwrap();
-            let cur_len = next_layer[0].evaluations().len() / num_product_fanin;
-            let cur_layer: Vec<ArcMultilinearExtension<E>> = (0..num_product_fanin)
-                .map(|index| {
-                    let mut evaluations = vec![E::ONE; cur_len];
-                    next_layer.iter().for_each(|f| match f.evaluations() {
-                        FieldType::Ext(f) => {
-                            let start: usize = index * cur_len;
-                            f[start..][..cur_len]
-                                .par_iter()
-                                .zip(evaluations.par_iter_mut())
-                                .with_min_len(MIN_PAR_SIZE)
-                                .map(|(v, evaluations)| *evaluations *= *v)
-                                .collect()
-                        }
-                        _ => unreachable!("must be extension field"),
-                    });
-                    evaluations.into_mle().into()
-                })
-                .collect_vec();
-            acc.push(cur_layer);
-            acc
-        });
+    let num_layers = num_vars / log2_num_product_fanin;
+    let mut wit_layers = Vec::with_capacity(num_layers);
+    wit_layers.push(last_layer);
+    for layer in 0..num_layers - 1 {
+        let next_layer = wit_layers.last().unwrap();
+        let cur_len = next_layer[0].evaluations().len() / num_product_fanin;
+        let span = entered_span!("tower_product_layer", layer = layer, size = cur_len);
+        let cur_layer: Vec<ArcMultilinearExtension<E>> = (0..num_product_fanin)
+            .map(|index| {
+                let mut evaluations = vec![E::ONE; cur_len];
+                next_layer.iter().for_each(|f| match f.evaluations() {
+                    FieldType::Ext(f) => {
+                        let start: usize = index * cur_len;
+                        f[start..][..cur_len]
+                            .par_iter()
+                            .zip(evaluations.par_iter_mut())
+                            .with_min_len(min_par_size)
+                            .map(|(v, evaluations)| *evaluations *= *v)
+                            .collect()
+                    }
+                    _ => unreachable!("must be extension field"),
+                });
+                evaluations.into_mle().into()
+            })
+            .collect_vec();
+        exit_span!(span);
+        wit_layers.push(cur_layer);
+    }
     wit_layers.reverse();
     wit_layers
 }
 
-pub(crate) fn wit_infer_by_expr<'a, E: ExtensionField, const N: usize>(
+/// `challenge_pows[id][pow] = challenges[id]^pow` for every power up to the max one used in `expr`
+fn challenge_pows_table<E: ExtensionField>(challenges: &[E], expr: &Expression<E>) -> Vec<Vec<E>> {
+    let max_pow = expr.max_challenge_power();
+    challenges
+        .iter()
+        .map(|challenge| {
+            (0..=max_pow)
+                .scan(E::ONE, |state, _| {
+                    let res = *state;
+                    *state *= *challenge;
+                    Some(res)
+                })
+                .collect_vec()
+        })
+        .collect_vec()
+}
+
+/// intermediate value of [`wit_infer_by_expr`]
+/// constant and challenge subtrees are folded as scalars, so a degree-0 mle is only
+/// allocated once a scalar meets a witness
+#[derive(Clone)]
+enum InferValue<'a, E: ExtensionField> {
+    Base(E::BaseField),
+    Ext(E),
+    Mle(ArcMultilinearExtension<'a, E>),
+}
+
+impl<'a, E: ExtensionField> InferValue<'a, E> {
+    fn into_mle(self) -> ArcMultilinearExtension<'a, E> {
+        match self {
+            InferValue::Base(scalar) => {
+                Arc::new(DenseMultilinearExtension::from_evaluations_vec(0, vec![
+                    scalar,
+                ]))
+            }
+            InferValue::Ext(scalar) => Arc::new(
+                DenseMultilinearExtension::from_evaluations_ext_vec(0, vec![scalar]),
+            ),
+            InferValue::Mle(mle) => mle,
+        }
+    }
+
+    /// apply `base_op` / `ext_op` when both sides are scalars, `None` otherwise
+    fn scalar_op(
+        a: &Self,
+        b: &Self,
+        base_op: impl Fn(E::BaseField, E::BaseField) -> E::BaseField,
+        ext_op: impl Fn(E, E) -> E,
+    ) -> Option<Self> {
+        match (a, b) {
+            (InferValue::Base(a), InferValue::Base(b)) => Some(InferValue::Base(base_op(*a, *b))),
+            (InferValue::Base(a), InferValue::Ext(b)) => {
+                Some(InferValue::Ext(ext_op(E::from(*a), *b)))
+            }
+            (InferValue::Ext(a), InferValue::Base(b)) => {
+                Some(InferValue::Ext(ext_op(*a, E::from(*b))))
+            }
+            (InferValue::Ext(a), InferValue::Ext(b)) => Some(InferValue::Ext(ext_op(*a, *b))),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn wit_infer_by_expr<'a, E: ExtensionField>(
     fixed: &[ArcMultilinearExtension<'a, E>],
     witnesses: &[ArcMultilinearExtension<'a, E>],
     instance: &[ArcMultilinearExtension<'a, E>],
-    challenges: &[E; N],
+    challenges: &[E],
     expr: &Expression<E>,
 ) -> ArcMultilinearExtension<'a, E> {
-    expr.evaluate_with_instance::<ArcMultilinearExtension<'_, E>>(
-        &|f| fixed[f.0].clone(),
-        &|witness_id| witnesses[witness_id as usize].clone(),
-        &|i| instance[i.0].clone(),
-        &|scalar| {
-            let scalar: ArcMultilinearExtension<E> =
-                Arc::new(DenseMultilinearExtension::from_evaluations_vec(0, vec![
-                    scalar,
-                ]));
-            scalar
-        },
+    wit_infer_by_expr_value(fixed, witnesses, instance, challenges, expr).into_mle()
+}
+
+fn wit_infer_by_expr_value<'a, E: ExtensionField>(
+    fixed: &[ArcMultilinearExtension<'a, E>],
+    witnesses: &[ArcMultilinearExtension<'a, E>],
+    instance: &[ArcMultilinearExtension<'a, E>],
+    challenges: &[E],
+    expr: &Expression<E>,
+) -> InferValue<'a, E> {
+    let challenge_pows = challenge_pows_table(challenges, expr);
+    expr.evaluate_with_instance::<InferValue<'a, E>>(
+        &|f| InferValue::Mle(fixed[f.0].clone()),
+        &|witness_id| InferValue::Mle(witnesses[witness_id as usize].clone()),
+        &|i| InferValue::Mle(instance[i.0].clone()),
+        &|scalar| InferValue::Base(scalar),
         &|challenge_id, pow, scalar, offset| {
-            // TODO cache challenge power to be acquired once for each power
-            let challenge = challenges[challenge_id as usize];
-            let challenge: ArcMultilinearExtension<E> = Arc::new(
-                DenseMultilinearExtension::from_evaluations_ext_vec(0, vec![
-                    challenge.pow([pow as u64]) * scalar + offset,
-                ]),
-            );
-            challenge
+            InferValue::Ext(challenge_pows[challenge_id as usize][pow] * scalar + offset)
         },
         &|a, b| {
-            commutative_op_mle_pair!(|a, b| {
+            if let Some(sum) = InferValue::scalar_op(&a, &b, |a, b| a + b, |a, b| a + b) {
+                return sum;
+            }
+            let (a, b) = (a.into_mle(), b.into_mle());
+            InferValue::Mle(commutative_op_mle_pair!(|a, b| {
                 match (a.len(), b.len()) {
                     (1, 1) => Arc::new(DenseMultilinearExtension::from_evaluation_vec_smart(
                         0,
@@ -291,10 +537,14 @@ pub(crate) fn wit_infer_by_expr<'a, E: ExtensionField, const N: usize>(
                             .collect(),
                     )),
                 }
-            })
+            }))
         },
         &|a, b| {
-            commutative_op_mle_pair!(|a, b| {
+            if let Some(product) = InferValue::scalar_op(&a, &b, |a, b| a * b, |a, b| a * b) {
+                return product;
+            }
+            let (a, b) = (a.into_mle(), b.into_mle());
+            InferValue::Mle(commutative_op_mle_pair!(|a, b| {
                 match (a.len(), b.len()) {
                     (1, 1) => Arc::new(DenseMultilinearExtension::from_evaluation_vec_smart(
                         0,
@@ -328,10 +578,16 @@ pub(crate) fn wit_infer_by_expr<'a, E: ExtensionField, const N: usize>(
                         ))
                     }
                 }
-            })
+            }))
         },
         &|x, a, b| {
-            op_mle_xa_b!(|x, a, b| {
+            if let Some(xa) = InferValue::scalar_op(&x, &a, |x, a| x * a, |x, a| x * a) {
+                if let Some(xa_b) = InferValue::scalar_op(&xa, &b, |xa, b| xa + b, |xa, b| xa + b) {
+                    return xa_b;
+                }
+            }
+            let (x, a, b) = (x.into_mle(), a.into_mle(), b.into_mle());
+            InferValue::Mle(op_mle_xa_b!(|x, a, b| {
                 assert_eq!(a.len(), 1);
                 assert_eq!(b.len(), 1);
                 let (a, b) = (a[0], b[0]);
@@ -342,11 +598,192 @@ pub(crate) fn wit_infer_by_expr<'a, E: ExtensionField, const N: usize>(
                         .map(|x| a * x + b)
                         .collect(),
                 ))
-            })
+            }))
         },
     )
 }
 
+/// degree 1 specialisation of [`wit_infer_by_expr`]. `expr` is flattened into
+/// `constant + \sum_j coeff_j * mle_j` and evaluated in a single fused pass,
+/// without materializing an mle per intermediate sum or product
+pub(crate) fn wit_infer_linear<'a, E: ExtensionField>(
+    fixed: &[ArcMultilinearExtension<'a, E>],
+    witnesses: &[ArcMultilinearExtension<'a, E>],
+    instance: &[ArcMultilinearExtension<'a, E>],
+    challenges: &[E],
+    expr: &Expression<E>,
+    min_par_size: usize,
+) -> ArcMultilinearExtension<'a, E> {
+    assert!(expr.is_linear(), "expression of degree {}", expr.degree());
+    let mut combination = LinearCombination {
+        fixed,
+        witnesses,
+        instance,
+        challenges,
+        terms: vec![],
+        constant: InferValue::Base(E::BaseField::ZERO),
+        min_par_size,
+    };
+    combination.collect(expr, InferValue::Base(E::BaseField::ONE));
+    combination.evaluate()
+}
+
+/// `constant + \sum_j coeff_j * mle_j`, with scalar coefficients kept in the base field
+/// as long as possible
+struct LinearCombination<'a, 'b, E: ExtensionField> {
+    fixed: &'b [ArcMultilinearExtension<'a, E>],
+    witnesses: &'b [ArcMultilinearExtension<'a, E>],
+    instance: &'b [ArcMultilinearExtension<'a, E>],
+    challenges: &'b [E],
+    terms: Vec<(ArcMultilinearExtension<'a, E>, InferValue<'a, E>)>,
+    constant: InferValue<'a, E>,
+    min_par_size: usize,
+}
+
+impl<'a, E: ExtensionField> LinearCombination<'a, '_, E> {
+    fn infer(&self, expr: &Expression<E>) -> InferValue<'a, E> {
+        wit_infer_by_expr_value(
+            self.fixed,
+            self.witnesses,
+            self.instance,
+            self.challenges,
+            expr,
+        )
+    }
+
+    fn mul(a: &InferValue<'a, E>, b: &InferValue<'a, E>) -> Option<InferValue<'a, E>> {
+        InferValue::scalar_op(a, b, |a, b| a * b, |a, b| a * b)
+    }
+
+    /// add `scale * value` to the combination
+    fn add_scaled(&mut self, value: InferValue<'a, E>, scale: InferValue<'a, E>) {
+        match value {
+            InferValue::Mle(mle) => self.terms.push((mle, scale)),
+            scalar => {
+                let scaled = Self::mul(&scale, &scalar).unwrap();
+                self.constant =
+                    InferValue::scalar_op(&self.constant, &scaled, |a, b| a + b, |a, b| a + b)
+                        .unwrap();
+            }
+        }
+    }
+
+    /// add `scale * expr` to the combination
+    fn collect(&mut self, expr: &Expression<E>, scale: InferValue<'a, E>) {
+        if expr.degree() == 0 {
+            let value = self.infer(expr);
+            self.add_scaled(value, scale);
+            return;
+        }
+        match expr {
+            Expression::WitIn(witness_id) => self
+                .terms
+                .push((self.witnesses[*witness_id as usize].clone(), scale)),
+            Expression::Fixed(f) => self.terms.push((self.fixed[f.0].clone(), scale)),
+            Expression::Sum(a, b) => {
+                self.collect(a, scale.clone());
+                self.collect(b, scale);
+            }
+            Expression::Product(a, b) => {
+                let (s, x) = if a.degree() == 0 { (a, b) } else { (b, a) };
+                match Self::mul(&scale, &self.infer(s)) {
+                    Some(scale) => self.collect(x, scale),
+                    // scaled by a non-constant instance column
+                    None => self.add_scaled(self.infer(expr), scale),
+                }
+            }
+            Expression::ScaledSum(x, a, b) => {
+                match (Self::mul(&scale, &self.infer(a)), self.infer(b)) {
+                    (Some(scale_a), b @ (InferValue::Base(_) | InferValue::Ext(_))) => {
+                        self.collect(x, scale_a);
+                        self.add_scaled(b, scale);
+                    }
+                    _ => self.add_scaled(self.infer(expr), scale),
+                }
+            }
+            Expression::Instance(_) | Expression::Constant(_) | Expression::Challenge(..) => {
+                unreachable!("degree 0 expressions are handled above")
+            }
+        }
+    }
+
+    fn evaluate(&self) -> ArcMultilinearExtension<'a, E> {
+        let num_evals = self
+            .terms
+            .iter()
+            .map(|(mle, _)| mle.evaluations().len())
+            .max()
+            .unwrap_or(1);
+        assert!(
+            self.terms
+                .iter()
+                .all(|(mle, _)| [1, num_evals].contains(&mle.evaluations().len()))
+        );
+        // length 1 columns are broadcast to every instance
+        let at = |len: usize, i: usize| if len == 1 { 0 } else { i };
+
+        let all_base = matches!(self.constant, InferValue::Base(_))
+            && self.terms.iter().all(|(mle, coeff)| {
+                matches!(coeff, InferValue::Base(_))
+                    && matches!(mle.evaluations(), FieldType::Base(_))
+            });
+        if all_base {
+            let base = |value: &InferValue<'a, E>| match value {
+                InferValue::Base(value) => *value,
+                _ => unreachable!(),
+            };
+            let constant = base(&self.constant);
+            let terms = self
+                .terms
+                .iter()
+                .map(|(mle, coeff)| (mle.get_base_field_vec(), base(coeff)))
+                .collect_vec();
+            let evaluations = (0..num_evals)
+                .into_par_iter()
+                .with_min_len(self.min_par_size)
+                .map(|i| {
+                    terms.iter().fold(constant, |acc, (evals, coeff)| {
+                        acc + *coeff * evals[at(evals.len(), i)]
+                    })
+                })
+                .collect();
+            Arc::new(DenseMultilinearExtension::from_evaluations_vec(
+                ceil_log2(num_evals),
+                evaluations,
+            ))
+        } else {
+            let ext = |value: &InferValue<'a, E>| match value {
+                InferValue::Base(value) => E::from(*value),
+                InferValue::Ext(value) => *value,
+                InferValue::Mle(_) => unreachable!(),
+            };
+            let constant = ext(&self.constant);
+            let terms = self
+                .terms
+                .iter()
+                .map(|(mle, coeff)| (mle.evaluations(), ext(coeff)))
+                .collect_vec();
+            let evaluations = (0..num_evals)
+                .into_par_iter()
+                .with_min_len(self.min_par_size)
+                .map(|i| {
+                    terms.iter().fold(constant, |acc, (evals, coeff)| {
+                        acc + match evals {
+                            FieldType::Base(evals) => *coeff * evals[at(evals.len(), i)],
+                            FieldType::Ext(evals) => *coeff * evals[at(evals.len(), i)],
+                            FieldType::Unreachable => unreachable!(),
+                        }
+                    })
+                })
+                .collect();
+            Arc::new(DenseMultilinearExtension::from_evaluations_ext_vec(
+                ceil_log2(num_evals),
+                evaluations,
+            ))
+        }
+    }
+}
+
 pub(crate) fn eval_by_expr<E: ExtensionField>(
     witnesses: &[E],
     challenges: &[E],
@@ -355,20 +792,50 @@ pub(crate) fn eval_by_expr<E: ExtensionField>(
     eval_by_expr_with_fixed(&[], witnesses, challenges, expr)
 }
 
+/// same as [`eval_by_expr`], but stays in the base field to avoid extension field arithmetic.
+/// errors if a challenge scalar or offset is extension-valued
+pub(crate) fn eval_by_expr_base<E: ExtensionField>(
+    witnesses: &[E::BaseField],
+    challenges: &[E::BaseField],
+    expr: &Expression<E>,
+) -> Result<E::BaseField, ZKVMError> {
+    let to_base = |value: E| {
+        let (base, ext) = value.as_bases().split_first().unwrap();
+        if ext.iter().all(|limb| *limb == E::BaseField::ZERO) {
+            Ok(*base)
+        } else {
+            Err(ZKVMError::NonBaseFieldValue(format!("{:?}", value)))
+        }
+    };
+    expr.evaluate::<Result<E::BaseField, ZKVMError>>(
+        &|_| unreachable!(),
+        &|witness_id| Ok(witnesses[witness_id as usize]),
+        &|scalar| Ok(scalar),
+        &|challenge_id, pow, scalar, offset| {
+            Ok(
+                challenges[challenge_id as usize].pow([pow as u64]) * to_base(scalar)?
+                    + to_base(offset)?,
+            )
+        },
+        &|a, b| Ok(a? + b?),
+        &|a, b| Ok(a? * b?),
+        &|x, a, b| Ok(a? * x? + b?),
+    )
+}
+
 pub(crate) fn eval_by_expr_with_fixed<E: ExtensionField>(
     fixed: &[E],
     witnesses: &[E],
     challenges: &[E],
     expr: &Expression<E>,
 ) -> E {
+    let challenge_pows = challenge_pows_table(challenges, expr);
     expr.evaluate::<E>(
         &|f| fixed[f.0],
         &|witness_id| witnesses[witness_id as usize],
         &|scalar| scalar.into(),
         &|challenge_id, pow, scalar, offset| {
-            // TODO cache challenge power to be acquired once for each power
-            let challenge = challenges[challenge_id as usize];
-            challenge.pow([pow as u64]) * scalar + offset
+            challenge_pows[challenge_id as usize][pow] * scalar + offset
         },
         &|a, b| a + b,
         &|a, b| a * b,
@@ -383,15 +850,14 @@ pub fn eval_by_expr_with_instance<E: ExtensionField>(
     challenges: &[E],
     expr: &Expression<E>,
 ) -> E {
+    let challenge_pows = challenge_pows_table(challenges, expr);
     expr.evaluate_with_instance::<E>(
         &|f| fixed[f.0],
         &|witness_id| witnesses[witness_id as usize],
         &|i| instance[i.0],
         &|scalar| scalar.into(),
         &|challenge_id, pow, scalar, offset| {
-            // TODO cache challenge power to be acquired once for each power
-            let challenge = challenges[challenge_id as usize];
-            challenge.pow([pow as u64]) * scalar + offset
+            challenge_pows[challenge_id as usize][pow] * scalar + offset
         },
         &|a, b| a + b,
         &|a, b| a * b,
@@ -401,8 +867,9 @@ pub fn eval_by_expr_with_instance<E: ExtensionField>(
 
 #[cfg(test)]
 mod tests {
+    use ark_std::{rand::RngCore, test_rng};
     use ff::Field;
-    use goldilocks::{ExtensionField, GoldilocksExt2};
+    use goldilocks::{ExtensionField, Goldilocks, GoldilocksExt2};
     use itertools::Itertools;
     use multilinear_extensions::{
         commutative_op_mle_pair,
@@ -413,13 +880,19 @@ mod tests {
 
     use crate::{
         circuit_builder::{CircuitBuilder, ConstraintSystem},
+        error::ZKVMError,
         expression::{Expression, ToExpr},
         scheme::utils::{
-            infer_tower_logup_witness, infer_tower_product_witness, interleaving_mles_to_mles,
+            infer_opcode_tower_witness, infer_tower_logup_witness, infer_tower_product_witness,
+            interleaving_mles_to_mles,
         },
+        structs::{ChallengeId, WitnessId},
     };
 
-    use super::wit_infer_by_expr;
+    use super::{
+        InferValue, MIN_PAR_SIZE, ParallelThresholds, eval_by_expr, eval_by_expr_base,
+        wit_infer_by_expr, wit_infer_by_expr_value, wit_infer_linear,
+    };
 
     #[test]
     fn test_infer_tower_witness() {
@@ -430,7 +903,7 @@ mod tests {
             vec![E::from(3u64), E::from(4u64)].into_mle().into(),
         ];
         let num_vars = ceil_log2(last_layer[0].evaluations().len()) + 1;
-        let res = infer_tower_product_witness(num_vars, last_layer.clone(), 2);
+        let res = infer_tower_product_witness(num_vars, last_layer.clone(), 2, MIN_PAR_SIZE);
         let (left, right) = (&res[0][0], &res[0][1]);
         let final_product = commutative_op_mle_pair!(
             |left, right| {
@@ -465,7 +938,8 @@ mod tests {
             vec![E::from(5u64), E::from(6u64)].into_mle().into(),
             vec![E::from(7u64), E::from(8u64)].into_mle().into(),
         ];
-        let res = interleaving_mles_to_mles(&input_mles, 2, num_product_fanin, E::ONE);
+        let res =
+            interleaving_mles_to_mles(&input_mles, 2, num_product_fanin, E::ONE, MIN_PAR_SIZE);
         // [[1, 3, 5, 7], [2, 4, 6, 8]]
         assert_eq!(res[0].get_ext_field_vec(), vec![
             E::ONE,
@@ -481,6 +955,44 @@ mod tests {
         ],);
     }
 
+    #[test]
+    fn test_interleaving_mles_to_mles_threshold_independent() {
+        type E = GoldilocksExt2;
+        let mut rng = test_rng();
+        let num_instances = 100;
+        let input_mles: Vec<ArcMultilinearExtension<E>> = (0..3)
+            .map(|_| {
+                (0..num_instances)
+                    .map(|_| E::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect_vec();
+        let interleave = |min_par_size| {
+            interleaving_mles_to_mles(&input_mles, num_instances, 2, E::ONE, min_par_size)
+                .iter()
+                .map(|mle| mle.get_ext_field_vec().to_vec())
+                .collect_vec()
+        };
+        assert_eq!(interleave(1), interleave(usize::MAX));
+        assert_eq!(interleave(1), interleave(MIN_PAR_SIZE));
+    }
+
+    #[test]
+    fn test_parallel_thresholds_builder() {
+        let thresholds = ParallelThresholds::default()
+            .with_interleave(1)
+            .with_tower(2)
+            .with_wit_infer(3);
+        assert_eq!(thresholds, ParallelThresholds {
+            interleave: 1,
+            tower: 2,
+            wit_infer: 3,
+        });
+        assert_eq!(ParallelThresholds::default().tower, MIN_PAR_SIZE);
+    }
+
     #[test]
     fn test_interleaving_mles_to_mles_padding() {
         type E = GoldilocksExt2;
@@ -493,7 +1005,8 @@ mod tests {
             vec![E::from(3u64), E::from(4u64)].into_mle().into(),
             vec![E::from(5u64), E::from(6u64)].into_mle().into(),
         ];
-        let res = interleaving_mles_to_mles(&input_mles, 2, num_product_fanin, E::ZERO);
+        let res =
+            interleaving_mles_to_mles(&input_mles, 2, num_product_fanin, E::ZERO, MIN_PAR_SIZE);
         // [[1, 3, 5, 0], [2, 4, 6, 0]]
         assert_eq!(res[0].get_ext_field_vec(), vec![
             E::ONE,
@@ -515,7 +1028,8 @@ mod tests {
             vec![E::from(3u64), E::from(0u64)].into_mle().into(),
             vec![E::from(5u64), E::from(0u64)].into_mle().into(),
         ];
-        let res = interleaving_mles_to_mles(&input_mles, 1, num_product_fanin, E::ONE);
+        let res =
+            interleaving_mles_to_mles(&input_mles, 1, num_product_fanin, E::ONE, MIN_PAR_SIZE);
         // [[1, 3, 5, 1], [1, 1, 1, 1]]
         assert_eq!(res[0].get_ext_field_vec(), vec![
             E::ONE,
@@ -535,7 +1049,8 @@ mod tests {
             vec![E::from(2u64)].into_mle().into(),
             vec![E::from(3u64)].into_mle().into(),
         ];
-        let res = interleaving_mles_to_mles(&input_mles, 1, num_product_fanin, E::ONE);
+        let res =
+            interleaving_mles_to_mles(&input_mles, 1, num_product_fanin, E::ONE, MIN_PAR_SIZE);
         // [[2, 3], [1, 1]]
         assert_eq!(res[0].get_ext_field_vec(), vec![
             E::from(2u64),
@@ -544,6 +1059,27 @@ mod tests {
         assert_eq!(res[1].get_ext_field_vec(), vec![E::ONE, E::ONE],);
     }
 
+    #[test]
+    #[should_panic(expected = "all_equal")]
+    fn test_infer_tower_logup_witness_rejects_mismatched_lengths() {
+        type E = GoldilocksExt2;
+        let q: Vec<ArcMultilinearExtension<E>> = vec![
+            vec![1, 2, 3, 4]
+                .into_iter()
+                .map(E::from)
+                .collect_vec()
+                .into_mle()
+                .into(),
+            vec![5, 6]
+                .into_iter()
+                .map(E::from)
+                .collect_vec()
+                .into_mle()
+                .into(),
+        ];
+        infer_tower_logup_witness(None, q, MIN_PAR_SIZE);
+    }
+
     #[test]
     fn test_infer_tower_logup_witness() {
         type E = GoldilocksExt2;
@@ -562,7 +1098,7 @@ mod tests {
                 .into_mle()
                 .into(),
         ];
-        let mut res = infer_tower_logup_witness(None, q);
+        let mut res = infer_tower_logup_witness(None, q, MIN_PAR_SIZE);
         assert_eq!(num_vars + 1, res.len());
         // input layer
         let layer = res.pop().unwrap();
@@ -715,4 +1251,239 @@ mod tests {
         );
         res.get_ext_field_vec();
     }
+
+    #[test]
+    fn test_wit_infer_by_expr_folds_constant_subtrees() {
+        type E = goldilocks::GoldilocksExt2;
+        type B = goldilocks::Goldilocks;
+        let mut rng = test_rng();
+        let mut cs = ConstraintSystem::<E>::new(|| "test");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let a = cb.create_witin(|| "a");
+        let b = cb.create_witin(|| "b");
+
+        let constants: Expression<E> = (Expression::Constant(B::from(3))
+            + Expression::Challenge(1, 1, E::ONE, E::ZERO))
+            * Expression::Challenge(0, 2, E::from(5u64), E::ONE)
+            + Expression::Constant(B::from(7));
+        let expr: Expression<E> = a.expr() * constants.clone() + constants.clone() + b.expr();
+
+        let challenges = [E::random(&mut rng), E::random(&mut rng)];
+        // a constant-only subtree is folded without materializing any mle
+        assert!(matches!(
+            wit_infer_by_expr_value(&[], &[], &[], &challenges, &constants),
+            InferValue::Ext(_)
+        ));
+
+        let (a_vals, b_vals): (Vec<B>, Vec<B>) = (0..8)
+            .map(|_| (B::from(rng.next_u64()), B::from(rng.next_u64())))
+            .unzip();
+        let res = wit_infer_by_expr(
+            &[],
+            &[
+                a_vals.clone().into_mle().into(),
+                b_vals.clone().into_mle().into(),
+            ],
+            &[],
+            &challenges,
+            &expr,
+        );
+        let expected = a_vals
+            .iter()
+            .zip_eq(b_vals.iter())
+            .map(|(a, b)| eval_by_expr(&[E::from(*a), E::from(*b)], &challenges, &expr))
+            .collect_vec();
+        assert_eq!(res.get_ext_field_vec(), expected);
+    }
+
+    #[test]
+    fn test_wit_infer_linear_matches_general_inference() {
+        type E = GoldilocksExt2;
+        type B = Goldilocks;
+        let mut rng = test_rng();
+        let mut cs = ConstraintSystem::<E>::new(|| "test");
+        let mut cb = CircuitBuilder::new(&mut cs);
+        let a = cb.create_witin(|| "a");
+        let b = cb.create_witin(|| "b");
+        let c = cb.create_witin(|| "c");
+
+        let witnesses: Vec<ArcMultilinearExtension<E>> = (0..3)
+            .map(|_| {
+                (0..8)
+                    .map(|_| B::from(rng.next_u64()))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect();
+        let challenges = [E::random(&mut rng), E::random(&mut rng)];
+
+        let base_expr: Expression<E> = a.expr() * Expression::Constant(B::from(3))
+            + Expression::ScaledSum(
+                Box::new(b.expr()),
+                Box::new(Expression::Constant(B::from(5))),
+                Box::new(Expression::Constant(B::from(7))),
+            )
+            - c.expr();
+        let ext_expr: Expression<E> = base_expr.clone()
+            + Expression::Challenge(0, 2, E::ONE, E::ZERO) * b.expr()
+            + Expression::Challenge(1, 1, E::from(11u64), E::ONE);
+
+        for expr in [base_expr, ext_expr] {
+            assert!(expr.is_linear());
+            let expected = wit_infer_by_expr(&[], &witnesses, &[], &challenges, &expr);
+            let res = wit_infer_linear(&[], &witnesses, &[], &challenges, &expr, MIN_PAR_SIZE);
+            assert_eq!(res.num_vars(), expected.num_vars());
+            assert_eq!(res.evaluations(), expected.evaluations());
+        }
+    }
+
+    #[test]
+    fn test_cached_challenge_pows_match_direct_pow() {
+        type E = goldilocks::GoldilocksExt2;
+        let mut rng = test_rng();
+        let challenges = [E::random(&mut rng), E::random(&mut rng)];
+        let witnesses = (0..4).map(|_| E::random(&mut rng)).collect_vec();
+
+        // random sum of products mixing witnesses and challenges with powers up to 8
+        let expr: Expression<E> = (0..16)
+            .map(|i| {
+                let challenge = Expression::Challenge(
+                    (rng.next_u32() % 2) as ChallengeId,
+                    (rng.next_u32() % 9) as usize,
+                    E::random(&mut rng),
+                    E::random(&mut rng),
+                );
+                Expression::WitIn((i % witnesses.len()) as WitnessId) * challenge
+            })
+            .reduce(|acc, term| acc + term)
+            .unwrap();
+        assert!(expr.max_challenge_power() <= 8);
+
+        let expected = expr.evaluate::<E>(
+            &|_| unreachable!(),
+            &|witness_id| witnesses[witness_id as usize],
+            &|scalar| scalar.into(),
+            &|challenge_id, pow, scalar, offset| {
+                challenges[challenge_id as usize].pow([pow as u64]) * scalar + offset
+            },
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|x, a, b| a * x + b,
+        );
+        assert_eq!(eval_by_expr(&witnesses, &challenges, &expr), expected);
+
+        let res = wit_infer_by_expr(
+            &[],
+            &witnesses
+                .iter()
+                .map(|w| vec![*w].into_mle().into())
+                .collect_vec(),
+            &[],
+            &challenges,
+            &expr,
+        );
+        assert_eq!(res.get_ext_field_vec(), vec![expected]);
+    }
+
+    #[test]
+    fn test_eval_by_expr_base_matches_ext() {
+        type E = GoldilocksExt2;
+        type F = Goldilocks;
+        let mut rng = test_rng();
+        let witnesses = (0..3).map(|_| F::random(&mut rng)).collect_vec();
+        let challenges = (0..2).map(|_| F::random(&mut rng)).collect_vec();
+
+        let x: Expression<E> = Expression::WitIn(0);
+        let y: Expression<E> = Expression::WitIn(1);
+        let z: Expression<E> = Expression::WitIn(2);
+        let expr = Expression::<E>::Constant(F::from(3)) * x.clone()
+            + y.clone() * Expression::Challenge(0, 2, E::from(5u64), E::from(7u64))
+            - z * Expression::Challenge(1, 1, E::ONE, E::ZERO)
+            + x * y
+            + Expression::Constant(F::from(11));
+
+        let base = eval_by_expr_base(&witnesses, &challenges, &expr).unwrap();
+        let ext = eval_by_expr(
+            &witnesses.iter().map(|w| E::from(*w)).collect_vec(),
+            &challenges.iter().map(|c| E::from(*c)).collect_vec(),
+            &expr,
+        );
+        assert_eq!(E::from(base), ext);
+
+        // extension-valued challenge scalar can't be evaluated in the base field
+        let expr = Expression::<E>::WitIn(0)
+            * Expression::Challenge(0, 1, E::from_bases(&[F::ONE, F::ONE]), E::ZERO);
+        assert!(matches!(
+            eval_by_expr_base(&witnesses, &challenges, &expr),
+            Err(ZKVMError::NonBaseFieldValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_infer_opcode_tower_witness_matches_sequential() {
+        type E = GoldilocksExt2;
+        let mut rng = test_rng();
+        let num_instances = 5;
+        let mut random_records = |count: usize| -> Vec<ArcMultilinearExtension<E>> {
+            (0..count)
+                .map(|_| {
+                    (0..num_instances.next_power_of_two())
+                        .map(|_| E::random(&mut rng))
+                        .collect_vec()
+                        .into_mle()
+                        .into()
+                })
+                .collect_vec()
+        };
+        let r_records = random_records(3);
+        let w_records = random_records(3);
+        let lk_records = random_records(5);
+        let alpha = E::random(&mut rng);
+
+        let (r_wit_layers, w_wit_layers, lk_wit_layers) = infer_opcode_tower_witness(
+            &r_records,
+            &w_records,
+            &lk_records,
+            num_instances,
+            alpha,
+            &ParallelThresholds::default(),
+        );
+
+        // build the same towers one after another
+        let log2_num_instances = ceil_log2(num_instances.next_power_of_two());
+        let expected_r_wit_layers = infer_tower_product_witness(
+            log2_num_instances + ceil_log2(r_records.len()),
+            interleaving_mles_to_mles(&r_records, num_instances, 2, E::ONE, MIN_PAR_SIZE),
+            2,
+            MIN_PAR_SIZE,
+        );
+        let expected_w_wit_layers = infer_tower_product_witness(
+            log2_num_instances + ceil_log2(w_records.len()),
+            interleaving_mles_to_mles(&w_records, num_instances, 2, E::ONE, MIN_PAR_SIZE),
+            2,
+            MIN_PAR_SIZE,
+        );
+        let expected_lk_wit_layers = infer_tower_logup_witness(
+            None,
+            interleaving_mles_to_mles(&lk_records, num_instances, 2, alpha, MIN_PAR_SIZE),
+            MIN_PAR_SIZE,
+        );
+
+        let out_evals = |layers: &[Vec<ArcMultilinearExtension<E>>]| {
+            layers[0]
+                .iter()
+                .map(|w| w.get_ext_field_vec()[0])
+                .collect_vec()
+        };
+        assert_eq!(r_wit_layers.len(), expected_r_wit_layers.len());
+        assert_eq!(w_wit_layers.len(), expected_w_wit_layers.len());
+        assert_eq!(lk_wit_layers.len(), expected_lk_wit_layers.len());
+        assert_eq!(out_evals(&r_wit_layers), out_evals(&expected_r_wit_layers));
+        assert_eq!(out_evals(&w_wit_layers), out_evals(&expected_w_wit_layers));
+        assert_eq!(
+            out_evals(&lk_wit_layers),
+            out_evals(&expected_lk_wit_layers)
+        );
+    }
 }
@@ -1,46 +1,133 @@
-use std::marker::PhantomData;
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashSet,
+    marker::PhantomData,
+};
 
 use ark_std::iterable::Iterable;
 use ceno_emul::WORD_SIZE;
 use ff_ext::ExtensionField;
+use goldilocks::SmallField;
 
-use itertools::{Itertools, interleave, izip};
+use itertools::{Itertools, chain, interleave, izip};
 use mpcs::PolynomialCommitmentScheme;
 use multilinear_extensions::{
-    mle::{IntoMLE, MultilinearExtension},
+    mle::{DenseMultilinearExtension, IntoMLE, MultilinearExtension},
     util::ceil_log2,
     virtual_poly::{VPAuxInfo, build_eq_x_r_vec_sequential, eq_eval},
 };
-use sumcheck::structs::{IOPProof, IOPVerifierState};
-use transcript::{ForkableTranscript, Transcript};
+use sumcheck::{
+    macros::{entered_span, exit_span},
+    structs::{IOPProof, IOPVerifierState},
+};
+use transcript::{BasicTranscriptWithStat, ForkableTranscript, StatisticRecorder, Transcript};
 
 use crate::{
-    circuit_builder::SetTableAddrType,
+    circuit_builder::{ConstraintSystem, SetTableAddrType},
     error::ZKVMError,
-    expression::Instance,
+    expression::{Expression, Instance},
     instructions::{Instruction, riscv::ecall::HaltInstruction},
     scheme::{
-        constants::{NUM_FANIN, NUM_FANIN_LOGUP, SEL_DEGREE},
+        constants::{MAX_NUM_INSTANCES, NUM_FANIN, NUM_FANIN_LOGUP, SEL_DEGREE},
         utils::eval_by_expr_with_instance,
     },
-    structs::{Point, PointAndEval, TowerProofs, VerifyingKey, ZKVMVerifyingKey},
-    utils::{
-        eq_eval_less_or_equal_than, eval_wellform_address_vec, get_challenge_pows,
-        next_pow2_instance_padding,
-    },
+    structs::{FixedCommitStore, Point, PointAndEval, TowerProofs, VerifyingKey, ZKVMVerifyingKey},
+    utils::{eval_wellform_address_vec, get_challenge_pows, next_pow2_instance_padding, sel_eval},
 };
 
 use super::{
     ZKVMOpcodeProof, ZKVMProof, ZKVMTableProof, constants::MAINCONSTRAIN_SUMCHECK_BATCH_SIZE,
 };
 
+/// knobs of [`ZKVMVerifier`], all off by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VerifierOptions {
+    /// UNSOUND, for benchmarking only: skip verifying the PCS opening proofs so the numbers
+    /// only cover the sumcheck and tower part of verification. each circuit opens its
+    /// commitments last on its own forked transcript, so skipping them does not disturb
+    /// the transcript of the remaining checks
+    pub skip_pcs: bool,
+}
+
+/// one step of the verifier on the main transcript, see [`ZKVMVerifier::transcript_script`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// absorb the `len` values of public input `index`, one by one
+    AppendPublicInput { index: usize, len: usize },
+    /// absorb the fixed commitment of circuit `name` in `absorbs` calls
+    WriteFixedCommit { name: String, absorbs: usize },
+    /// absorb the witness commitment of circuit `name` in `absorbs` calls
+    WriteWitsCommit { name: String, absorbs: usize },
+    /// squeeze the `index`-th global challenge
+    ReadChallenge { index: usize },
+    /// fork one transcript per circuit of the vk
+    Fork { n: usize },
+    /// verify circuit `name` on forked transcript `index`, leaving the main transcript alone
+    VerifyCircuit { name: String, index: usize },
+}
+
+impl TranscriptOp {
+    /// number of absorb and squeeze calls this op makes on the main transcript
+    pub fn num_interactions(&self) -> usize {
+        match self {
+            TranscriptOp::AppendPublicInput { len, .. } => *len,
+            TranscriptOp::WriteFixedCommit { absorbs, .. }
+            | TranscriptOp::WriteWitsCommit { absorbs, .. } => *absorbs,
+            TranscriptOp::ReadChallenge { .. } | TranscriptOp::Fork { .. } => 1,
+            TranscriptOp::VerifyCircuit { .. } => 0,
+        }
+    }
+}
+
 pub struct ZKVMVerifier<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
     pub(crate) vk: ZKVMVerifyingKey<E, PCS>,
+    pub(crate) options: VerifierOptions,
+    /// source of the fixed commitments, the ones held in `vk` if unset
+    fixed_commit_store: Option<Box<dyn FixedCommitStore<E, PCS>>>,
 }
 
 impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS> {
     pub fn new(vk: ZKVMVerifyingKey<E, PCS>) -> Self {
-        ZKVMVerifier { vk }
+        Self::new_with_options(vk, VerifierOptions::default())
+    }
+
+    pub fn new_with_options(vk: ZKVMVerifyingKey<E, PCS>, options: VerifierOptions) -> Self {
+        if options.skip_pcs {
+            tracing::warn!("PCS opening verification is skipped, proofs are NOT sound");
+        }
+        ZKVMVerifier {
+            vk,
+            options,
+            fixed_commit_store: None,
+        }
+    }
+
+    /// fetch the fixed commitments from `store` rather than from the verifying key, e.g. to
+    /// load large fixed tables lazily
+    pub fn with_fixed_commit_store(
+        mut self,
+        store: impl FixedCommitStore<E, PCS> + 'static,
+    ) -> Self {
+        self.fixed_commit_store = Some(Box::new(store));
+        self
+    }
+
+    fn fixed_commit_store(&self) -> &dyn FixedCommitStore<E, PCS> {
+        self.fixed_commit_store.as_deref().unwrap_or(&self.vk)
+    }
+
+    /// fixed commitments of the circuits with fixed columns, fetched from the fixed commit
+    /// store in the order they are written to the transcript (sorted by circuit name)
+    fn fixed_commits(&self) -> Result<Vec<(&String, Cow<'_, PCS::Commitment>)>, ZKVMError> {
+        self.vk
+            .circuit_vks
+            .iter()
+            .filter_map(|(name, vk)| {
+                fetch_fixed_commit(name, vk.get_cs(), self.fixed_commit_store())
+                    .map(|commit| commit.map(|commit| (name, commit)))
+                    .transpose()
+            })
+            .collect()
     }
 
     /// Verify a trace from start to halt.
@@ -53,12 +140,36 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         self.verify_proof_halt(vm_proof, transcript, true)
     }
 
+    /// Verify a trace from start to halt, bound to the `public_inputs` known to the verifier,
+    /// i.e. the [`super::PublicValues::to_vec`] the prover absorbed into the transcript.
+    /// the public inputs of the proof must match them entry by entry.
+    pub fn verify_proof_with_public_inputs(
+        &self,
+        vm_proof: ZKVMProof<E, PCS>,
+        public_inputs: &[Vec<E::BaseField>],
+        transcript: impl ForkableTranscript<E>,
+    ) -> Result<bool, ZKVMError> {
+        self.verify_halt_and_validity(vm_proof, public_inputs, transcript, true)
+    }
+
     /// Verify a trace from start to optional halt.
     pub fn verify_proof_halt(
         &self,
         vm_proof: ZKVMProof<E, PCS>,
         transcript: impl ForkableTranscript<E>,
         does_halt: bool,
+    ) -> Result<bool, ZKVMError> {
+        // TODO fix soundness: construct raw public input by ourself and trustless from proof
+        let public_inputs = vm_proof.raw_pi.clone();
+        self.verify_halt_and_validity(vm_proof, &public_inputs, transcript, does_halt)
+    }
+
+    fn verify_halt_and_validity(
+        &self,
+        vm_proof: ZKVMProof<E, PCS>,
+        public_inputs: &[Vec<E::BaseField>],
+        transcript: impl ForkableTranscript<E>,
+        does_halt: bool,
     ) -> Result<bool, ZKVMError> {
         // require ecall/halt proof to exist, depending whether we expect a halt.
         let num_instances = vm_proof
@@ -73,12 +184,13 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             )));
         }
 
-        self.verify_proof_validity(vm_proof, transcript)
+        self.verify_proof_validity(vm_proof, public_inputs, transcript)
     }
 
     fn verify_proof_validity(
         &self,
         vm_proof: ZKVMProof<E, PCS>,
+        public_inputs: &[Vec<E::BaseField>],
         mut transcript: impl ForkableTranscript<E>,
     ) -> Result<bool, ZKVMError> {
         // main invariant between opcode circuits and table circuits
@@ -88,13 +200,12 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
 
         let pi_evals = &vm_proof.pi_evals;
 
-        // TODO fix soundness: construct raw public input by ourself and trustless from proof
-        // including raw public input to transcript
-        vm_proof
-            .raw_pi
-            .iter()
-            .for_each(|v| v.iter().for_each(|v| transcript.append_field_element(v)));
-
+        // the raw public input carried by the proof feeds the pi evaluations below
+        if vm_proof.raw_pi != public_inputs {
+            return Err(ZKVMError::VerifyError(
+                "public inputs of the proof mismatch the expected ones".into(),
+            ));
+        }
         // verify constant poly(s) evaluation result match
         // we can evaluate at this moment because constant always evaluate to same value
         // non-constant poly(s) will be verified in respective (table) proof accordingly
@@ -109,35 +220,17 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                     Ok(())
                 }
             })?;
-        // write fixed commitment to transcript
-        for (_, vk) in self.vk.circuit_vks.iter() {
-            if let Some(fixed_commit) = vk.fixed_commit.as_ref() {
-                PCS::write_commitment(fixed_commit, &mut transcript)
-                    .map_err(ZKVMError::PCSError)?;
-            }
-        }
-
-        for (name, (_, proof)) in vm_proof.opcode_proofs.iter() {
-            tracing::debug!("read {}'s commit", name);
-            PCS::write_commitment(&proof.wits_commit, &mut transcript)
-                .map_err(ZKVMError::PCSError)?;
-        }
-        for (name, (_, proof)) in vm_proof.table_proofs.iter() {
-            tracing::debug!("read {}'s commit", name);
-            PCS::write_commitment(&proof.wits_commit, &mut transcript)
-                .map_err(ZKVMError::PCSError)?;
-        }
-
-        // alpha, beta
-        let challenges = [
-            transcript.read_challenge().elements,
-            transcript.read_challenge().elements,
-        ];
+        let challenges = absorb_up_to_fork::<E, PCS, _>(
+            public_inputs,
+            self.fixed_commits()?.into_iter().map(|(_, commit)| commit),
+            self.vk.num_challenges(),
+            &mut transcript,
+            |transcript| write_wits_commitments(&vm_proof, None, transcript),
+        )?;
         tracing::debug!("challenges in verifier: {:?}", challenges);
 
-        let dummy_table_item = challenges[0];
-        let mut dummy_table_item_multiplicity = 0;
         let point_eval = PointAndEval::default();
+        self.check_transcript_indices(&vm_proof)?;
         let mut transcripts = transcript.fork(self.vk.circuit_vks.len());
 
         for (name, (i, opcode_proof)) in vm_proof.opcode_proofs {
@@ -163,20 +256,22 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
 
             // getting the number of dummy padding item that we used in this opcode circuit
             let num_lks = circuit_vk.get_cs().lk_expressions.len();
-            let num_padded_lks_per_instance = next_pow2_instance_padding(num_lks) - num_lks;
-            let num_padded_instance =
-                next_pow2_instance_padding(opcode_proof.num_instances) - opcode_proof.num_instances;
-            dummy_table_item_multiplicity += num_padded_lks_per_instance
-                * opcode_proof.num_instances
-                + num_lks.next_power_of_two() * num_padded_instance;
+            let num_padded_lk_items = num_padded_lk_items(num_lks, opcode_proof.num_instances);
+            if num_padded_lk_items != opcode_proof.num_padded_lk_items {
+                return Err(ZKVMError::VerifyError(format!(
+                    "[opcode {name}] padded lookup items mismatch: recomputed {num_padded_lk_items} != {} in proof",
+                    opcode_proof.num_padded_lk_items
+                )));
+            }
+            // no table entry cancels the dummy items padding the logup tower
+            let dummy_lookup_item = circuit_vk.get_cs().dummy_lookup_item(&challenges);
+            logup_sum -= E::from(num_padded_lk_items as u64) * dummy_lookup_item.invert().unwrap();
 
             prod_r *= opcode_proof.record_r_out_evals.iter().product::<E>();
             prod_w *= opcode_proof.record_w_out_evals.iter().product::<E>();
 
-            logup_sum +=
-                opcode_proof.lk_p1_out_eval * opcode_proof.lk_q1_out_eval.invert().unwrap();
-            logup_sum +=
-                opcode_proof.lk_p2_out_eval * opcode_proof.lk_q2_out_eval.invert().unwrap();
+            let [p1, p2, q1, q2] = opcode_proof.lk_out_evals;
+            logup_sum += p1 * q1.invert().unwrap() + p2 * q2.invert().unwrap();
         }
 
         for (name, (i, table_proof)) in vm_proof.table_proofs {
@@ -191,6 +286,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 &name,
                 &self.vk.vp,
                 circuit_vk,
+                self.fixed_commit_store(),
                 &table_proof,
                 &vm_proof.raw_pi,
                 &vm_proof.pi_evals,
@@ -211,8 +307,6 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             prod_w *= table_proof.w_out_evals.iter().flatten().product::<E>();
             prod_r *= table_proof.r_out_evals.iter().flatten().product::<E>();
         }
-        logup_sum -=
-            E::from(dummy_table_item_multiplicity as u64) * dummy_table_item.invert().unwrap();
 
         // check logup relation across all proofs
         if logup_sum != E::ZERO {
@@ -246,6 +340,127 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         Ok(true)
     }
 
+    /// the ordered transcript operations [`Self::verify_proof`] performs on `vm_proof`, derived
+    /// from the proof shape and vk alone. useful to diff against the prover when Fiat-Shamir
+    /// goes out of sync
+    pub fn transcript_script(
+        &self,
+        vm_proof: &ZKVMProof<E, PCS>,
+    ) -> Result<Vec<TranscriptOp>, ZKVMError> {
+        let absorbs = |commit: &PCS::Commitment| {
+            let stat = StatisticRecorder::default();
+            let mut transcript = BasicTranscriptWithStat::new(&stat, b"commit");
+            PCS::write_commitment(commit, &mut transcript).map_err(ZKVMError::PCSError)?;
+            Ok::<_, ZKVMError>(stat.into_inner().field_appended_num as usize)
+        };
+
+        let mut script = vm_proof
+            .raw_pi
+            .iter()
+            .enumerate()
+            .map(|(index, raw)| TranscriptOp::AppendPublicInput {
+                index,
+                len: raw.len(),
+            })
+            .collect_vec();
+        for (name, fixed_commit) in self.fixed_commits()? {
+            script.push(TranscriptOp::WriteFixedCommit {
+                name: name.clone(),
+                absorbs: absorbs(&fixed_commit)?,
+            });
+        }
+        let wits_commits = chain!(
+            vm_proof.opcode_proofs.iter().map(|(name, (_, proof))| {
+                let ext_commit = proof.ext_wits.as_ref().map(|ext_wits| &ext_wits.commit);
+                (name, &proof.wits_commit, ext_commit)
+            }),
+            vm_proof
+                .table_proofs
+                .iter()
+                .map(|(name, (_, proof))| (name, &proof.wits_commit, None)),
+        );
+        for (name, wits_commit, ext_commit) in wits_commits {
+            let ext_absorbs = ext_commit.map_or(Ok(0), absorbs)?;
+            script.push(TranscriptOp::WriteWitsCommit {
+                name: name.clone(),
+                absorbs: absorbs(wits_commit)? + ext_absorbs,
+            });
+        }
+        script.extend(
+            (0..self.vk.num_challenges()).map(|index| TranscriptOp::ReadChallenge { index }),
+        );
+        script.push(TranscriptOp::Fork {
+            n: self.vk.circuit_vks.len(),
+        });
+        let circuits = chain!(
+            vm_proof
+                .opcode_proofs
+                .iter()
+                .map(|(name, (index, _))| (name, *index)),
+            vm_proof
+                .table_proofs
+                .iter()
+                .map(|(name, (index, _))| (name, *index)),
+        );
+        script.extend(circuits.map(|(name, index)| TranscriptOp::VerifyCircuit {
+            name: name.clone(),
+            index,
+        }));
+        Ok(script)
+    }
+
+    /// Each proof must run on the transcript forked for its circuit, which is indexed by the
+    /// circuit's position in the vk, and be of the circuit's kind. Otherwise an out-of-range
+    /// index would panic, and a circuit proven both as opcode and table circuit would let two
+    /// proofs share a transcript.
+    fn check_transcript_indices(&self, vm_proof: &ZKVMProof<E, PCS>) -> Result<(), ZKVMError> {
+        let opcode_indices = vm_proof
+            .opcode_proofs
+            .iter()
+            .map(|(name, (i, _))| (name, *i, true));
+        let table_indices = vm_proof
+            .table_proofs
+            .iter()
+            .map(|(name, (i, _))| (name, *i, false));
+        let mut seen = HashSet::new();
+        for (name, i, is_opcode_proof) in opcode_indices.chain(table_indices) {
+            let cs = self
+                .vk
+                .circuit_vks
+                .get(name)
+                .ok_or(ZKVMError::VKNotFound(name.clone()))?
+                .get_cs();
+            let is_opcode_circuit = cs.lk_table_expressions.is_empty()
+                && cs.r_table_expressions.is_empty()
+                && cs.w_table_expressions.is_empty();
+            if is_opcode_proof != is_opcode_circuit {
+                return Err(ZKVMError::VerifyError(format!(
+                    "circuit {name} is proven as {} circuit",
+                    if is_opcode_proof {
+                        "an opcode"
+                    } else {
+                        "a table"
+                    }
+                )));
+            }
+            let expected = self
+                .vk
+                .transcript_index(name)
+                .ok_or(ZKVMError::VKNotFound(name.clone()))?;
+            if i != expected {
+                return Err(ZKVMError::VerifyError(format!(
+                    "circuit {name} has transcript index {i}, expected {expected}"
+                )));
+            }
+            if !seen.insert(i) {
+                return Err(ZKVMError::VerifyError(format!(
+                    "circuit {name} shares transcript index {i} with another proof"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// verify proof and return input opening point
     #[allow(clippy::too_many_arguments)]
     pub fn verify_opcode_proof(
@@ -258,7 +473,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         transcript: &mut impl Transcript<E>,
         num_product_fanin: usize,
         _out_evals: &PointAndEval<E>,
-        challenges: &[E; 2], // derive challenge from PCS
+        challenges: &[E], // derive challenge from PCS
     ) -> Result<Point<E>, ZKVMError> {
         let cs = circuit_vk.get_cs();
         let (r_counts_per_instance, w_counts_per_instance, lk_counts_per_instance) = (
@@ -271,26 +486,62 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             ceil_log2(w_counts_per_instance),
             ceil_log2(lk_counts_per_instance),
         );
-        let (chip_record_alpha, _) = (challenges[0], challenges[1]);
+        let dummy_lookup_item = cs.dummy_lookup_item(challenges);
+
+        // reject malformed proofs before indexing into them
+        for (field, len, expected) in [
+            (
+                "r_records_in_evals",
+                proof.r_records_in_evals.len(),
+                r_counts_per_instance,
+            ),
+            (
+                "w_records_in_evals",
+                proof.w_records_in_evals.len(),
+                w_counts_per_instance,
+            ),
+            (
+                "lk_records_in_evals",
+                proof.lk_records_in_evals.len(),
+                lk_counts_per_instance,
+            ),
+            (
+                "wits_in_evals",
+                proof.wits_in_evals.len(),
+                cs.num_witin as usize,
+            ),
+        ] {
+            if len < expected {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{field} has {len} evals, expected at least {expected}"
+                )));
+            }
+        }
 
         let num_instances = proof.num_instances;
+        // a circuit without instance is left out of the proof rather than proven
+        if num_instances == 0 {
+            return Err(ZKVMError::EmptyCircuit(name.to_string()));
+        }
+        // bound the claimed instances before any padding arithmetic can overflow
+        if num_instances > MAX_NUM_INSTANCES {
+            return Err(ZKVMError::VerifyError(format!(
+                "{name} claims {num_instances} instances, at most {MAX_NUM_INSTANCES} supported"
+            )));
+        }
         let next_pow2_instance = next_pow2_instance_padding(num_instances);
         let log2_num_instances = ceil_log2(next_pow2_instance);
 
         // verify and reduce product tower sumcheck
         let tower_proofs = &proof.tower_proof;
 
+        let span = entered_span!("verify::tower", circuit = name);
         let (rt_tower, record_evals, logup_p_evals, logup_q_evals) = TowerVerify::verify(
             vec![
-                proof.record_r_out_evals.clone(),
-                proof.record_w_out_evals.clone(),
+                proof.record_r_out_evals.to_vec(),
+                proof.record_w_out_evals.to_vec(),
             ],
-            vec![vec![
-                proof.lk_p1_out_eval,
-                proof.lk_p2_out_eval,
-                proof.lk_q1_out_eval,
-                proof.lk_q2_out_eval,
-            ]],
+            vec![proof.lk_out_evals.to_vec()],
             tower_proofs,
             vec![
                 log2_num_instances + log2_r_count,
@@ -300,6 +551,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             num_product_fanin,
             transcript,
         )?;
+        exit_span!(span);
         assert!(record_evals.len() == 2, "[r_record, w_record]");
         assert!(logup_q_evals.len() == 1, "[lk_q_record]");
         assert!(logup_p_evals.len() == 1, "[lk_p_record]");
@@ -307,12 +559,14 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         // verify LogUp witness nominator p(x) ?= constant vector 1
         // index 0 is LogUp witness for Fixed Lookup table
         if logup_p_evals[0].eval != E::ONE {
-            return Err(ZKVMError::VerifyError(
-                "Lookup table witness p(x) != constant 1".into(),
-            ));
+            return Err(ZKVMError::VerifyError(format!(
+                "{name} lookup witness p(x) = {:?} != constant 1",
+                logup_p_evals[0].eval
+            )));
         }
 
         // verify zero statement (degree > 1) + sel sumcheck
+        let span = entered_span!("verify::main_sel", circuit = name);
         let (rt_r, rt_w, rt_lk): (Vec<E>, Vec<E>, Vec<E>) = (
             record_evals[0].point.clone(),
             record_evals[1].point.clone(),
@@ -329,11 +583,11 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             alpha_pow_iter.next().unwrap(),
             alpha_pow_iter.next().unwrap(),
         );
-        // alpha_read * (out_r[rt] - 1) + alpha_write * (out_w[rt] - 1) + alpha_lk * (out_lk_q - chip_record_alpha)
+        // alpha_read * (out_r[rt] - 1) + alpha_write * (out_w[rt] - 1) + alpha_lk * (out_lk_q - dummy_lookup_item)
         // + 0 // 0 come from zero check
         let claim_sum = *alpha_read * (record_evals[0].eval - E::ONE)
             + *alpha_write * (record_evals[1].eval - E::ONE)
-            + *alpha_lk * (logup_q_evals[0].eval - chip_record_alpha);
+            + *alpha_lk * (logup_q_evals[0].eval - dummy_lookup_item);
 
         let main_sel_subclaim = IOPVerifierState::verify(
             claim_sum,
@@ -342,8 +596,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 proofs: proof.main_sel_sumcheck_proofs.clone(),
             },
             &VPAuxInfo {
-                // + 1 from sel_non_lc_zero_sumcheck
-                max_degree: SEL_DEGREE.max(cs.max_non_lc_degree + 1),
+                max_degree: cs.selector_degree(),
                 num_variables: log2_num_instances,
                 phantom: PhantomData,
             },
@@ -364,27 +617,15 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         let (sel_r, sel_w, sel_lk, sel_non_lc_zero_sumcheck) = {
             // sel(rt, t)
             (
-                eq_eval_less_or_equal_than(
-                    num_instances - 1,
-                    &input_opening_point,
-                    &rt_r[log2_r_count..],
-                ),
-                eq_eval_less_or_equal_than(
-                    num_instances - 1,
-                    &input_opening_point,
-                    &rt_w[log2_w_count..],
-                ),
-                eq_eval_less_or_equal_than(
-                    num_instances - 1,
-                    &input_opening_point,
-                    &rt_lk[log2_lk_count..],
-                ),
+                sel_eval(num_instances, &input_opening_point, &rt_r[log2_r_count..]),
+                sel_eval(num_instances, &input_opening_point, &rt_w[log2_w_count..]),
+                sel_eval(num_instances, &input_opening_point, &rt_lk[log2_lk_count..]),
                 // only initialize when circuit got non empty assert_zero_sumcheck_expressions
                 {
                     let rt_non_lc_sumcheck = rt_tower[..log2_num_instances].to_vec();
                     if !cs.assert_zero_sumcheck_expressions.is_empty() {
-                        Some(eq_eval_less_or_equal_than(
-                            num_instances - 1,
+                        Some(sel_eval(
+                            num_instances,
                             &input_opening_point,
                             &rt_non_lc_sumcheck,
                         ))
@@ -418,7 +659,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 * ((0..lk_counts_per_instance)
                     .map(|i| proof.lk_records_in_evals[i] * eq_lk[i])
                     .sum::<E>()
-                    + chip_record_alpha
+                    + dummy_lookup_item
                         * (eq_lk[lk_counts_per_instance..].iter().sum::<E>() - E::ONE)),
             // degree > 1 zero exp sumcheck
             {
@@ -448,6 +689,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 "main + sel evaluation verify failed".into(),
             ));
         }
+        exit_span!(span);
         // verify records (degree = 1) statement, thus no sumcheck
         if cs
             .r_expressions
@@ -471,10 +713,25 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         }
 
         // verify zero expression (degree = 1) statement, thus no sumcheck
-        if cs.assert_zero_expressions.iter().any(|expr| {
-            eval_by_expr_with_instance(&[], &proof.wits_in_evals, pi, challenges, expr) != E::ZERO
-        }) {
-            return Err(ZKVMError::VerifyError("zero expression != 0".into()));
+        // degree > 1 zero expressions are already bound by the main + sel sumcheck above,
+        // so only the linear ones are checked here
+        debug_assert!(
+            cs.assert_zero_expressions
+                .iter()
+                .all(|expr| expr.degree() == 1)
+        );
+        if let Some((_, name)) = cs
+            .assert_zero_expressions
+            .iter()
+            .zip_eq(cs.assert_zero_expressions_namespace_map.iter())
+            .find(|(expr, _)| {
+                eval_by_expr_with_instance(&[], &proof.wits_in_evals, pi, challenges, expr)
+                    != E::ZERO
+            })
+        {
+            return Err(ZKVMError::VerifyError(format!(
+                "zero expression {name} != 0"
+            )));
         }
 
         tracing::debug!(
@@ -482,15 +739,44 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             name,
             proof.wits_in_evals.len(),
         );
-        PCS::simple_batch_verify(
-            vp,
-            &proof.wits_commit,
-            &input_opening_point,
-            &proof.wits_in_evals,
-            &proof.wits_opening_proof,
-            transcript,
-        )
-        .map_err(ZKVMError::PCSError)?;
+        if self.options.skip_pcs {
+            return Ok(input_opening_point);
+        }
+        let span = entered_span!("verify::pcs", circuit = name);
+        match &proof.ext_wits {
+            None => PCS::simple_batch_verify(
+                vp,
+                &proof.wits_commit,
+                &input_opening_point,
+                &proof.wits_in_evals,
+                &proof.wits_opening_proof,
+                transcript,
+            )
+            .map_err(ZKVMError::PCSError)?,
+            Some(ext_wits) => {
+                let (base_evals, ext_evals) =
+                    split_wits_in_evals(&proof.wits_in_evals, &ext_wits.wit_ids)?;
+                PCS::simple_batch_verify(
+                    vp,
+                    &proof.wits_commit,
+                    &input_opening_point,
+                    &base_evals,
+                    &proof.wits_opening_proof,
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?;
+                PCS::simple_batch_verify(
+                    vp,
+                    &ext_wits.commit,
+                    &input_opening_point,
+                    &ext_evals,
+                    &ext_wits.opening_proof,
+                    transcript,
+                )
+                .map_err(ZKVMError::PCSError)?;
+            }
+        }
+        exit_span!(span);
 
         Ok(input_opening_point)
     }
@@ -501,13 +787,14 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         name: &str,
         vp: &PCS::VerifierParam,
         circuit_vk: &VerifyingKey<E, PCS>,
+        fixed_commits: &(impl FixedCommitStore<E, PCS> + ?Sized),
         proof: &ZKVMTableProof<E, PCS>,
         raw_pi: &[Vec<E::BaseField>],
         pi: &[E],
         transcript: &mut impl Transcript<E>,
         num_logup_fanin: usize,
         _out_evals: &PointAndEval<E>,
-        challenges: &[E; 2],
+        challenges: &[E],
     ) -> Result<Point<E>, ZKVMError> {
         let cs = circuit_vk.get_cs();
         debug_assert!(
@@ -525,6 +812,46 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             .all_equal();
 
         // verify and reduce product tower sumcheck
+        // range check the revealed multiplicities of the tables declaring a bit width, ahead
+        // of the tower and sumcheck challenges. they are bound to the committed multiplicity
+        // at the opening point
+        let bounded_lk_tables = cs
+            .lk_table_expressions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, lk)| Some((i, lk.table_len, lk.multiplicity_bits?)))
+            .collect_vec();
+        if proof.bounded_lk_multiplicities.len() != bounded_lk_tables.len() {
+            return Err(ZKVMError::VerifyError(format!(
+                "{name} has {} bounded lookup multiplicities, expected {}",
+                proof.bounded_lk_multiplicities.len(),
+                bounded_lk_tables.len()
+            )));
+        }
+        for (&(i, table_len, bits), values) in
+            izip!(&bounded_lk_tables, &proof.bounded_lk_multiplicities)
+        {
+            let num_leaves = 1 << ceil_log2(table_len);
+            if values.len() != num_leaves {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{name} lookup table {i} reveals {} multiplicities, expected {num_leaves}",
+                    values.len()
+                )));
+            }
+            if let Some(v) = values
+                .iter()
+                .map(|v| v.to_canonical_u64())
+                .find(|v| bits < u64::BITS as usize && v >> bits != 0)
+            {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{name} lookup table {i} multiplicity {v} exceeds {bits} bits"
+                )));
+            }
+            values
+                .iter()
+                .for_each(|v| transcript.append_field_element(v));
+        }
+
         let tower_proofs = &proof.tower_proof;
 
         let expected_rounds = izip!(
@@ -553,6 +880,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
         )
         .collect_vec();
         let expected_max_rounds = expected_rounds.iter().cloned().max().unwrap();
+        let span = entered_span!("verify::tower", circuit = name);
         let (rt_tower, prod_point_and_eval, logup_p_point_and_eval, logup_q_point_and_eval) =
             TowerVerify::verify(
                 proof
@@ -571,6 +899,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                 num_logup_fanin,
                 transcript,
             )?;
+        exit_span!(span);
         assert_eq!(
             logup_q_point_and_eval.len(),
             cs.lk_table_expressions.len(),
@@ -605,6 +934,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             )
         } else {
             assert!(proof.same_r_sumcheck_proofs.is_some());
+            let span = entered_span!("verify::main_sel", circuit = name);
 
             // verify opening same point layer sumcheck
             let alpha_pow = get_challenge_pows(
@@ -684,37 +1014,74 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
                     "sel evaluation verify failed".into(),
                 ));
             }
+            exit_span!(span);
             (
                 input_opening_point,
                 [proof.rw_in_evals.to_vec(), proof.lk_in_evals.to_vec()].concat(),
             )
         };
-        // verify records (degree = 1) statement, thus no sumcheck
-        if interleave(
-            &cs.r_table_expressions, // r
-            &cs.w_table_expressions, // w
-        )
-        .map(|rw| &rw.expr)
-        .chain(
-            cs.lk_table_expressions
-                .iter()
-                .flat_map(|lk| vec![&lk.multiplicity, &lk.values]), // p, q
-        )
-        .zip_eq(in_evals)
-        .any(|(expr, expected_evals)| {
+        let eval_expr = |expr: &Expression<E>| {
             eval_by_expr_with_instance(
                 &proof.fixed_in_evals,
                 &proof.wits_in_evals,
                 pi,
                 challenges,
                 expr,
-            ) != expected_evals
-        }) {
+            )
+        };
+        let num_rw_records = cs.r_table_expressions.len() + cs.w_table_expressions.len();
+        let (rw_in_evals, lk_in_evals) = in_evals.split_at(num_rw_records);
+
+        // verify records (degree = 1) statement, thus no sumcheck
+        if interleave(
+            &cs.r_table_expressions, // r
+            &cs.w_table_expressions, // w
+        )
+        .map(|rw| &rw.expr)
+        .zip_eq(rw_in_evals)
+        .any(|(expr, expected_evals)| eval_expr(expr) != *expected_evals)
+        {
             return Err(ZKVMError::VerifyError(
                 "record evaluate != expected_evals".into(),
             ));
         }
 
+        // verify LogUp table nominator p(x) ?= multiplicity and denominator q(x) ?= table values
+        let mut bounded_multiplicities = proof.bounded_lk_multiplicities.iter();
+        for (i, (lk, evals)) in cs
+            .lk_table_expressions
+            .iter()
+            .zip_eq(lk_in_evals.chunks_exact(2))
+            .enumerate()
+        {
+            let expected_p = eval_expr(&lk.multiplicity);
+            if evals[0] != expected_p {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{name} lookup table {i} p(x) = {:?} != multiplicity {expected_p:?}",
+                    evals[0]
+                )));
+            }
+            if lk.multiplicity_bits.is_some() {
+                // the range checked multiplicities must be the committed ones
+                let values = bounded_multiplicities.next().unwrap();
+                if values.len() != 1 << input_opening_point.len() || {
+                    let revealed: DenseMultilinearExtension<E> = values.to_vec().into_mle();
+                    revealed.evaluate(&input_opening_point) != expected_p
+                } {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "{name} lookup table {i} revealed multiplicities mismatch the committed ones"
+                    )));
+                }
+            }
+            let expected_q = eval_expr(&lk.values);
+            if evals[1] != expected_q {
+                return Err(ZKVMError::VerifyError(format!(
+                    "{name} lookup table {i} q(x) = {:?} != table values {expected_q:?}",
+                    evals[1]
+                )));
+            }
+        }
+
         // verify dynamic address evaluation succinctly
         // TODO we can also skip their mpcs proof
         for r_table in cs.r_table_expressions.iter() {
@@ -750,8 +1117,12 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             );
         }
 
+        if self.options.skip_pcs {
+            return Ok(input_opening_point);
+        }
+        let span = entered_span!("verify::pcs", circuit = name);
         // do optional check of fixed_commitment openings by vk
-        if circuit_vk.fixed_commit.is_some() {
+        if let Some(fixed_commit) = fetch_fixed_commit(name, cs, fixed_commits)? {
             let Some(fixed_opening_proof) = &proof.fixed_opening_proof else {
                 return Err(ZKVMError::VerifyError(
                     "fixed openning proof shoudn't be none".into(),
@@ -759,7 +1130,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             };
             PCS::simple_batch_verify(
                 vp,
-                circuit_vk.fixed_commit.as_ref().unwrap(),
+                &fixed_commit,
                 &input_opening_point,
                 &proof.fixed_in_evals,
                 fixed_opening_proof,
@@ -783,6 +1154,7 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
             transcript,
         )
         .map_err(ZKVMError::PCSError)?;
+        exit_span!(span);
         tracing::debug!(
             "[table {}] verified opening proof for {} polys",
             name,
@@ -793,6 +1165,120 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifier<E, PCS>
     }
 }
 
+/// absorb everything the circuits share ahead of the transcript fork, in the order of
+/// [`super::prover::ZKVMProver::create_proof`]: the raw public inputs, the fixed commitments
+/// sorted by circuit name, then the witness commitments written by `write_wits_commits`.
+/// returns the challenges squeezed right after: alpha, beta, followed by any extra
+/// challenges referenced by the circuits
+pub(crate) fn absorb_up_to_fork<E, PCS, T>(
+    raw_pi: &[Vec<E::BaseField>],
+    fixed_commits: impl IntoIterator<Item = impl Borrow<PCS::Commitment>>,
+    num_challenges: usize,
+    transcript: &mut T,
+    write_wits_commits: impl FnOnce(&mut T) -> Result<(), ZKVMError>,
+) -> Result<Vec<E>, ZKVMError>
+where
+    E: ExtensionField,
+    PCS: PolynomialCommitmentScheme<E>,
+    T: Transcript<E>,
+{
+    for v in raw_pi.iter().flatten() {
+        transcript.append_field_element(v);
+    }
+    for fixed_commit in fixed_commits {
+        PCS::write_commitment(fixed_commit.borrow(), transcript).map_err(ZKVMError::PCSError)?;
+    }
+    write_wits_commits(transcript)?;
+    Ok((0..num_challenges)
+        .map(|_| transcript.read_challenge().elements)
+        .collect_vec())
+}
+
+/// write the witness commitments of `vm_proof` in the order of the prover: opcode witness
+/// commitments, then table witness commitments, each sorted by circuit name. the opcode
+/// circuit named in `replaced`, if any, is written with the given commitment instead
+pub(crate) fn write_wits_commitments<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+    vm_proof: &ZKVMProof<E, PCS>,
+    replaced: Option<(&str, &PCS::Commitment)>,
+    transcript: &mut impl Transcript<E>,
+) -> Result<(), ZKVMError> {
+    for (name, (_, proof)) in vm_proof.opcode_proofs.iter() {
+        tracing::debug!("read {}'s commit", name);
+        match replaced {
+            Some((replaced_name, commit)) if replaced_name == name => {
+                PCS::write_commitment(commit, transcript).map_err(ZKVMError::PCSError)?
+            }
+            _ => write_opcode_commitments(proof, transcript)?,
+        }
+    }
+    for (name, (_, proof)) in vm_proof.table_proofs.iter() {
+        tracing::debug!("read {}'s commit", name);
+        PCS::write_commitment(&proof.wits_commit, transcript).map_err(ZKVMError::PCSError)?;
+    }
+    Ok(())
+}
+
+/// write the witness commitment(s) of an opcode proof, base field batch first
+pub(crate) fn write_opcode_commitments<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+    proof: &ZKVMOpcodeProof<E, PCS>,
+    transcript: &mut impl Transcript<E>,
+) -> Result<(), ZKVMError> {
+    PCS::write_commitment(&proof.wits_commit, transcript).map_err(ZKVMError::PCSError)?;
+    if let Some(ext_wits) = &proof.ext_wits {
+        PCS::write_commitment(&ext_wits.commit, transcript).map_err(ZKVMError::PCSError)?;
+    }
+    Ok(())
+}
+
+/// split `wits_in_evals` into the evals of the base field batch and of the extension field
+/// batch holding the columns `ext_wit_ids`, which must be strictly increasing and in range.
+/// both batches must be non-empty, an unsplit witness is committed in the base batch alone
+fn split_wits_in_evals<E: Copy>(
+    wits_in_evals: &[E],
+    ext_wit_ids: &[usize],
+) -> Result<(Vec<E>, Vec<E>), ZKVMError> {
+    if ext_wit_ids.is_empty()
+        || ext_wit_ids.len() >= wits_in_evals.len()
+        || !ext_wit_ids.iter().tuple_windows().all(|(a, b)| a < b)
+        || ext_wit_ids
+            .last()
+            .is_some_and(|&id| id >= wits_in_evals.len())
+    {
+        return Err(ZKVMError::VerifyError(format!(
+            "malformed extension witness ids {ext_wit_ids:?} for {} witnesses",
+            wits_in_evals.len()
+        )));
+    }
+    let (ext_evals, base_evals) = wits_in_evals
+        .iter()
+        .enumerate()
+        .partition::<Vec<_>, _>(|(i, _)| ext_wit_ids.binary_search(i).is_ok());
+    let evals = |evals: Vec<(usize, &E)>| evals.into_iter().map(|(_, eval)| *eval).collect_vec();
+    Ok((evals(base_evals), evals(ext_evals)))
+}
+
+/// number of dummy items an opcode circuit with `num_lks` lookups per instance pads its
+/// logup tower with, i.e. the leaves of `num_lks` interleaved lookup records left unused
+pub(crate) fn num_padded_lk_items(num_lks: usize, num_instances: usize) -> usize {
+    (next_pow2_instance_padding(num_instances) << ceil_log2(num_lks)) - num_lks * num_instances
+}
+
+/// fixed commitment of circuit `name` from `store`, or `None` if the circuit has no
+/// fixed columns. a circuit with fixed columns missing from the store is an error
+pub(crate) fn fetch_fixed_commit<'s, E: ExtensionField, PCS: PolynomialCommitmentScheme<E>>(
+    name: &str,
+    cs: &ConstraintSystem<E>,
+    store: &'s (impl FixedCommitStore<E, PCS> + ?Sized),
+) -> Result<Option<Cow<'s, PCS::Commitment>>, ZKVMError> {
+    if cs.num_fixed == 0 {
+        return Ok(None);
+    }
+    store
+        .fixed_commit(name)
+        .map(Some)
+        .ok_or_else(|| ZKVMError::VKNotFound(name.to_string()))
+}
+
 pub struct TowerVerify;
 
 pub type TowerVerifyResult<E> = Result<
@@ -814,21 +1300,49 @@ impl TowerVerify {
         num_fanin: usize,
         transcript: &mut impl Transcript<E>,
     ) -> TowerVerifyResult<E> {
-        // XXX to sumcheck batched product argument with logup, we limit num_product_fanin to 2
-        // TODO mayber give a better naming?
-        assert_eq!(num_fanin, 2);
+        assert!(
+            num_fanin >= 2 && num_fanin.is_power_of_two(),
+            "fanin {num_fanin} is not a power of two"
+        );
+        // logup layers are binary, i.e. [p1, p2, q1, q2], so a tower batching logup
+        // specs with product specs is limited to fanin 2
+        assert!(
+            logup_out_evals.is_empty() || num_fanin == 2,
+            "logup towers only support fanin 2"
+        );
         let num_prod_spec = prod_out_evals.len();
         let num_logup_spec = logup_out_evals.len();
 
         let log2_num_fanin = ceil_log2(num_fanin);
         // sanity check
-        assert!(num_prod_spec == tower_proofs.prod_spec_size());
         assert!(prod_out_evals.iter().all(|evals| evals.len() == num_fanin));
-        assert!(num_logup_spec == tower_proofs.logup_spec_size());
         assert!(logup_out_evals.iter().all(|evals| {
             evals.len() == 4 // [p1, p2, q1, q2]
         }));
         assert_eq!(num_variables.len(), num_prod_spec + num_logup_spec);
+        // every spec needs at least its output layer
+        if num_variables.is_empty() || num_variables.contains(&0) {
+            return Err(ZKVMError::VerifyError("empty tower".into()));
+        }
+        if let Some(num_vars) = num_variables
+            .iter()
+            .find(|num_vars| *num_vars % log2_num_fanin != 0)
+        {
+            return Err(ZKVMError::VerifyError(format!(
+                "{num_vars} variables can not be split into layers of fanin {num_fanin}"
+            )));
+        }
+        // each layer consumes log2_num_fanin variables
+        let num_layers = num_variables
+            .iter()
+            .map(|num_vars| num_vars / log2_num_fanin)
+            .collect_vec();
+        tower_proofs.validate(
+            num_prod_spec,
+            num_logup_spec,
+            num_fanin,
+            &num_layers.iter().map(|layers| layers - 1).collect_vec(),
+        )?;
 
         let alpha_pows = get_challenge_pows(
             num_prod_spec + num_logup_spec * 2, /* logup occupy 2 sumcheck: numerator and denominator */
@@ -877,9 +1391,9 @@ impl TowerVerify {
             .map(|(point_n_eval, alpha)| point_n_eval.eval * alpha)
             .sum::<E>();
 
-        let max_num_variables = num_variables.iter().max().unwrap();
+        let max_num_layers = num_layers.iter().max().unwrap();
 
-        let (next_rt, _) = (0..(max_num_variables-1)).try_fold(
+        let (next_rt, _) = (0..(max_num_layers-1)).try_fold(
             (
                 PointAndEval {
                     point: initial_rt,
@@ -889,6 +1403,14 @@ impl TowerVerify {
             ),
             |(point_and_eval, alpha_pows), round| {
                 let (out_rt, out_claim) = (&point_and_eval.point, &point_and_eval.eval);
+                // every layer adds log2_num_fanin variables to the point
+                let num_variables = (round + 1) * log2_num_fanin;
+                if tower_proofs.proofs[round].len() != num_variables {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "tower round {round} sumcheck has {} messages, expected {num_variables}",
+                        tower_proofs.proofs[round].len()
+                    )));
+                }
                 let sumcheck_claim = IOPVerifierState::verify(
                     *out_claim,
                     &IOPProof {
@@ -896,18 +1418,24 @@ impl TowerVerify {
                         proofs: tower_proofs.proofs[round].clone(),
                     },
                     &VPAuxInfo {
-                        max_degree: NUM_FANIN + 1, // + 1 for eq
-                        num_variables: (round + 1) * log2_num_fanin,
+                        max_degree: num_fanin + 1, // + 1 for eq
+                        num_variables,
                         phantom: PhantomData,
                     },
                     transcript,
                 );
+                if sumcheck_claim.point.len() != num_variables {
+                    return Err(ZKVMError::VerifyError(format!(
+                        "tower round {round} sumcheck point has {} variables, expected {num_variables}",
+                        sumcheck_claim.point.len()
+                    )));
+                }
 
                 // check expected_evaluation
                 let rt: Point<E> = sumcheck_claim.point.iter().map(|c| c.elements).collect();
                 let expected_evaluation: E = (0..num_prod_spec)
                     .zip(alpha_pows.iter())
-                    .zip(num_variables.iter())
+                    .zip(num_layers.iter())
                     .map(|((spec_index, alpha), max_round)| {
                         eq_eval(out_rt, &rt)
                             * alpha
@@ -918,7 +1446,7 @@ impl TowerVerify {
                     .sum::<E>()
                     + (0..num_logup_spec)
                         .zip_eq(alpha_pows[num_prod_spec..].chunks(2))
-                        .zip_eq(num_variables[num_prod_spec..].iter())
+                        .zip_eq(num_layers[num_prod_spec..].iter())
                         .map(|((spec_index, alpha), max_round)| {
                             let (alpha_numerator, alpha_denominator) = (&alpha[0], &alpha[1]);
                             eq_eval(out_rt, &rt) * if round < *max_round-1 {
@@ -945,6 +1473,8 @@ impl TowerVerify {
                 let coeffs = build_eq_x_r_vec_sequential(&r_merge);
                 assert_eq!(coeffs.len(), num_fanin);
                 let rt_prime = [rt, r_merge].concat();
+                // catch a transcript desync at the round it happens
+                tower_proofs.check_round_point(round, &rt_prime, &num_layers)?;
 
                 // generate next round challenge
                 let next_alpha_pows = get_challenge_pows(
@@ -954,7 +1484,7 @@ impl TowerVerify {
                 let next_round = round + 1;
                 let next_prod_spec_evals = (0..num_prod_spec)
                     .zip(next_alpha_pows.iter())
-                    .zip(num_variables.iter())
+                    .zip(num_layers.iter())
                     .map(|((spec_index, alpha), max_round)| {
                         if round < max_round -1 {
                             // merged evaluation
@@ -978,7 +1508,7 @@ impl TowerVerify {
                     .sum::<E>();
                 let next_logup_spec_evals = (0..num_logup_spec)
                     .zip_eq(next_alpha_pows[num_prod_spec..].chunks(2))
-                    .zip_eq(num_variables[num_prod_spec..].iter())
+                    .zip_eq(num_layers[num_prod_spec..].iter())
                     .map(|((spec_index, alpha), max_round)| {
                         if round < max_round -1 {
                             let (alpha_numerator, alpha_denominator) = (&alpha[0], &alpha[1]);
@@ -1028,3 +1558,61 @@ impl TowerVerify {
         ))
     }
 }
+
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMOpcodeProof<E, PCS> {
+    /// Input opening point of this proof, derived by running the tower and main sel sumcheck
+    /// verifiers. `transcript` must be in the state [`ZKVMVerifier::verify_opcode_proof`]
+    /// receives it in.
+    ///
+    /// The final evaluations and PCS openings are not checked, so the point is only
+    /// meaningful for a proof which is trusted or verified separately.
+    pub fn opening_point(
+        &self,
+        cs: &ConstraintSystem<E>,
+        challenges: &[E],
+        transcript: &mut impl Transcript<E>,
+    ) -> Result<Point<E>, ZKVMError> {
+        let log2_num_instances = ceil_log2(next_pow2_instance_padding(self.num_instances));
+        let (_, record_evals, _, logup_q_evals) = TowerVerify::verify(
+            vec![
+                self.record_r_out_evals.to_vec(),
+                self.record_w_out_evals.to_vec(),
+            ],
+            vec![self.lk_out_evals.to_vec()],
+            &self.tower_proof,
+            vec![
+                log2_num_instances + ceil_log2(cs.r_expressions.len()),
+                log2_num_instances + ceil_log2(cs.w_expressions.len()),
+                log2_num_instances + ceil_log2(cs.lk_expressions.len()),
+            ],
+            NUM_FANIN,
+            transcript,
+        )?;
+
+        let alpha_pow = get_challenge_pows(
+            MAINCONSTRAIN_SUMCHECK_BATCH_SIZE + cs.assert_zero_sumcheck_expressions.len(),
+            transcript,
+        );
+        let claim_sum = alpha_pow[0] * (record_evals[0].eval - E::ONE)
+            + alpha_pow[1] * (record_evals[1].eval - E::ONE)
+            + alpha_pow[2] * (logup_q_evals[0].eval - cs.dummy_lookup_item(challenges));
+        let main_sel_subclaim = IOPVerifierState::verify(
+            claim_sum,
+            &IOPProof {
+                point: vec![],
+                proofs: self.main_sel_sumcheck_proofs.clone(),
+            },
+            &VPAuxInfo {
+                max_degree: cs.selector_degree(),
+                num_variables: log2_num_instances,
+                phantom: PhantomData,
+            },
+            transcript,
+        );
+        Ok(main_sel_subclaim
+            .point
+            .iter()
+            .map(|c| c.elements)
+            .collect_vec())
+    }
+}
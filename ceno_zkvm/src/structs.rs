@@ -3,25 +3,33 @@ use crate::{
     error::ZKVMError,
     expression::Expression,
     instructions::Instruction,
+    scheme::constants::MIN_NUM_CHALLENGES,
     state::StateCircuit,
     tables::TableCircuit,
+    utils::next_pow2_instance_padding,
     witness::{LkMultiplicity, RowMajorMatrix},
 };
-use ceno_emul::{CENO_PLATFORM, Platform, StepRecord};
+use ceno_emul::{CENO_PLATFORM, InsnKind, Platform, StepRecord};
+use ff::Field;
 use ff_ext::ExtensionField;
 use itertools::{Itertools, chain};
 use mpcs::PolynomialCommitmentScheme;
 use multilinear_extensions::{
-    mle::DenseMultilinearExtension, virtual_poly_v2::ArcMultilinearExtension,
+    mle::{DenseMultilinearExtension, IntoMLE},
+    virtual_poly_v2::ArcMultilinearExtension,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
 };
-use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
 use strum_macros::EnumIter;
 use sumcheck::structs::IOPProverMessage;
 
 pub struct TowerProver;
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
 pub struct TowerProofs<E: ExtensionField> {
     pub proofs: Vec<Vec<IOPProverMessage<E>>>,
     // specs -> layers -> evals
@@ -237,9 +245,123 @@ impl<E: ExtensionField> ZKVMFixedTraces<E> {
     }
 }
 
+/// registers opcode circuits by the instruction kind they prove, alongside table circuits,
+/// and produces proving and verifying keys from them in one go. circuits are keyed by name,
+/// so the index each circuit gets in `transcript.fork` is the same on both sides regardless
+/// of registration order
+pub struct ZKVMBuilder<E: ExtensionField> {
+    cs: ZKVMConstraintSystem<E>,
+    fixed_traces: ZKVMFixedTraces<E>,
+    opcode_circuits: BTreeMap<InsnKind, String>,
+}
+
+impl<E: ExtensionField> Default for ZKVMBuilder<E> {
+    fn default() -> Self {
+        Self::new(ProgramParams::default())
+    }
+}
+
+impl<E: ExtensionField> ZKVMBuilder<E> {
+    pub fn new(params: ProgramParams) -> Self {
+        ZKVMBuilder {
+            cs: ZKVMConstraintSystem::new_with_platform(params),
+            fixed_traces: ZKVMFixedTraces::default(),
+            opcode_circuits: BTreeMap::new(),
+        }
+    }
+
+    /// register the circuit proving instructions of `kind`
+    pub fn register_opcode_circuit<OC: Instruction<E>>(
+        &mut self,
+        kind: InsnKind,
+    ) -> OC::InstructionConfig {
+        assert!(
+            self.opcode_circuits.insert(kind, OC::name()).is_none(),
+            "{kind} already registered"
+        );
+        let config = self.cs.register_opcode_circuit::<OC>();
+        self.fixed_traces.register_opcode_circuit::<OC>(&self.cs);
+        config
+    }
+
+    pub fn register_table_circuit<TC: TableCircuit<E>>(
+        &mut self,
+        input: &TC::FixedInput,
+    ) -> TC::TableConfig {
+        let config = self.cs.register_table_circuit::<TC>();
+        self.fixed_traces
+            .register_table_circuit::<TC>(&self.cs, &config, input);
+        config
+    }
+
+    pub fn register_global_state<SC: StateCircuit<E>>(&mut self) {
+        self.cs.register_global_state::<SC>();
+    }
+
+    /// name of the circuit registered for instructions of `kind`
+    pub fn circuit_name(&self, kind: InsnKind) -> Option<&String> {
+        self.opcode_circuits.get(&kind)
+    }
+
+    pub fn constraint_system(&self) -> &ZKVMConstraintSystem<E> {
+        &self.cs
+    }
+
+    pub fn key_gen<PCS: PolynomialCommitmentScheme<E>>(
+        self,
+        pp: PCS::ProverParam,
+        vp: PCS::VerifierParam,
+    ) -> Result<(ZKVMProvingKey<E, PCS>, ZKVMVerifyingKey<E, PCS>), ZKVMError> {
+        let pk = self.cs.key_gen::<PCS>(pp, vp, self.fixed_traces)?;
+        let vk = pk.get_vk();
+        assert!(
+            pk.circuit_pks.keys().eq(vk.circuit_vks.keys()),
+            "pk and vk circuit order diverged"
+        );
+        Ok((pk, vk))
+    }
+}
+
+/// a single unpadded witness column, kept in the base field unless it
+/// genuinely carries extension field values
+#[derive(Clone, Debug)]
+pub enum WitnessColumn<E: ExtensionField> {
+    Base(Vec<E::BaseField>),
+    Ext(Vec<E>),
+}
+
+impl<E: ExtensionField> WitnessColumn<E> {
+    pub fn len(&self) -> usize {
+        match self {
+            WitnessColumn::Base(column) => column.len(),
+            WitnessColumn::Ext(column) => column.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// zero pad to `len` rows and turn into a mle of the same field type
+    pub fn into_padded_mle(self, len: usize) -> DenseMultilinearExtension<E> {
+        match self {
+            WitnessColumn::Base(mut column) => {
+                column.resize(len, E::BaseField::ZERO);
+                column.into_mle()
+            }
+            WitnessColumn::Ext(mut column) => {
+                column.resize(len, E::ZERO);
+                column.into_mle()
+            }
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct ZKVMWitnesses<E: ExtensionField> {
     witnesses_opcodes: BTreeMap<String, RowMajorMatrix<E::BaseField>>,
+    /// opcode witnesses given column by column, each column in its own field type
+    witnesses_opcode_columns: BTreeMap<String, Vec<WitnessColumn<E>>>,
     witnesses_tables: BTreeMap<String, RowMajorMatrix<E::BaseField>>,
     lk_mlts: BTreeMap<String, LkMultiplicity>,
     combined_lk_mlt: Option<Vec<HashMap<u64, usize>>>,
@@ -266,6 +388,7 @@ impl<E: ExtensionField> ZKVMWitnesses<E> {
         let (witness, logup_multiplicity) =
             OC::assign_instances(config, cs.num_witin as usize, records)?;
         assert!(self.witnesses_opcodes.insert(OC::name(), witness).is_none());
+        assert!(!self.witnesses_opcode_columns.contains_key(&OC::name()));
         assert!(!self.witnesses_tables.contains_key(&OC::name()));
         assert!(
             self.lk_mlts
@@ -276,6 +399,36 @@ impl<E: ExtensionField> ZKVMWitnesses<E> {
         Ok(())
     }
 
+    /// assign the witness of opcode circuit `OC` as unpadded `columns`, which keep their own
+    /// field type through proving. `lk_multiplicity` counts the lookups of the columns
+    pub fn assign_opcode_columns<OC: Instruction<E>>(
+        &mut self,
+        cs: &ZKVMConstraintSystem<E>,
+        columns: Vec<WitnessColumn<E>>,
+        lk_multiplicity: LkMultiplicity,
+    ) -> Result<(), ZKVMError> {
+        assert!(self.combined_lk_mlt.is_none());
+
+        let cs = cs.get_cs(&OC::name()).unwrap();
+        if columns.len() != cs.num_witin as usize {
+            return Err(ZKVMError::WitnessCountMismatch {
+                expected: cs.num_witin as usize,
+                got: columns.len(),
+            });
+        }
+        assert!(columns.iter().map(|column| column.len()).all_equal());
+        assert!(
+            self.witnesses_opcode_columns
+                .insert(OC::name(), columns)
+                .is_none()
+        );
+        assert!(!self.witnesses_opcodes.contains_key(&OC::name()));
+        assert!(!self.witnesses_tables.contains_key(&OC::name()));
+        assert!(self.lk_mlts.insert(OC::name(), lk_multiplicity).is_none());
+
+        Ok(())
+    }
+
     // merge the multiplicities in each opcode circuit into one
     pub fn finalize_lk_multiplicities(&mut self) {
         assert!(self.combined_lk_mlt.is_none());
@@ -319,14 +472,57 @@ impl<E: ExtensionField> ZKVMWitnesses<E> {
         )?;
         assert!(self.witnesses_tables.insert(TC::name(), witness).is_none());
         assert!(!self.witnesses_opcodes.contains_key(&TC::name()));
+        assert!(!self.witnesses_opcode_columns.contains_key(&TC::name()));
 
         Ok(())
     }
 
     /// Iterate opcode circuits, then table circuits, sorted by name.
+    /// opcode witnesses assigned by [`Self::assign_opcode_columns`] are left out
     pub fn into_iter_sorted(self) -> impl Iterator<Item = (String, RowMajorMatrix<E::BaseField>)> {
         chain(self.witnesses_opcodes, self.witnesses_tables)
     }
+
+    /// Iterate the number of instances and padded witness mles of opcode circuits, then table
+    /// circuits, sorted by name. a circuit without instance comes with no mle
+    pub fn into_mles_sorted(
+        self,
+    ) -> impl Iterator<Item = (String, usize, Vec<DenseMultilinearExtension<E>>)> {
+        let rows_into_mles = |(name, witness): (String, RowMajorMatrix<E::BaseField>)| {
+            let num_instances = witness.num_instances();
+            let mles = match num_instances {
+                0 => vec![],
+                _ => witness.into_mles::<E>(),
+            };
+            (name, num_instances, mles)
+        };
+        let columns_into_mles = |(name, columns): (String, Vec<WitnessColumn<E>>)| {
+            let num_instances = columns.first().map_or(0, |column| column.len());
+            let mles = match num_instances {
+                0 => vec![],
+                _ => columns
+                    .into_iter()
+                    .map(|column| column.into_padded_mle(next_pow2_instance_padding(num_instances)))
+                    .collect(),
+            };
+            (name, num_instances, mles)
+        };
+        // both maps are sorted by name, so merging them keeps the opcodes sorted
+        let opcodes = self
+            .witnesses_opcodes
+            .into_iter()
+            .map(rows_into_mles)
+            .merge_by(
+                self.witnesses_opcode_columns
+                    .into_iter()
+                    .map(columns_into_mles),
+                |(a, ..), (b, ..)| a < b,
+            );
+        chain(
+            opcodes,
+            self.witnesses_tables.into_iter().map(rows_into_mles),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -367,6 +563,22 @@ impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMProvingKey<E, PC
             finalize_global_state_expr: self.finalize_global_state_expr.clone(),
         }
     }
+
+    /// index of the transcript fork circuit `name` is proven on, i.e. its position in the
+    /// circuits sorted by name
+    pub fn transcript_index(&self, name: &str) -> Option<usize> {
+        self.circuit_pks
+            .keys()
+            .position(|circuit_name| circuit_name == name)
+    }
+
+    /// number of challenges to squeeze from the transcript after committing to witnesses
+    pub fn num_challenges(&self) -> usize {
+        num_challenges(self.circuit_pks.values().map(|pk| pk.get_cs()), [
+            &self.initial_global_state_expr,
+            &self.finalize_global_state_expr,
+        ])
+    }
 }
 
 #[derive(Clone)]
@@ -378,3 +590,69 @@ pub struct ZKVMVerifyingKey<E: ExtensionField, PCS: PolynomialCommitmentScheme<E
     pub initial_global_state_expr: Expression<E>,
     pub finalize_global_state_expr: Expression<E>,
 }
+
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> ZKVMVerifyingKey<E, PCS> {
+    /// [`ConstraintSystem::digest`] of circuit `name`, if present
+    pub fn circuit_digest(&self, name: &str) -> Option<[u8; 32]> {
+        self.circuit_vks.get(name).map(|vk| vk.get_cs().digest())
+    }
+
+    /// circuits with a fixed commitment, in the order their commitments are written to the
+    /// transcript (sorted by circuit name)
+    pub fn fixed_commit_order(&self) -> impl Iterator<Item = (&String, &PCS::Commitment)> {
+        self.circuit_vks
+            .iter()
+            .filter_map(|(name, vk)| vk.fixed_commit.as_ref().map(|commit| (name, commit)))
+    }
+
+    /// index of the transcript fork circuit `name` is verified on, i.e. its position in the
+    /// circuits sorted by name
+    pub fn transcript_index(&self, name: &str) -> Option<usize> {
+        self.circuit_vks
+            .keys()
+            .position(|circuit_name| circuit_name == name)
+    }
+
+    /// number of challenges to squeeze from the transcript after committing to witnesses
+    pub fn num_challenges(&self) -> usize {
+        num_challenges(self.circuit_vks.values().map(|vk| vk.get_cs()), [
+            &self.initial_global_state_expr,
+            &self.finalize_global_state_expr,
+        ])
+    }
+}
+
+/// source of the fixed commitments of table circuits, looked up by circuit name.
+/// lets the verifier load large fixed tables lazily instead of keeping every
+/// commitment in [`ZKVMVerifyingKey`]
+pub trait FixedCommitStore<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> {
+    fn fixed_commit(&self, name: &str) -> Option<Cow<'_, PCS::Commitment>>;
+}
+
+/// in-memory store backed by the commitments held in the verifying key
+impl<E: ExtensionField, PCS: PolynomialCommitmentScheme<E>> FixedCommitStore<E, PCS>
+    for ZKVMVerifyingKey<E, PCS>
+{
+    fn fixed_commit(&self, name: &str) -> Option<Cow<'_, PCS::Commitment>> {
+        self.circuit_vks
+            .get(name)
+            .and_then(|vk| vk.fixed_commit.as_ref())
+            .map(Cow::Borrowed)
+    }
+}
+
+/// max challenge id referenced across all circuits and global state expressions, plus one.
+/// alpha and beta are always sampled as they are used by the record rlc and dummy table item
+fn num_challenges<'a, E: ExtensionField>(
+    css: impl Iterator<Item = &'a ConstraintSystem<E>>,
+    global_state_exprs: [&Expression<E>; 2],
+) -> usize {
+    css.map(|cs| cs.num_challenges())
+        .chain(
+            global_state_exprs
+                .into_iter()
+                .filter_map(|expr| expr.max_challenge_id())
+                .map(|id| id as usize + 1),
+        )
+        .fold(MIN_NUM_CHALLENGES, usize::max)
+}
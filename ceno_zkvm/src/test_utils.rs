@@ -0,0 +1,70 @@
+use ff_ext::ExtensionField;
+use goldilocks::SmallField;
+use transcript::{BasicTranscript, Transcript};
+
+/// deterministic source of witness values for tests, driven by a transcript seeded with a
+/// byte string. tests sharing a seed see the same values, so a failure can be reproduced
+/// from the seed alone
+pub struct WitnessRng<E: ExtensionField> {
+    transcript: BasicTranscript<E>,
+}
+
+impl<E: ExtensionField> WitnessRng<E> {
+    pub fn new(seed: &'static [u8]) -> Self {
+        Self {
+            transcript: BasicTranscript::new(seed),
+        }
+    }
+
+    /// next extension field element, advancing the transcript
+    pub fn next_ext(&mut self) -> E {
+        self.transcript
+            .get_and_append_challenge(b"witness_rng")
+            .elements
+    }
+
+    /// next base field element, advancing the transcript
+    pub fn next_base(&mut self) -> E::BaseField {
+        self.next_ext().as_bases()[0]
+    }
+
+    /// next base field element below `bound`, e.g. for range checked witnesses
+    pub fn next_bounded(&mut self, bound: u64) -> E::BaseField {
+        E::BaseField::from(self.next_base().to_canonical_u64() % bound)
+    }
+
+    pub fn base_vec(&mut self, len: usize) -> Vec<E::BaseField> {
+        (0..len).map(|_| self.next_base()).collect()
+    }
+
+    pub fn ext_vec(&mut self, len: usize) -> Vec<E> {
+        (0..len).map(|_| self.next_ext()).collect()
+    }
+}
+
+mod tests {
+    use goldilocks::{GoldilocksExt2, SmallField};
+
+    use super::WitnessRng;
+
+    type E = GoldilocksExt2;
+
+    #[test]
+    fn test_witness_rng_is_deterministic() {
+        let mut a = WitnessRng::<E>::new(b"seed");
+        let mut b = WitnessRng::<E>::new(b"seed");
+        assert_eq!(a.base_vec(16), b.base_vec(16));
+        assert_eq!(a.ext_vec(16), b.ext_vec(16));
+
+        let mut c = WitnessRng::<E>::new(b"other seed");
+        let mut a = WitnessRng::<E>::new(b"seed");
+        assert_ne!(a.base_vec(16), c.base_vec(16));
+
+        // consecutive values differ as the transcript advances
+        let values = WitnessRng::<E>::new(b"seed").base_vec(2);
+        assert_ne!(values[0], values[1]);
+
+        let mut rng = WitnessRng::<E>::new(b"seed");
+        assert!((0..16).all(|_| rng.next_bounded(1 << 8).to_canonical_u64() < 1 << 8));
+    }
+}
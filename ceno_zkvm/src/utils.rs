@@ -63,17 +63,40 @@ pub fn get_challenge_pows<E: ExtensionField>(
     size: usize,
     transcript: &mut impl Transcript<E>,
 ) -> Vec<E> {
-    // println!("alpha_pow");
-    let alpha = transcript
-        .get_and_append_challenge(b"combine subset evals")
-        .elements;
-    (0..size)
-        .scan(E::ONE, |state, _| {
-            let res = *state;
-            *state *= alpha;
-            Some(res)
-        })
-        .collect_vec()
+    ChallengePowers::new(transcript).powers(size).to_vec()
+}
+
+/// powers of a single challenge derived from transcript, computed on demand and cached,
+/// so asking for more powers neither squeezes the transcript again nor recomputes them
+pub struct ChallengePowers<E: ExtensionField> {
+    alpha: E,
+    pows: Vec<E>,
+}
+
+impl<E: ExtensionField> ChallengePowers<E> {
+    pub fn new(transcript: &mut impl Transcript<E>) -> Self {
+        let alpha = transcript
+            .get_and_append_challenge(b"combine subset evals")
+            .elements;
+        ChallengePowers {
+            alpha,
+            pows: vec![E::ONE],
+        }
+    }
+
+    /// alpha^k
+    pub fn pow(&mut self, k: usize) -> E {
+        self.powers(k + 1)[k]
+    }
+
+    /// [1, alpha, ..., alpha^(n-1)]
+    pub fn powers(&mut self, n: usize) -> &[E] {
+        while self.pows.len() < n {
+            let next = *self.pows.last().unwrap() * self.alpha;
+            self.pows.push(next);
+        }
+        &self.pows[..n]
+    }
 }
 
 // split single u64 value into W slices, each slice got C bits.
@@ -92,12 +115,21 @@ pub fn u64vec<const W: usize, const C: usize>(x: u64) -> [u64; W] {
 /// we expect each thread at least take 4 num of sumcheck variables
 /// return optimal num threads to run sumcheck
 pub fn optimal_sumcheck_threads(num_vars: usize) -> usize {
-    let expected_max_threads = max_usable_threads();
-    let min_numvar_per_thread = 4;
+    proper_num_threads_for(num_vars, 1, max_usable_threads())
+}
+
+/// return num threads, a power of 2 capped by `max_threads`, to run a sumcheck batching
+/// `num_terms` mle terms over `num_vars` variables.
+/// each thread takes at least 4 sumcheck variables, one less per doubling of terms beyond 16,
+/// so a term heavy sumcheck over few variables still gets parallelized
+pub fn proper_num_threads_for(num_vars: usize, num_terms: usize, max_threads: usize) -> usize {
+    let max_threads = 1 << max_threads.max(1).ilog2();
+    let extra_log2_terms = num_terms.max(1).ilog2().saturating_sub(4) as usize;
+    let min_numvar_per_thread = 4usize.saturating_sub(extra_log2_terms).max(1);
     if num_vars <= min_numvar_per_thread {
         1
     } else {
-        (1 << (num_vars - min_numvar_per_thread)).min(expected_max_threads)
+        (1 << (num_vars - min_numvar_per_thread)).min(max_threads)
     }
 }
 
@@ -151,6 +183,15 @@ pub(crate) fn eq_eval_less_or_equal_than<E: ExtensionField>(max_idx: usize, a: &
     ans
 }
 
+/// evaluation of the selector of the first `num_instances` rows at two points of the same
+/// length, i.e. \sum_{i < num_instances} eq(a, i) * eq(b, i). symmetric in `a` and `b`,
+/// as every term of [`eq_eval_less_or_equal_than`] is once both have the same length
+pub(crate) fn sel_eval<E: ExtensionField>(num_instances: usize, a: &[E], b: &[E]) -> E {
+    assert_eq!(a.len(), b.len());
+    assert!(num_instances > 0 && num_instances <= 1 << a.len());
+    eq_eval_less_or_equal_than(num_instances - 1, a, b)
+}
+
 /// evaluate MLE M(x0, x1, x2, ..., xn) address vector with it evaluation format a*[0, 1, 2, 3, ....2^n-1] + b
 /// on r = [r0, r1, r2, ...rn] succintly
 /// a, b, is constant
@@ -229,3 +270,87 @@ where
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use goldilocks::GoldilocksExt2;
+    use itertools::Itertools;
+    use multilinear_extensions::virtual_poly::build_eq_x_r_vec_sequential;
+    use transcript::{BasicTranscript, Transcript};
+
+    use super::{ChallengePowers, get_challenge_pows, proper_num_threads_for, sel_eval};
+
+    #[test]
+    fn test_sel_eval() {
+        type E = GoldilocksExt2;
+        let a = [E::from(3), E::from(5)];
+        let b = [E::from(7), E::from(11)];
+        // sel of the 3 instances 0, 1, 2 over 2 variables
+        let (eq_a, eq_b) = (
+            build_eq_x_r_vec_sequential(&a),
+            build_eq_x_r_vec_sequential(&b),
+        );
+        let expected = (0..3).map(|i| eq_a[i] * eq_b[i]).sum::<E>();
+        assert_eq!(sel_eval(3, &a, &b), expected);
+        assert_eq!(sel_eval(3, &b, &a), expected);
+
+        // on the hypercube the selector is 1 exactly on the first 3 rows
+        let bits = |i: usize| [E::from((i & 1) as u64), E::from((i >> 1) as u64)];
+        for i in 0..4 {
+            let sel = sel_eval(3, &bits(i), &bits(i));
+            assert_eq!(sel, if i < 3 { E::ONE } else { E::ZERO });
+        }
+    }
+
+    #[test]
+    fn test_proper_num_threads_for() {
+        for max_threads in [1, 6, 8, 64] {
+            for num_terms in [0, 1, 16, 32, 100, 1 << 10] {
+                for num_vars in 0..24 {
+                    let threads = proper_num_threads_for(num_vars, num_terms, max_threads);
+                    assert!(threads.is_power_of_two() && threads <= max_threads);
+                    // every thread keeps at least one sumcheck variable
+                    assert!(threads == 1 || threads < 1 << num_vars);
+                    // monotonic in both num_vars and num_terms
+                    assert!(
+                        threads <= proper_num_threads_for(num_vars + 1, num_terms, max_threads)
+                    );
+                    assert!(
+                        threads <= proper_num_threads_for(num_vars, num_terms * 2, max_threads)
+                    );
+                }
+            }
+        }
+        // few terms keep 4 variables per thread
+        assert_eq!(proper_num_threads_for(4, 16, 64), 1);
+        assert_eq!(proper_num_threads_for(6, 16, 64), 4);
+        // while many terms parallelize small sumchecks
+        assert_eq!(proper_num_threads_for(4, 100, 64), 4);
+        assert_eq!(proper_num_threads_for(4, 1 << 10, 64), 8);
+        assert_eq!(proper_num_threads_for(20, 1 << 10, 6), 4);
+    }
+
+    #[test]
+    fn test_challenge_powers_match_get_challenge_pows() {
+        type E = GoldilocksExt2;
+        let alpha = BasicTranscript::<E>::new(b"test")
+            .get_and_append_challenge(b"combine subset evals")
+            .elements;
+        let expected = std::iter::successors(Some(E::ONE), |pow| Some(*pow * alpha))
+            .take(8)
+            .collect_vec();
+        assert_eq!(
+            get_challenge_pows::<E>(8, &mut BasicTranscript::new(b"test")),
+            expected
+        );
+
+        let mut transcript = BasicTranscript::new(b"test");
+        let mut pows = ChallengePowers::<E>::new(&mut transcript);
+        // out of order and growing requests hit the same sequence
+        assert_eq!(pows.pow(5), expected[5]);
+        assert_eq!(pows.powers(3), &expected[..3]);
+        assert_eq!(pows.powers(8), expected.as_slice());
+        assert_eq!(pows.pow(0), expected[0]);
+    }
+}
@@ -32,6 +32,25 @@ impl<'a, E: ExtensionField> VirtualPolynomials<'a, E> {
         }
     }
 
+    /// same as [`Self::new`], with the term storage pre-sized for `expected_terms` calls to
+    /// [`Self::add_mle_list`], so circuits with many record expressions do not keep
+    /// reallocating while the terms are added
+    pub fn with_capacity(
+        num_threads: usize,
+        max_num_variables: usize,
+        expected_terms: usize,
+    ) -> Self {
+        let mut virtual_polys = Self::new(num_threads, max_num_variables);
+        virtual_polys.polys.iter_mut().for_each(|poly| {
+            poly.products.reserve(expected_terms);
+            poly.flattened_ml_extensions.reserve(expected_terms);
+        });
+        virtual_polys
+            .thread_based_mles_storage
+            .reserve(expected_terms);
+        virtual_polys
+    }
+
     fn get_range_polys_by_thread_id(
         &self,
         thread_id: usize,
@@ -155,7 +174,7 @@ impl<'a, E: ExtensionField> VirtualPolynomials<'a, E> {
             .collect::<BTreeSet<u16>>()
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "sanity-check"))]
     pub fn degree(&self) -> usize {
         assert!(self.polys.iter().map(|p| p.aux_info.max_degree).all_equal());
         self.polys
@@ -226,6 +245,54 @@ mod tests {
         assert!(virtual_polys.degree() == 3);
     }
 
+    #[test]
+    fn test_with_capacity_matches_new() {
+        let (num_threads, max_num_vars, num_terms) = (4, 4, 200);
+        let mut rng = test_rng();
+        let mles: Vec<ArcMultilinearExtension<E>> = (0..num_terms + 1)
+            .map(|_| {
+                (0..1 << max_num_vars)
+                    .map(|_| Goldilocks::random(&mut rng))
+                    .collect_vec()
+                    .into_mle()
+                    .into()
+            })
+            .collect();
+        let coeffs = (0..num_terms).map(|_| E::random(&mut rng)).collect_vec();
+
+        // sel * record_i, as the record terms of an opcode circuit
+        let mut default_polys = VirtualPolynomials::new(num_threads, max_num_vars);
+        let mut sized_polys =
+            VirtualPolynomials::with_capacity(num_threads, max_num_vars, num_terms);
+        for (mle, coeff) in mles[1..].iter().zip(&coeffs) {
+            default_polys.add_mle_list(vec![&mles[0], mle], *coeff);
+            sized_polys.add_mle_list(vec![&mles[0], mle], *coeff);
+        }
+
+        let (default_polys, sized_polys) = (
+            default_polys.get_batched_polys(),
+            sized_polys.get_batched_polys(),
+        );
+        assert_eq!(default_polys.len(), sized_polys.len());
+        for (a, b) in default_polys.iter().zip_eq(&sized_polys) {
+            assert_eq!(a.aux_info, b.aux_info);
+            assert_eq!(a.products, b.products);
+            assert_eq!(a.products.len(), num_terms);
+            assert_eq!(
+                a.flattened_ml_extensions.len(),
+                b.flattened_ml_extensions.len()
+            );
+            for (f, g) in a
+                .flattened_ml_extensions
+                .iter()
+                .zip_eq(&b.flattened_ml_extensions)
+            {
+                assert_eq!(f.evaluations(), g.evaluations());
+                assert_eq!(f.evaluations_range(), g.evaluations_range());
+            }
+        }
+    }
+
     #[test]
     fn test_sumcheck_different_degree() {
         let max_num_vars = 3;
@@ -10,10 +10,13 @@ use std::{
 /// Contact Matthias, if your examples get complicated enough to need their own crates, instead of just being one file.
 const EXAMPLES: &[&str] = &[
     "ceno_rt_alloc",
+    "ceno_rt_heap_reset",
     "ceno_rt_io",
     "ceno_rt_mem",
     "ceno_rt_mini",
+    "ceno_rt_oom",
     "ceno_rt_panic",
+    "ceno_rt_slice",
     "hints",
     "sorting",
     "median",
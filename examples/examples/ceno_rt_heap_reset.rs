@@ -0,0 +1,32 @@
+extern crate ceno_rt;
+use ceno_rt::println;
+use core::fmt::Write;
+
+extern crate alloc;
+use alloc::{vec, vec::Vec};
+
+fn main() {
+    let v: Vec<u32> = (0..1000).collect();
+    let first = v.as_ptr();
+    let sum: u32 = v.iter().sum();
+    drop(v);
+
+    // SAFETY: the only heap allocation has been dropped above.
+    unsafe { ceno_rt::heap_reset() };
+
+    // The bump allocator hands out the same memory again.
+    let v: Vec<u32> = (0..1000).rev().collect();
+    assert_eq!(v.as_ptr(), first);
+    assert_eq!(v.iter().sum::<u32>(), sum);
+    println!("{sum}");
+    drop(v);
+
+    // SAFETY: all heap allocations have been dropped above.
+    unsafe { ceno_rt::heap_reset() };
+
+    // Zeroed allocations are zero even on reused memory.
+    let zeroed = vec![0u8; 4000];
+    assert_eq!(zeroed.as_ptr(), first as *const u8);
+    assert!(zeroed.iter().all(|&b| b == 0));
+    println!("{}", zeroed.len());
+}
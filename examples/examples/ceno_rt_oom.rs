@@ -0,0 +1,11 @@
+extern crate ceno_rt;
+
+extern crate alloc;
+use alloc::vec::Vec;
+use core::ptr::read_volatile;
+
+fn main() {
+    // More than the whole heap region: the allocator must fail instead of running into the stack.
+    let v: Vec<u8> = Vec::with_capacity(1 << 30);
+    unsafe { read_volatile(&v.as_ptr()) };
+}
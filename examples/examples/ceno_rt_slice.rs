@@ -0,0 +1,15 @@
+extern crate ceno_rt;
+use ceno_rt::println;
+use core::fmt::Write;
+
+fn main() {
+    let first: &[u8] = ceno_rt::read_slice();
+    let second: &[u8] = ceno_rt::read_slice();
+    assert_eq!(first.as_ptr() as usize % 4, 0);
+    assert_eq!(second.as_ptr() as usize % 4, 0);
+
+    assert_eq!(first, b"hello");
+    println!("{}", second.len());
+    let sum: u32 = second.iter().map(|&b| b as u32).sum();
+    println!("{sum}");
+}
@@ -8,7 +8,6 @@ use super::{
 };
 use crate::util::{
     arithmetic::{interpolate_over_boolean_hypercube, interpolate2_weights},
-    field_type_index_ext, field_type_iter_ext,
     hash::write_digest_to_transcript,
     log2_strict,
     merkle_tree::MerkleTree,
@@ -45,7 +44,7 @@ where
     #[cfg(feature = "sanity-check")]
     assert_eq!(point.len(), num_vars);
     let mut trees = Vec::with_capacity(num_vars);
-    let mut running_oracle = field_type_iter_ext(&comm.get_codewords()[0]).collect_vec();
+    let mut running_oracle = comm.get_codewords()[0].iter_ext().collect_vec();
     let mut running_evals = comm.polynomials_bh_evals[0].clone();
 
     #[cfg(feature = "sanity-check")]
@@ -204,7 +203,7 @@ where
         .for_each(|(index, comm)| {
             running_oracle
                 .iter_mut()
-                .zip_eq(field_type_iter_ext(&comm.get_codewords()[0]))
+                .zip_eq(comm.get_codewords()[0].iter_ext())
                 .for_each(|(r, a)| *r += a * coeffs[index]);
         });
     end_timer!(build_oracle_timer);
@@ -224,8 +223,7 @@ where
                 // to align the polynomials to the variable with index 0 before adding them
                 // together. So each element is repeated by
                 // sum_of_all_evals_for_sumcheck.len() / bh_evals.len() times
-                *r += field_type_index_ext(
-                    &comm.polynomials_bh_evals[0],
+                *r += comm.polynomials_bh_evals[0].index_as_ext(
                     pos >> (num_vars - log2_strict(comm.polynomials_bh_evals[0].len())),
                 ) * coeffs[index]
             });
@@ -291,7 +289,7 @@ where
                 .for_each(|(index, comm)| {
                     new_running_oracle
                         .iter_mut()
-                        .zip_eq(field_type_iter_ext(&comm.get_codewords()[0]))
+                        .zip_eq(comm.get_codewords()[0].iter_ext())
                         .for_each(|(r, a)| *r += a * coeffs[index]);
                 });
             running_oracle = new_running_oracle;
@@ -372,7 +370,7 @@ where
             comm.polynomials_bh_evals
                 .iter()
                 .zip(batch_coeffs)
-                .map(|(eval, coeff)| field_type_index_ext(eval, i) * *coeff)
+                .map(|(eval, coeff)| eval.index_as_ext(i) * *coeff)
                 .sum()
         })
         .collect::<Vec<_>>();
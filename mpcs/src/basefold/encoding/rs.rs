@@ -548,7 +548,7 @@ fn naive_fft<E: ExtensionField>(poly: &[E], rate: usize, shift: E::BaseField) ->
 mod tests {
     use crate::{
         basefold::encoding::test_util::test_codeword_folding,
-        util::{field_type_index_ext, plonky2_util::reverse_index_bits_in_place_field_type},
+        util::plonky2_util::reverse_index_bits_in_place_field_type,
     };
 
     use super::*;
@@ -731,23 +731,20 @@ mod tests {
         let (pp, _) = Code::trim(pp, num_vars).unwrap();
         let mut codeword = Code::encode(&pp, &poly);
         check_low_degree(&codeword, "low degree check for original codeword");
-        let c0 = field_type_index_ext(&codeword, 0);
-        let c_mid = field_type_index_ext(&codeword, codeword.len() >> 1);
-        let c1 = field_type_index_ext(&codeword, 1);
-        let c_mid1 = field_type_index_ext(&codeword, (codeword.len() >> 1) + 1);
+        let c0 = codeword.index_as_ext(0);
+        let c_mid = codeword.index_as_ext(codeword.len() >> 1);
+        let c1 = codeword.index_as_ext(1);
+        let c_mid1 = codeword.index_as_ext((codeword.len() >> 1) + 1);
 
         reverse_index_bits_in_place_field_type(&mut codeword);
         // After the bit inversion, the first element is still the first,
         // but the middle one is switched to the second.
-        assert_eq!(c0, field_type_index_ext(&codeword, 0));
-        assert_eq!(c_mid, field_type_index_ext(&codeword, 1));
+        assert_eq!(c0, codeword.index_as_ext(0));
+        assert_eq!(c_mid, codeword.index_as_ext(1));
         // The second element is placed at the middle, and the next to middle
         // element is still at the place.
-        assert_eq!(c1, field_type_index_ext(&codeword, codeword.len() >> 1));
-        assert_eq!(
-            c_mid1,
-            field_type_index_ext(&codeword, (codeword.len() >> 1) + 1)
-        );
+        assert_eq!(c1, codeword.index_as_ext(codeword.len() >> 1));
+        assert_eq!(c_mid1, codeword.index_as_ext((codeword.len() >> 1) + 1));
 
         // For RS codeword, the addition of the left and right halves is also
         // a valid codeword
@@ -795,8 +792,8 @@ mod tests {
         let c_fold1 = folded_codeword[folded_codeword.len() >> 1];
         let mut folded_codeword = FieldType::Ext(folded_codeword);
         reverse_index_bits_in_place_field_type(&mut folded_codeword);
-        assert_eq!(c_fold, field_type_index_ext(&folded_codeword, 0));
-        assert_eq!(c_fold1, field_type_index_ext(&folded_codeword, 1));
+        assert_eq!(c_fold, folded_codeword.index_as_ext(0));
+        assert_eq!(c_fold1, folded_codeword.index_as_ext(1));
 
         // The top level folding coefficient should have shift factor gamma
         let folding_coeffs = Code::prover_folding_coeffs(&pp, log2_strict(codeword.len()) - 1, 0);
@@ -878,7 +875,7 @@ mod tests {
         ifft(&mut codeword, 0, &root_table);
         for i in (codeword.len() >> <Code as EncodingScheme<E>>::get_rate_log())..codeword.len() {
             assert_eq!(
-                field_type_index_ext(&codeword, i),
+                codeword.index_as_ext(i),
                 E::ZERO,
                 "{}: zero check failed for i = {}",
                 message,
@@ -3,7 +3,7 @@ use crate::util::{
         degree_2_eval, degree_2_zero_plus_one, inner_product, interpolate_over_boolean_hypercube,
         interpolate2_weights,
     },
-    ext_to_usize, field_type_index_base, field_type_index_ext,
+    ext_to_usize, field_type_index_base,
     hash::Digest,
     log2_strict,
     merkle_tree::{MerklePathWithoutLeafOrRoot, MerkleTree},
@@ -479,14 +479,8 @@ where
 
     let commitment_query = match poly_codewords[0] {
         FieldType::Ext(_) => SimpleBatchCommitmentSingleQueryResult::new_ext(
-            poly_codewords
-                .iter()
-                .map(|c| field_type_index_ext(c, p0))
-                .collect(),
-            poly_codewords
-                .iter()
-                .map(|c| field_type_index_ext(c, p1))
-                .collect(),
+            poly_codewords.iter().map(|c| c.index_as_ext(p0)).collect(),
+            poly_codewords.iter().map(|c| c.index_as_ext(p1)).collect(),
             p0,
         ),
         FieldType::Base(_) => SimpleBatchCommitmentSingleQueryResult::new_base(
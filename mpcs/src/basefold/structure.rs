@@ -1,4 +1,5 @@
 use crate::{
+    Error,
     sum_check::classic::{Coefficients, SumcheckProof},
     util::{hash::Digest, merkle_tree::MerkleTree},
 };
@@ -111,7 +112,7 @@ where
         1 << self.num_vars
     }
 
-    pub fn get_codeword_entry_base(&self, index: usize) -> Vec<E::BaseField> {
+    pub fn get_codeword_entry_base(&self, index: usize) -> Result<Vec<E::BaseField>, Error> {
         self.codeword_tree.get_leaf_as_base(index)
     }
 
@@ -311,8 +311,31 @@ pub enum Error {
     PolynomialTooLarge(usize),
     PolynomialSizesNotEqual,
     MerkleRootMismatch,
+    MismatchedFieldType(String),
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidSumcheck(msg) => write!(f, "invalid sumcheck: {msg}"),
+            Error::InvalidPcsParam(msg) => write!(f, "invalid pcs param: {msg}"),
+            Error::InvalidPcsOpen(msg) => write!(f, "invalid pcs open: {msg}"),
+            Error::InvalidSnark(msg) => write!(f, "invalid snark: {msg}"),
+            Error::Serialization(msg) => write!(f, "serialization error: {msg}"),
+            Error::Transcript(msg) => write!(f, "transcript error: {msg}"),
+            Error::ExtensionFieldElementNotFit => write!(f, "extension field element not fit"),
+            Error::PolynomialTooLarge(num_vars) => {
+                write!(f, "polynomial too large: {num_vars} variables")
+            }
+            Error::PolynomialSizesNotEqual => write!(f, "polynomial sizes not equal"),
+            Error::MerkleRootMismatch => write!(f, "merkle root mismatch"),
+            Error::MismatchedFieldType(msg) => write!(f, "mismatched field type: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 mod basefold;
 pub use basefold::{
     Basecode, BasecodeDefaultSpec, Basefold, BasefoldBasecodeParams, BasefoldCommitment,
@@ -104,14 +104,6 @@ pub fn field_type_index_base<E: ExtensionField>(poly: &FieldType<E>, index: usiz
     }
 }
 
-pub fn field_type_index_ext<E: ExtensionField>(poly: &FieldType<E>, index: usize) -> E {
-    match &poly {
-        FieldType::Ext(coeffs) => coeffs[index],
-        FieldType::Base(coeffs) => E::from(coeffs[index]),
-        _ => unreachable!(),
-    }
-}
-
 pub fn field_type_index_mul_base<E: ExtensionField>(
     poly: &mut FieldType<E>,
     index: usize,
@@ -151,17 +143,7 @@ pub fn field_type_index_set_ext<E: ExtensionField>(
 pub fn poly_iter_ext<E: ExtensionField>(
     poly: &DenseMultilinearExtension<E>,
 ) -> impl Iterator<Item = E> + '_ {
-    field_type_iter_ext(&poly.evaluations)
-}
-
-pub fn field_type_iter_ext<E: ExtensionField>(
-    evaluations: &FieldType<E>,
-) -> impl Iterator<Item = E> + '_ {
-    match evaluations {
-        FieldType::Ext(coeffs) => Either::Left(coeffs.iter().copied()),
-        FieldType::Base(coeffs) => Either::Right(coeffs.iter().map(|x| (*x).into())),
-        _ => unreachable!(),
-    }
+    poly.evaluations.iter_ext()
 }
 
 pub fn field_type_to_ext_vec<E: ExtensionField>(evaluations: &FieldType<E>) -> Vec<E> {
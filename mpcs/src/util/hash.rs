@@ -1,3 +1,5 @@
+use std::fmt::Debug;
+
 use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 use poseidon::poseidon_hash::PoseidonHash;
@@ -47,3 +49,47 @@ pub fn hash_two_leaves_batch_base<E: ExtensionField>(
 pub fn hash_two_digests<F: SmallField + Poseidon>(a: &Digest<F>, b: &Digest<F>) -> Digest<F> {
     PoseidonHash::two_to_one(a, b)
 }
+
+/// Hash used to build and authenticate Merkle trees:
+/// leaf hashing for base/ext/batch leaves plus two-to-one digest compression.
+pub trait Hasher<E: ExtensionField>: Clone + Debug + Default + Send + Sync {
+    fn hash_two_leaves_base(a: &E::BaseField, b: &E::BaseField) -> Digest<E::BaseField>;
+
+    fn hash_two_leaves_ext(a: &E, b: &E) -> Digest<E::BaseField>;
+
+    fn hash_two_leaves_batch_base(a: &[E::BaseField], b: &[E::BaseField]) -> Digest<E::BaseField>;
+
+    fn hash_two_leaves_batch_ext(a: &[E], b: &[E]) -> Digest<E::BaseField>;
+
+    fn hash_two_digests(a: &Digest<E::BaseField>, b: &Digest<E::BaseField>)
+    -> Digest<E::BaseField>;
+}
+
+/// The default Merkle hash, backed by the functions above
+#[derive(Clone, Debug, Default)]
+pub struct PoseidonHasher;
+
+impl<E: ExtensionField> Hasher<E> for PoseidonHasher {
+    fn hash_two_leaves_base(a: &E::BaseField, b: &E::BaseField) -> Digest<E::BaseField> {
+        hash_two_leaves_base::<E>(a, b)
+    }
+
+    fn hash_two_leaves_ext(a: &E, b: &E) -> Digest<E::BaseField> {
+        hash_two_leaves_ext(a, b)
+    }
+
+    fn hash_two_leaves_batch_base(a: &[E::BaseField], b: &[E::BaseField]) -> Digest<E::BaseField> {
+        hash_two_leaves_batch_base::<E>(a, b)
+    }
+
+    fn hash_two_leaves_batch_ext(a: &[E], b: &[E]) -> Digest<E::BaseField> {
+        hash_two_leaves_batch_ext(a, b)
+    }
+
+    fn hash_two_digests(
+        a: &Digest<E::BaseField>,
+        b: &Digest<E::BaseField>,
+    ) -> Digest<E::BaseField> {
+        hash_two_digests(a, b)
+    }
+}
@@ -2,19 +2,19 @@ use ff_ext::ExtensionField;
 use itertools::Itertools;
 use multilinear_extensions::mle::FieldType;
 use rayon::{
-    iter::{
-        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
-    },
+    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
     slice::ParallelSlice,
 };
 
-use crate::util::{
-    Deserialize, DeserializeOwned, Serialize, field_type_index_base, field_type_index_ext,
-    hash::{
-        Digest, hash_two_digests, hash_two_leaves_base, hash_two_leaves_batch_base,
-        hash_two_leaves_batch_ext, hash_two_leaves_ext,
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use crate::{
+    Error,
+    util::{
+        Deserialize, DeserializeOwned, Serialize, field_type_index_base,
+        hash::{Digest, Hasher, PoseidonHasher},
+        log2_strict,
     },
-    log2_strict,
 };
 use transcript::Transcript;
 
@@ -22,30 +22,65 @@ use ark_std::{end_timer, start_timer};
 
 use super::hash::write_digest_to_transcript;
 
+/// field the leaves of a [`MerkleTree`] live in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeafFieldType {
+    Base,
+    Ext,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
-pub struct MerkleTree<E: ExtensionField>
+pub struct MerkleTree<E: ExtensionField, H: Hasher<E> = PoseidonHasher>
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
     inner: Vec<Vec<Digest<E::BaseField>>>,
     leaves: Vec<FieldType<E>>,
+    #[serde(skip)]
+    _hasher: PhantomData<H>,
 }
 
-impl<E: ExtensionField> MerkleTree<E>
+impl<E: ExtensionField, H: Hasher<E>> MerkleTree<E, H>
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
     pub fn compute_inner(leaves: &FieldType<E>) -> Vec<Vec<Digest<E::BaseField>>> {
-        merkelize::<E>(&[leaves])
+        merkelize::<E, H>(&[leaves])
     }
 
     pub fn compute_inner_base(leaves: &[E::BaseField]) -> Vec<Vec<Digest<E::BaseField>>> {
-        merkelize_base::<E>(&[leaves])
+        merkelize_base::<E, H>(&[leaves])
     }
 
     pub fn compute_inner_ext(leaves: &[E]) -> Vec<Vec<Digest<E::BaseField>>> {
-        merkelize_ext::<E>(&[leaves])
+        merkelize_ext::<E, H>(&[leaves])
+    }
+
+    /// same as [`Self::compute_inner_base`] over `len` leaves produced by `leaves`, hashing
+    /// leaf pairs as they arrive so the leaves are never all resident at once
+    pub fn compute_inner_from_leaf_iter(
+        leaves: impl IntoIterator<Item = E::BaseField>,
+        len: usize,
+    ) -> Vec<Vec<Digest<E::BaseField>>> {
+        let log_v = log2_strict(len);
+        let mut tree = Vec::with_capacity(log_v);
+        let mut leaves = leaves.into_iter();
+        let hashes = (0..len >> 1)
+            .map(|_| {
+                let mut next = || leaves.next().expect("fewer leaves than len");
+                let (left, right) = (next(), next());
+                H::hash_two_leaves_base(&left, &right)
+            })
+            .collect_vec();
+        assert!(leaves.next().is_none(), "more leaves than len");
+        tree.push(hashes);
+
+        for i in 1..log_v {
+            let oracle = hash_internal_layer::<E, H>(&tree[i - 1], MIN_PAR_MERKLE);
+            tree.push(oracle);
+        }
+        tree
     }
 
     pub fn root_from_inner(inner: &[Vec<Digest<E::BaseField>>]) -> Digest<E::BaseField> {
@@ -56,6 +91,7 @@ where
         Self {
             inner,
             leaves: vec![leaves],
+            _hasher: PhantomData,
         }
     }
 
@@ -63,13 +99,15 @@ where
         Self {
             inner: Self::compute_inner(&leaves),
             leaves: vec![leaves],
+            _hasher: PhantomData,
         }
     }
 
     pub fn from_batch_leaves(leaves: Vec<FieldType<E>>) -> Self {
         Self {
-            inner: merkelize::<E>(&leaves.iter().collect_vec()),
+            inner: merkelize::<E, H>(&leaves.iter().collect_vec()),
             leaves,
+            _hasher: PhantomData,
         }
     }
 
@@ -96,7 +134,7 @@ where
                 self.leaves
                     .iter()
                     .zip(coeffs.iter())
-                    .map(|(leaf, coeff)| field_type_index_ext(leaf, i) * *coeff)
+                    .map(|(leaf, coeff)| leaf.index_as_ext(i) * *coeff)
                     .sum()
             })
             .collect()
@@ -106,42 +144,40 @@ where
         (self.leaves.len(), self.leaves[0].len())
     }
 
-    pub fn get_leaf_as_base(&self, index: usize) -> Vec<E::BaseField> {
+    pub fn leaf_field_type(&self) -> LeafFieldType {
         match &self.leaves[0] {
-            FieldType::Base(_) => self
-                .leaves
-                .iter()
-                .map(|leaves| field_type_index_base(leaves, index))
-                .collect(),
-            FieldType::Ext(_) => panic!(
-                "Mismatching field type, calling get_leaf_as_base on a Merkle tree over extension fields"
-            ),
+            FieldType::Base(_) => LeafFieldType::Base,
+            FieldType::Ext(_) => LeafFieldType::Ext,
             FieldType::Unreachable => unreachable!(),
         }
     }
 
-    pub fn get_leaf_as_extension(&self, index: usize) -> Vec<E> {
-        match &self.leaves[0] {
-            FieldType::Base(_) => self
+    pub fn get_leaf_as_base(&self, index: usize) -> Result<Vec<E::BaseField>, Error> {
+        match self.leaf_field_type() {
+            LeafFieldType::Base => Ok(self
                 .leaves
                 .iter()
-                .map(|leaves| field_type_index_ext(leaves, index))
-                .collect(),
-            FieldType::Ext(_) => self
-                .leaves
-                .iter()
-                .map(|leaves| field_type_index_ext(leaves, index))
-                .collect(),
-            FieldType::Unreachable => unreachable!(),
+                .map(|leaves| field_type_index_base(leaves, index))
+                .collect()),
+            LeafFieldType::Ext => Err(Error::MismatchedFieldType(
+                "calling get_leaf_as_base on a Merkle tree over extension fields".to_string(),
+            )),
         }
     }
 
+    pub fn get_leaf_as_extension(&self, index: usize) -> Vec<E> {
+        self.leaves
+            .iter()
+            .map(|leaves| leaves.index_as_ext(index))
+            .collect()
+    }
+
     pub fn merkle_path_without_leaf_sibling_or_root(
         &self,
         leaf_index: usize,
-    ) -> MerklePathWithoutLeafOrRoot<E> {
+    ) -> MerklePathWithoutLeafOrRoot<E, H> {
         assert!(leaf_index < self.size().1);
-        MerklePathWithoutLeafOrRoot::<E>::new(
+        MerklePathWithoutLeafOrRoot::<E, H>::new(
             self.inner
                 .iter()
                 .take(self.height() - 1)
@@ -152,22 +188,179 @@ where
                 .collect(),
         )
     }
+
+    /// [`Self::merkle_path_without_leaf_sibling_or_root`] of every index in `leaf_indices`,
+    /// computed in parallel
+    pub fn merkle_paths_par(
+        &self,
+        leaf_indices: &[usize],
+    ) -> Vec<MerklePathWithoutLeafOrRoot<E, H>> {
+        leaf_indices
+            .par_iter()
+            .map(|index| self.merkle_path_without_leaf_sibling_or_root(*index))
+            .collect()
+    }
+
+    /// Replace the leaf pair at `leaf_group_index`, i.e. leaves `2 * leaf_group_index` and
+    /// `2 * leaf_group_index + 1`, and recompute only its ancestors.
+    /// `new_left` and `new_right` hold one value per batched leaf vector.
+    pub fn update_leaf(
+        &mut self,
+        leaf_group_index: usize,
+        new_left: FieldType<E>,
+        new_right: FieldType<E>,
+    ) {
+        assert!(leaf_group_index < self.inner[0].len());
+        assert_eq!(new_left.len(), self.leaves.len());
+        assert_eq!(new_right.len(), self.leaves.len());
+        for (i, leaves) in self.leaves.iter_mut().enumerate() {
+            match (leaves, &new_left, &new_right) {
+                (FieldType::Base(leaves), FieldType::Base(left), FieldType::Base(right)) => {
+                    leaves[leaf_group_index << 1] = left[i];
+                    leaves[(leaf_group_index << 1) + 1] = right[i];
+                }
+                (FieldType::Ext(leaves), FieldType::Ext(left), FieldType::Ext(right)) => {
+                    leaves[leaf_group_index << 1] = left[i];
+                    leaves[(leaf_group_index << 1) + 1] = right[i];
+                }
+                _ => panic!("Mismatching field type when updating Merkle tree leaves"),
+            }
+        }
+
+        let mut position = leaf_group_index;
+        self.inner[0][position] = hash_leaf_pair::<E, H>(new_left, new_right);
+        for layer in 1..self.height() {
+            position >>= 1;
+            let (lower, upper) = self.inner.split_at_mut(layer);
+            let children = &lower[layer - 1];
+            upper[0][position] =
+                H::hash_two_digests(&children[position << 1], &children[(position << 1) + 1]);
+        }
+    }
+
+    /// Recompute the whole tree from the leaves and check it matches the stored digests,
+    /// e.g. to catch corruption after deserialization.
+    pub fn verify_all(&self) -> bool {
+        merkelize::<E, H>(&self.leaves.iter().collect_vec()) == self.inner
+    }
+
+    /// Merkle paths of many leaves at once. An internal node is included at most once, and
+    /// not at all if the verifier can recompute it from the opened leaves.
+    pub fn batch_merkle_paths(&self, leaf_indices: &[usize]) -> BatchMerkleProof<E, H> {
+        assert!(leaf_indices.iter().all(|index| *index < self.size().1));
+        // positions of the nodes known to the verifier, starting from the hashes of leaf pairs
+        let mut known = leaf_indices.iter().map(|index| index >> 1).collect_vec();
+        known.sort_unstable();
+        known.dedup();
+        let inner = self
+            .inner
+            .iter()
+            .take(self.height() - 1)
+            .map(|layer| {
+                let siblings = known
+                    .iter()
+                    .map(|position| position ^ 1)
+                    .filter(|sibling| known.binary_search(sibling).is_err())
+                    .map(|sibling| layer[sibling].clone())
+                    .collect_vec();
+                known = known.iter().map(|position| position >> 1).collect_vec();
+                known.dedup();
+                siblings
+            })
+            .collect();
+        BatchMerkleProof {
+            inner,
+            _hasher: PhantomData,
+        }
+    }
 }
 
+/// Merkle paths of multiple leaves with shared internal nodes deduplicated.
+/// `inner[i]` holds the siblings required at layer `i`, sorted by position.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
-pub struct MerklePathWithoutLeafOrRoot<E: ExtensionField>
+#[serde(bound(serialize = "", deserialize = ""))]
+pub struct BatchMerkleProof<E: ExtensionField, H: Hasher<E> = PoseidonHasher>
+where
+    E::BaseField: Serialize + DeserializeOwned,
+{
+    inner: Vec<Vec<Digest<E::BaseField>>>,
+    #[serde(skip)]
+    _hasher: PhantomData<H>,
+}
+
+impl<E: ExtensionField, H: Hasher<E>> BatchMerkleProof<E, H>
+where
+    E::BaseField: Serialize + DeserializeOwned,
+{
+    /// number of digests in the proof
+    pub fn len(&self) -> usize {
+        self.inner.iter().map(|layer| layer.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `leaves[k]` is the (left, right) leaf pair containing `leaf_indices[k]`,
+    /// in the same format as `authenticate_batch_leaves_root_*`
+    pub fn authenticate_batch(
+        &self,
+        leaf_indices: &[usize],
+        leaves: Vec<(FieldType<E>, FieldType<E>)>,
+        root: &Digest<E::BaseField>,
+    ) {
+        assert_eq!(leaf_indices.len(), leaves.len());
+        let mut known = BTreeMap::new();
+        for (index, (left, right)) in leaf_indices.iter().zip_eq(leaves) {
+            let hash = hash_leaf_pair::<E, H>(left, right);
+            if let Some(prev) = known.insert(index >> 1, hash.clone()) {
+                assert_eq!(prev, hash);
+            }
+        }
+
+        for siblings in self.inner.iter() {
+            let mut siblings = siblings.iter();
+            let mut parents = BTreeMap::new();
+            let mut nodes = known.iter().peekable();
+            while let Some((position, hash)) = nodes.next() {
+                let parent = if position & 1 == 0 {
+                    match nodes.next_if(|(next, _)| **next == position ^ 1) {
+                        Some((_, right)) => H::hash_two_digests(hash, right),
+                        None => H::hash_two_digests(hash, siblings.next().unwrap()),
+                    }
+                } else {
+                    H::hash_two_digests(siblings.next().unwrap(), hash)
+                };
+                parents.insert(position >> 1, parent);
+            }
+            assert!(siblings.next().is_none());
+            known = parents;
+        }
+        assert_eq!(known.len(), 1);
+        assert_eq!(known.get(&0), Some(root));
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = ""))]
+pub struct MerklePathWithoutLeafOrRoot<E: ExtensionField, H: Hasher<E> = PoseidonHasher>
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
     inner: Vec<Digest<E::BaseField>>,
+    #[serde(skip)]
+    _hasher: PhantomData<H>,
 }
 
-impl<E: ExtensionField> MerklePathWithoutLeafOrRoot<E>
+impl<E: ExtensionField, H: Hasher<E>> MerklePathWithoutLeafOrRoot<E, H>
 where
     E::BaseField: Serialize + DeserializeOwned,
 {
     pub fn new(inner: Vec<Digest<E::BaseField>>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            _hasher: PhantomData,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -195,7 +388,7 @@ where
         index: usize,
         root: &Digest<E::BaseField>,
     ) {
-        authenticate_merkle_path_root::<E>(
+        authenticate_merkle_path_root::<E, H>(
             &self.inner,
             FieldType::Ext(vec![left, right]),
             index,
@@ -210,7 +403,7 @@ where
         index: usize,
         root: &Digest<E::BaseField>,
     ) {
-        authenticate_merkle_path_root::<E>(
+        authenticate_merkle_path_root::<E, H>(
             &self.inner,
             FieldType::Base(vec![left, right]),
             index,
@@ -225,7 +418,7 @@ where
         index: usize,
         root: &Digest<E::BaseField>,
     ) {
-        authenticate_merkle_path_root_batch::<E>(
+        authenticate_merkle_path_root_batch::<E, H>(
             &self.inner,
             FieldType::Ext(left),
             FieldType::Ext(right),
@@ -241,7 +434,7 @@ where
         index: usize,
         root: &Digest<E::BaseField>,
     ) {
-        authenticate_merkle_path_root_batch::<E>(
+        authenticate_merkle_path_root_batch::<E, H>(
             &self.inner,
             FieldType::Base(left),
             FieldType::Base(right),
@@ -251,167 +444,141 @@ where
     }
 }
 
-/// Merkle tree construction
-/// TODO: Support merkelizing mixed-type values
-fn merkelize<E: ExtensionField>(values: &[&FieldType<E>]) -> Vec<Vec<Digest<E::BaseField>>> {
-    #[cfg(feature = "sanity-check")]
-    for i in 0..(values.len() - 1) {
-        assert_eq!(values[i].len(), values[i + 1].len());
-    }
-    let timer = start_timer!(|| format!("merkelize {} values", values[0].len() * values.len()));
-    let log_v = log2_strict(values[0].len());
-    let mut tree = Vec::with_capacity(log_v);
-    // The first layer of hashes, half the number of leaves
-    let mut hashes = vec![Digest::default(); values[0].len() >> 1];
-    if values.len() == 1 {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = match &values[0] {
-                FieldType::Base(values) => {
-                    hash_two_leaves_base::<E>(&values[i << 1], &values[(i << 1) + 1])
-                }
-                FieldType::Ext(values) => {
-                    hash_two_leaves_ext::<E>(&values[i << 1], &values[(i << 1) + 1])
-                }
-                FieldType::Unreachable => unreachable!(),
-            };
-        });
+/// Trees with fewer hashes per layer than this are hashed sequentially, where the rayon
+/// overhead would outweigh the work.
+const MIN_PAR_MERKLE: usize = 64;
+
+/// The first layer of hashes, one per leaf pair
+fn hash_leaf_layer<E: ExtensionField>(
+    num_pairs: usize,
+    min_par_size: usize,
+    hash_pair: impl Fn(usize) -> Digest<E::BaseField> + Sync + Send,
+) -> Vec<Digest<E::BaseField>> {
+    if num_pairs < min_par_size {
+        (0..num_pairs).map(hash_pair).collect()
     } else {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = match &values[0] {
-                FieldType::Base(_) => hash_two_leaves_batch_base::<E>(
-                    values
-                        .iter()
-                        .map(|values| field_type_index_base(values, i << 1))
-                        .collect_vec()
-                        .as_slice(),
-                    values
-                        .iter()
-                        .map(|values| field_type_index_base(values, (i << 1) + 1))
-                        .collect_vec()
-                        .as_slice(),
-                ),
-                FieldType::Ext(_) => hash_two_leaves_batch_ext::<E>(
-                    values
-                        .iter()
-                        .map(|values| field_type_index_ext(values, i << 1))
-                        .collect_vec()
-                        .as_slice(),
-                    values
-                        .iter()
-                        .map(|values| field_type_index_ext(values, (i << 1) + 1))
-                        .collect_vec()
-                        .as_slice(),
-                ),
-                FieldType::Unreachable => unreachable!(),
-            };
-        });
+        (0..num_pairs).into_par_iter().map(hash_pair).collect()
     }
+}
 
-    tree.push(hashes);
-
-    for i in 1..(log_v) {
-        let oracle = tree[i - 1]
+fn hash_internal_layer<E: ExtensionField, H: Hasher<E>>(
+    layer: &[Digest<E::BaseField>],
+    min_par_size: usize,
+) -> Vec<Digest<E::BaseField>> {
+    if layer.len() >> 1 < min_par_size {
+        layer
+            .chunks_exact(2)
+            .map(|ys| H::hash_two_digests(&ys[0], &ys[1]))
+            .collect()
+    } else {
+        layer
             .par_chunks_exact(2)
-            .map(|ys| hash_two_digests(&ys[0], &ys[1]))
-            .collect::<Vec<_>>();
-
-        tree.push(oracle);
+            .map(|ys| H::hash_two_digests(&ys[0], &ys[1]))
+            .collect()
     }
-    end_timer!(timer);
-    tree
 }
 
-fn merkelize_base<E: ExtensionField>(values: &[&[E::BaseField]]) -> Vec<Vec<Digest<E::BaseField>>> {
+/// Merkle tree construction
+/// TODO: Support merkelizing mixed-type values
+fn merkelize<E: ExtensionField, H: Hasher<E>>(
+    values: &[&FieldType<E>],
+) -> Vec<Vec<Digest<E::BaseField>>> {
     #[cfg(feature = "sanity-check")]
     for i in 0..(values.len() - 1) {
         assert_eq!(values[i].len(), values[i + 1].len());
     }
-    let timer = start_timer!(|| format!("merkelize {} values", values[0].len() * values.len()));
-    let log_v = log2_strict(values[0].len());
-    let mut tree = Vec::with_capacity(log_v);
-    // The first layer of hashes, half the number of leaves
-    let mut hashes = vec![Digest::default(); values[0].len() >> 1];
-    if values.len() == 1 {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = hash_two_leaves_base::<E>(&values[0][i << 1], &values[0][(i << 1) + 1]);
-        });
-    } else {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = hash_two_leaves_batch_base::<E>(
-                values
-                    .iter()
-                    .map(|values| values[i << 1])
-                    .collect_vec()
-                    .as_slice(),
-                values
-                    .iter()
-                    .map(|values| values[(i << 1) + 1])
-                    .collect_vec()
-                    .as_slice(),
-            );
-        });
+    match values[0] {
+        FieldType::Base(_) => merkelize_base::<E, H>(
+            &values
+                .iter()
+                .map(|values| match values {
+                    FieldType::Base(values) => values.as_slice(),
+                    _ => panic!("Cannot get base field from extension field"),
+                })
+                .collect_vec(),
+        ),
+        // base leaves batched after extension ones are lifted to the extension field
+        FieldType::Ext(_) => merkelize_leaves::<E, H, _, _>(
+            values,
+            values[0].len(),
+            |values, i| values.index_as_ext(i),
+            H::hash_two_leaves_ext,
+            H::hash_two_leaves_batch_ext,
+        ),
+        FieldType::Unreachable => unreachable!(),
     }
+}
 
-    tree.push(hashes);
-
-    for i in 1..(log_v) {
-        let oracle = tree[i - 1]
-            .par_chunks_exact(2)
-            .map(|ys| hash_two_digests(&ys[0], &ys[1]))
-            .collect::<Vec<_>>();
+fn merkelize_base<E: ExtensionField, H: Hasher<E>>(
+    values: &[&[E::BaseField]],
+) -> Vec<Vec<Digest<E::BaseField>>> {
+    merkelize_leaves::<E, H, _, _>(
+        values,
+        values[0].len(),
+        |values, i| values[i],
+        H::hash_two_leaves_base,
+        H::hash_two_leaves_batch_base,
+    )
+}
 
-        tree.push(oracle);
-    }
-    end_timer!(timer);
-    tree
+fn merkelize_ext<E: ExtensionField, H: Hasher<E>>(
+    values: &[&[E]],
+) -> Vec<Vec<Digest<E::BaseField>>> {
+    merkelize_leaves::<E, H, _, _>(
+        values,
+        values[0].len(),
+        |values, i| values[i],
+        H::hash_two_leaves_ext,
+        H::hash_two_leaves_batch_ext,
+    )
 }
 
-fn merkelize_ext<E: ExtensionField>(values: &[&[E]]) -> Vec<Vec<Digest<E::BaseField>>> {
-    #[cfg(feature = "sanity-check")]
-    for i in 0..(values.len() - 1) {
-        assert_eq!(values[i].len(), values[i + 1].len());
-    }
-    let timer = start_timer!(|| format!("merkelize {} values", values[0].len() * values.len()));
-    let log_v = log2_strict(values[0].len());
+/// the leaf hashing shared by base and extension leaves: `leaf(column, i)` reads leaf `i` of
+/// each of the `num_leaves` long columns. a single column hashes its leaf pairs with
+/// `hash_two_leaves`, a batch of columns with `hash_two_leaves_batch`
+fn merkelize_leaves<E: ExtensionField, H: Hasher<E>, V: Sync, T: Send + Sync>(
+    values: &[V],
+    num_leaves: usize,
+    leaf: impl Fn(&V, usize) -> T + Sync + Send,
+    hash_two_leaves: impl Fn(&T, &T) -> Digest<E::BaseField> + Sync + Send,
+    hash_two_leaves_batch: impl Fn(&[T], &[T]) -> Digest<E::BaseField> + Sync + Send,
+) -> Vec<Vec<Digest<E::BaseField>>> {
+    let timer = start_timer!(|| format!("merkelize {} values", num_leaves * values.len()));
+    let log_v = log2_strict(num_leaves);
     let mut tree = Vec::with_capacity(log_v);
-    // The first layer of hashes, half the number of leaves
-    let mut hashes = vec![Digest::default(); values[0].len() >> 1];
-    if values.len() == 1 {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = hash_two_leaves_ext::<E>(&values[0][i << 1], &values[0][(i << 1) + 1]);
-        });
+    let num_pairs = num_leaves >> 1;
+    let hashes = if values.len() == 1 {
+        hash_leaf_layer::<E>(num_pairs, MIN_PAR_MERKLE, |i| {
+            hash_two_leaves(&leaf(&values[0], i << 1), &leaf(&values[0], (i << 1) + 1))
+        })
     } else {
-        hashes.par_iter_mut().enumerate().for_each(|(i, hash)| {
-            *hash = hash_two_leaves_batch_ext::<E>(
+        hash_leaf_layer::<E>(num_pairs, MIN_PAR_MERKLE, |i| {
+            hash_two_leaves_batch(
                 values
                     .iter()
-                    .map(|values| values[i << 1])
+                    .map(|values| leaf(values, i << 1))
                     .collect_vec()
                     .as_slice(),
                 values
                     .iter()
-                    .map(|values| values[(i << 1) + 1])
+                    .map(|values| leaf(values, (i << 1) + 1))
                     .collect_vec()
                     .as_slice(),
-            );
-        });
-    }
+            )
+        })
+    };
 
     tree.push(hashes);
 
     for i in 1..(log_v) {
-        let oracle = tree[i - 1]
-            .par_chunks_exact(2)
-            .map(|ys| hash_two_digests(&ys[0], &ys[1]))
-            .collect::<Vec<_>>();
-
+        let oracle = hash_internal_layer::<E, H>(&tree[i - 1], MIN_PAR_MERKLE);
         tree.push(oracle);
     }
     end_timer!(timer);
     tree
 }
 
-fn authenticate_merkle_path_root<E: ExtensionField>(
+fn authenticate_merkle_path_root<E: ExtensionField, H: Hasher<E>>(
     path: &[Digest<E::BaseField>],
     leaves: FieldType<E>,
     x_index: usize,
@@ -420,8 +587,8 @@ fn authenticate_merkle_path_root<E: ExtensionField>(
     let mut x_index = x_index;
     assert_eq!(leaves.len(), 2);
     let mut hash = match leaves {
-        FieldType::Base(leaves) => hash_two_leaves_base::<E>(&leaves[0], &leaves[1]),
-        FieldType::Ext(leaves) => hash_two_leaves_ext(&leaves[0], &leaves[1]),
+        FieldType::Base(leaves) => H::hash_two_leaves_base(&leaves[0], &leaves[1]),
+        FieldType::Ext(leaves) => H::hash_two_leaves_ext(&leaves[0], &leaves[1]),
         FieldType::Unreachable => unreachable!(),
     };
 
@@ -429,16 +596,16 @@ fn authenticate_merkle_path_root<E: ExtensionField>(
     x_index >>= 1;
     for path_i in path.iter() {
         hash = if x_index & 1 == 0 {
-            hash_two_digests(&hash, path_i)
+            H::hash_two_digests(&hash, path_i)
         } else {
-            hash_two_digests(path_i, &hash)
+            H::hash_two_digests(path_i, &hash)
         };
         x_index >>= 1;
     }
     assert_eq!(&hash, root);
 }
 
-fn authenticate_merkle_path_root_batch<E: ExtensionField>(
+fn authenticate_merkle_path_root_batch<E: ExtensionField, H: Hasher<E>>(
     path: &[Digest<E::BaseField>],
     left: FieldType<E>,
     right: FieldType<E>,
@@ -446,37 +613,348 @@ fn authenticate_merkle_path_root_batch<E: ExtensionField>(
     root: &Digest<E::BaseField>,
 ) {
     let mut x_index = x_index;
-    let mut hash = if left.len() > 1 {
+    let mut hash = hash_leaf_pair::<E, H>(left, right);
+
+    // The lowest bit in the index is ignored. It can point to either leaves
+    x_index >>= 1;
+    for path_i in path.iter() {
+        hash = if x_index & 1 == 0 {
+            H::hash_two_digests(&hash, path_i)
+        } else {
+            H::hash_two_digests(path_i, &hash)
+        };
+        x_index >>= 1;
+    }
+    assert_eq!(&hash, root);
+}
+
+/// hash of a (left, right) leaf pair, batched over all the values if there are more than one
+fn hash_leaf_pair<E: ExtensionField, H: Hasher<E>>(
+    left: FieldType<E>,
+    right: FieldType<E>,
+) -> Digest<E::BaseField> {
+    if left.len() > 1 {
         match (left, right) {
             (FieldType::Base(left), FieldType::Base(right)) => {
-                hash_two_leaves_batch_base::<E>(&left, &right)
+                H::hash_two_leaves_batch_base(&left, &right)
             }
             (FieldType::Ext(left), FieldType::Ext(right)) => {
-                hash_two_leaves_batch_ext::<E>(&left, &right)
+                H::hash_two_leaves_batch_ext(&left, &right)
             }
             _ => unreachable!(),
         }
     } else {
         match (left, right) {
             (FieldType::Base(left), FieldType::Base(right)) => {
-                hash_two_leaves_base::<E>(&left[0], &right[0])
+                H::hash_two_leaves_base(&left[0], &right[0])
             }
             (FieldType::Ext(left), FieldType::Ext(right)) => {
-                hash_two_leaves_ext::<E>(&left[0], &right[0])
+                H::hash_two_leaves_ext(&left[0], &right[0])
             }
             _ => unreachable!(),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use ff_ext::ExtensionField;
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+    use multilinear_extensions::mle::FieldType;
+    use poseidon::poseidon_hash::PoseidonHash;
+    use rand::rngs::OsRng;
+
+    use super::{LeafFieldType, MIN_PAR_MERKLE, MerkleTree, hash_internal_layer, hash_leaf_layer};
+    use crate::{
+        Error,
+        util::hash::{Digest, Hasher, PoseidonHasher},
     };
 
-    // The lowest bit in the index is ignored. It can point to either leaves
-    x_index >>= 1;
-    for path_i in path.iter() {
-        hash = if x_index & 1 == 0 {
-            hash_two_digests(&hash, path_i)
-        } else {
-            hash_two_digests(path_i, &hash)
-        };
-        x_index >>= 1;
+    type E = GoldilocksExt2;
+    type F = Goldilocks;
+
+    /// poseidon with a domain separation tag prepended to every input
+    #[derive(Clone, Debug, Default)]
+    struct TaggedPoseidonHasher;
+
+    impl TaggedPoseidonHasher {
+        fn hash<'a>(inputs: impl Iterator<Item = &'a F>) -> Digest<F> {
+            PoseidonHash::hash_or_noop_iter([F::ONE].iter().chain(inputs))
+        }
+    }
+
+    impl Hasher<E> for TaggedPoseidonHasher {
+        fn hash_two_leaves_base(a: &F, b: &F) -> Digest<F> {
+            Self::hash([*a, *b].iter())
+        }
+
+        fn hash_two_leaves_ext(a: &E, b: &E) -> Digest<F> {
+            Self::hash(a.as_bases().iter().chain(b.as_bases()))
+        }
+
+        fn hash_two_leaves_batch_base(a: &[F], b: &[F]) -> Digest<F> {
+            Self::hash(a.iter().chain(b))
+        }
+
+        fn hash_two_leaves_batch_ext(a: &[E], b: &[E]) -> Digest<F> {
+            Self::hash(a.iter().chain(b).flat_map(|v| v.as_bases()))
+        }
+
+        fn hash_two_digests(a: &Digest<F>, b: &Digest<F>) -> Digest<F> {
+            Self::hash(a.0.iter().chain(&b.0))
+        }
+    }
+
+    fn assert_authenticates<H: Hasher<E>>(tree: &MerkleTree<E, H>, leaves: &[F]) {
+        let root = tree.root();
+        for index in (0..leaves.len()).step_by(2) {
+            tree.merkle_path_without_leaf_sibling_or_root(index)
+                .authenticate_leaves_root_base(leaves[index], leaves[index + 1], index, &root);
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_with_different_hashers() {
+        let leaves = (0..16).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+
+        let poseidon_tree =
+            MerkleTree::<E, PoseidonHasher>::from_leaves(FieldType::Base(leaves.clone()));
+        let tagged_tree =
+            MerkleTree::<E, TaggedPoseidonHasher>::from_leaves(FieldType::Base(leaves.clone()));
+        assert_ne!(poseidon_tree.root(), tagged_tree.root());
+
+        assert_authenticates(&poseidon_tree, &leaves);
+        assert_authenticates(&tagged_tree, &leaves);
+    }
+
+    #[test]
+    fn test_merkelize_base_and_ext_leaves() {
+        /// root of a tree hashed naively pair by pair
+        fn naive_root(leaf_hashes: Vec<Digest<F>>) -> Digest<F> {
+            let mut layer = leaf_hashes;
+            while layer.len() > 1 {
+                layer = layer
+                    .chunks_exact(2)
+                    .map(|ys| <PoseidonHasher as Hasher<E>>::hash_two_digests(&ys[0], &ys[1]))
+                    .collect();
+            }
+            layer.pop().unwrap()
+        }
+        let num_leaves = 4 * MIN_PAR_MERKLE;
+        let base = (0..2)
+            .map(|_| {
+                (0..num_leaves)
+                    .map(|_| F::random(OsRng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let ext = base
+            .iter()
+            .map(|col| col.iter().map(|v| E::from(*v)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let column = |cols: &[Vec<F>], i: usize| cols.iter().map(|col| col[i]).collect::<Vec<_>>();
+        let ext_column =
+            |cols: &[Vec<E>], i: usize| cols.iter().map(|col| col[i]).collect::<Vec<_>>();
+
+        // single column
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Base(base[0].clone()));
+        let expected = naive_root(
+            (0..num_leaves / 2)
+                .map(|i| {
+                    <PoseidonHasher as Hasher<E>>::hash_two_leaves_base(
+                        &base[0][2 * i],
+                        &base[0][2 * i + 1],
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(tree.root(), expected);
+        assert_eq!(
+            MerkleTree::<E>::root_from_inner(&MerkleTree::<E>::compute_inner_base(&base[0])),
+            expected
+        );
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Ext(ext[0].clone()));
+        let expected = naive_root(
+            (0..num_leaves / 2)
+                .map(|i| {
+                    <PoseidonHasher as Hasher<E>>::hash_two_leaves_ext(
+                        &ext[0][2 * i],
+                        &ext[0][2 * i + 1],
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(tree.root(), expected);
+        assert_eq!(
+            MerkleTree::<E>::root_from_inner(&MerkleTree::<E>::compute_inner_ext(&ext[0])),
+            expected
+        );
+
+        // batched columns
+        let tree =
+            MerkleTree::<E>::from_batch_leaves(base.iter().cloned().map(FieldType::Base).collect());
+        let expected = naive_root(
+            (0..num_leaves / 2)
+                .map(|i| {
+                    <PoseidonHasher as Hasher<E>>::hash_two_leaves_batch_base(
+                        &column(&base, 2 * i),
+                        &column(&base, 2 * i + 1),
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(tree.root(), expected);
+        let tree =
+            MerkleTree::<E>::from_batch_leaves(ext.iter().cloned().map(FieldType::Ext).collect());
+        let expected = naive_root(
+            (0..num_leaves / 2)
+                .map(|i| {
+                    <PoseidonHasher as Hasher<E>>::hash_two_leaves_batch_ext(
+                        &ext_column(&ext, 2 * i),
+                        &ext_column(&ext, 2 * i + 1),
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(tree.root(), expected);
+
+        // base columns batched after an extension one are lifted
+        let mixed = MerkleTree::<E>::from_batch_leaves(vec![
+            FieldType::Ext(ext[0].clone()),
+            FieldType::Base(base[1].clone()),
+        ]);
+        assert_eq!(mixed.root(), expected);
+    }
+
+    #[test]
+    fn test_compute_inner_from_leaf_iter() {
+        let leaves = (0..64).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+        let expected = MerkleTree::<E>::compute_inner_base(&leaves);
+        let streamed =
+            MerkleTree::<E>::compute_inner_from_leaf_iter(leaves.iter().copied(), leaves.len());
+        assert_eq!(
+            MerkleTree::<E>::root_from_inner(&streamed),
+            MerkleTree::<E>::root_from_inner(&expected)
+        );
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_batch_merkle_paths() {
+        let leaves = (0..32).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Base(leaves.clone()));
+        let root = tree.root();
+
+        // 0 and 1 share the leaf pair, 4..8 share ancestors with each other and with 0
+        let indices = [0, 1, 4, 6, 7, 13, 29];
+        let batch_proof = tree.batch_merkle_paths(&indices);
+        let pairs = indices
+            .iter()
+            .map(|index| {
+                let left = index & !1;
+                (
+                    FieldType::Base(vec![leaves[left]]),
+                    FieldType::Base(vec![leaves[left + 1]]),
+                )
+            })
+            .collect();
+        batch_proof.authenticate_batch(&indices, pairs, &root);
+
+        let individual_len: usize = indices
+            .iter()
+            .map(|index| tree.merkle_path_without_leaf_sibling_or_root(*index).len())
+            .sum();
+        assert!(batch_proof.len() < individual_len);
+    }
+
+    #[test]
+    fn test_merkle_paths_par() {
+        let leaves = (0..64).map(|_| E::random(OsRng)).collect::<Vec<_>>();
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Ext(leaves));
+
+        let indices = [0, 1, 5, 5, 17, 42, 63];
+        let paths = tree.merkle_paths_par(&indices);
+        assert_eq!(paths.len(), indices.len());
+        for (index, path) in indices.iter().zip(paths) {
+            let expected = tree.merkle_path_without_leaf_sibling_or_root(*index);
+            assert!(path.iter().eq(expected.iter()));
+        }
+    }
+
+    #[test]
+    fn test_update_leaf() {
+        let num_batch = 3;
+        let mut leaves = (0..num_batch)
+            .map(|_| (0..16).map(|_| E::random(OsRng)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut tree = MerkleTree::<E>::from_batch_leaves(
+            leaves.iter().cloned().map(FieldType::Ext).collect(),
+        );
+
+        let leaf_group_index = 5;
+        let new_left = (0..num_batch).map(|_| E::random(OsRng)).collect::<Vec<_>>();
+        let new_right = (0..num_batch).map(|_| E::random(OsRng)).collect::<Vec<_>>();
+        tree.update_leaf(
+            leaf_group_index,
+            FieldType::Ext(new_left.clone()),
+            FieldType::Ext(new_right.clone()),
+        );
+
+        for (leaves, (left, right)) in leaves.iter_mut().zip(new_left.into_iter().zip(new_right)) {
+            leaves[leaf_group_index << 1] = left;
+            leaves[(leaf_group_index << 1) + 1] = right;
+        }
+        let rebuilt =
+            MerkleTree::<E>::from_batch_leaves(leaves.into_iter().map(FieldType::Ext).collect());
+        assert_eq!(tree.root(), rebuilt.root());
+        assert_eq!(tree.inner, rebuilt.inner);
+    }
+
+    #[test]
+    fn test_verify_all() {
+        let leaves = (0..16).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+        let mut tree = MerkleTree::<E>::from_leaves(FieldType::Base(leaves));
+        assert!(tree.verify_all());
+
+        tree.inner[1][2] = Digest::default();
+        assert!(!tree.verify_all());
+    }
+
+    #[test]
+    fn test_small_tree_matches_parallel_path() {
+        let leaves = (0..16).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+        assert!(leaves.len() >> 1 < MIN_PAR_MERKLE);
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Base(leaves.clone()));
+
+        // rebuild the tree with every layer forced onto the parallel path
+        let mut layer = hash_leaf_layer::<E>(leaves.len() >> 1, 0, |i| {
+            <PoseidonHasher as Hasher<E>>::hash_two_leaves_base(
+                &leaves[i << 1],
+                &leaves[(i << 1) + 1],
+            )
+        });
+        let mut inner = vec![layer.clone()];
+        while layer.len() > 1 {
+            layer = hash_internal_layer::<E, PoseidonHasher>(&layer, 0);
+            inner.push(layer.clone());
+        }
+        assert_eq!(tree.inner, inner);
+    }
+
+    #[test]
+    fn test_leaf_field_type() {
+        let leaves = (0..16).map(|_| E::random(OsRng)).collect::<Vec<_>>();
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Ext(leaves));
+        assert_eq!(tree.leaf_field_type(), LeafFieldType::Ext);
+        assert!(matches!(
+            tree.get_leaf_as_base(0),
+            Err(Error::MismatchedFieldType(_))
+        ));
+
+        let leaves = (0..16).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+        let tree = MerkleTree::<E>::from_leaves(FieldType::Base(leaves.clone()));
+        assert_eq!(tree.leaf_field_type(), LeafFieldType::Base);
+        assert_eq!(tree.get_leaf_as_base(3).unwrap(), vec![leaves[3]]);
     }
-    assert_eq!(&hash, root);
 }
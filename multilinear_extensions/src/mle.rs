@@ -5,6 +5,7 @@ use ark_std::{end_timer, rand::RngCore, start_timer};
 use core::hash::Hash;
 use ff::Field;
 use ff_ext::ExtensionField;
+use itertools::Either;
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
 };
@@ -156,6 +157,24 @@ impl<E: ExtensionField> FieldType<E> {
             FieldType::Unreachable => "Unreachable",
         }
     }
+
+    /// the `index`-th value, lifted to the extension field if stored on base field
+    pub fn index_as_ext(&self, index: usize) -> E {
+        match self {
+            FieldType::Base(content) => E::from(content[index]),
+            FieldType::Ext(content) => content[index],
+            FieldType::Unreachable => unreachable!(),
+        }
+    }
+
+    /// iterate over all values, lifted to the extension field if stored on base field
+    pub fn iter_ext(&self) -> impl Iterator<Item = E> + '_ {
+        match self {
+            FieldType::Base(content) => Either::Left(content.iter().map(|v| E::from(*v))),
+            FieldType::Ext(content) => Either::Right(content.iter().copied()),
+            FieldType::Unreachable => unreachable!(),
+        }
+    }
 }
 
 /// Stores a multilinear polynomial in dense evaluation form.
@@ -9,7 +9,7 @@ use multilinear_extensions::{
     mle::{DenseMultilinearExtension, MultilinearExtension},
     op_mle, op_mle_product_3, op_mle3_range,
     util::largest_even_below,
-    virtual_poly_v2::VirtualPolynomialV2,
+    virtual_poly_v2::{ArcMultilinearExtension, VirtualPolynomialV2},
 };
 use rayon::{
     Scope,
@@ -529,7 +529,14 @@ impl<'a, E: ExtensionField> IOPProverStateV2<'a, E> {
                         )
                         .to_vec()
                     }
-                    _ => unimplemented!("do not support degree > 3"),
+                    _ => products_sum_generic(
+                        &products
+                            .iter()
+                            .map(|&i| &self.poly.flattened_ml_extensions[i])
+                            .collect_vec(),
+                        self.poly.aux_info.max_num_variables,
+                        self.round,
+                    ),
                 };
                 exit_span!(span);
                 sum.iter_mut().for_each(|sum| *sum *= coefficient);
@@ -884,7 +891,14 @@ impl<'a, E: ExtensionField> IOPProverStateV2<'a, E> {
                             )
                             .to_vec()
                         }
-                        _ => unimplemented!("do not support degree > 3"),
+                        _ => products_sum_generic(
+                            &products
+                                .iter()
+                                .map(|&i| &self.poly.flattened_ml_extensions[i])
+                                .collect_vec(),
+                            self.poly.aux_info.max_num_variables,
+                            self.round,
+                        ),
                     };
                     exit_span!(span);
                     sum.iter_mut().for_each(|sum| *sum *= coefficient);
@@ -917,3 +931,39 @@ impl<'a, E: ExtensionField> IOPProverStateV2<'a, E> {
         }
     }
 }
+
+/// Evaluations of `\sum_b \prod_j f_j(t, b)` at `t = 0..=fs.len()`, for products of
+/// more than 3 mles. Slower than the specialised arms since every mle is lifted to the
+/// extension field first.
+fn products_sum_generic<E: ExtensionField>(
+    fs: &[&ArcMultilinearExtension<E>],
+    max_num_variables: usize,
+    round: usize,
+) -> Vec<E> {
+    let degree = fs.len();
+    let fs = fs
+        .iter()
+        .map(|f| op_mle!(|f| f.iter().map(|v| E::from(*v)).collect_vec(), |v| v))
+        .collect_vec();
+    let len = fs[0].len();
+    let mut res = vec![E::ZERO; degree + 1];
+    if len == 1 {
+        res.fill(fs.iter().map(|f| f[0]).product());
+    } else {
+        for b in (0..largest_even_below(len)).step_by(2) {
+            // f = c x + d
+            let mut evals = fs.iter().map(|f| f[b]).collect_vec();
+            let steps = fs.iter().map(|f| f[b + 1] - f[b]).collect_vec();
+            for sum in res.iter_mut() {
+                *sum += evals.iter().product::<E>();
+                evals.iter_mut().zip(&steps).for_each(|(e, c)| *e += c);
+            }
+        }
+    }
+    let num_vars_multiplicity = max_num_variables - (ceil_log2(len).max(1) + round - 1);
+    if num_vars_multiplicity > 0 {
+        res.iter_mut()
+            .for_each(|e| *e *= E::BaseField::from(1 << num_vars_multiplicity));
+    }
+    res
+}
@@ -3,7 +3,13 @@ use ff_ext::ExtensionField;
 use goldilocks::SmallField;
 use poseidon::poseidon_permutation::PoseidonPermutation;
 
-use crate::{Challenge, ForkableTranscript, Transcript};
+use crate::{Challenge, ForkableTranscript, SnapshotTranscript, Transcript};
+
+/// Internal sponge state of a [`BasicTranscript`].
+#[derive(Copy, Clone)]
+pub struct TranscriptState<E: ExtensionField> {
+    permutation: PoseidonPermutation<E::BaseField>,
+}
 
 #[derive(Copy, Clone)]
 pub struct BasicTranscript<E: ExtensionField> {
@@ -60,4 +66,47 @@ impl<E: ExtensionField> Transcript<E> for BasicTranscript<E> {
     }
 }
 
+impl<E: ExtensionField> SnapshotTranscript<E> for BasicTranscript<E> {
+    fn snapshot(&self) -> TranscriptState<E> {
+        TranscriptState {
+            permutation: self.permutation,
+        }
+    }
+
+    fn restore(&mut self, state: TranscriptState<E>) {
+        self.permutation = state.permutation;
+    }
+}
+
 impl<E: ExtensionField> ForkableTranscript<E> for BasicTranscript<E> {}
+
+#[cfg(test)]
+mod tests {
+    use goldilocks::{Goldilocks, GoldilocksExt2};
+
+    use crate::{BasicTranscript, SnapshotTranscript, Transcript};
+
+    #[test]
+    fn test_snapshot_restore() {
+        type E = GoldilocksExt2;
+        let mut transcript = BasicTranscript::<E>::new(b"test");
+        transcript.append_field_element(&Goldilocks::from(1u64));
+
+        let state = transcript.snapshot();
+        let mut continuation = transcript;
+        let expected = (0..3)
+            .map(|_| continuation.get_and_append_challenge(b"test").elements)
+            .collect::<Vec<_>>();
+
+        // speculative branch that is rolled back
+        transcript.append_field_element(&Goldilocks::from(2u64));
+        transcript.append_field_element_ext(&E::from(3u64));
+        transcript.get_and_append_challenge(b"speculative");
+        transcript.restore(state);
+
+        let challenges = (0..3)
+            .map(|_| transcript.get_and_append_challenge(b"test").elements)
+            .collect::<Vec<_>>();
+        assert_eq!(challenges, expected);
+    }
+}
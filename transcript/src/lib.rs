@@ -6,7 +6,7 @@
 pub mod basic;
 mod statistics;
 pub mod syncronized;
-pub use basic::BasicTranscript;
+pub use basic::{BasicTranscript, TranscriptState};
 pub use statistics::{BasicTranscriptWithStat, StatisticRecorder};
 pub use syncronized::TranscriptSyncronized;
 
@@ -91,6 +91,16 @@ pub trait Transcript<E: ExtensionField> {
     fn commit_rolling(&mut self);
 }
 
+/// Transcript whose state can be captured and rolled back, e.g. for speculative verification
+pub trait SnapshotTranscript<E: ExtensionField>: Transcript<E> {
+    /// Capture the current transcript state so it can be rolled back
+    /// with [`SnapshotTranscript::restore`] after a speculative branch.
+    fn snapshot(&self) -> TranscriptState<E>;
+
+    /// Roll the transcript back to a state taken with [`SnapshotTranscript::snapshot`].
+    fn restore(&mut self, state: TranscriptState<E>);
+}
+
 /// Forkable Transcript trait, enable fork method
 pub trait ForkableTranscript<E: ExtensionField>: Transcript<E> + Sized + Clone {
     /// Fork this transcript into n different threads.
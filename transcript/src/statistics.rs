@@ -1,4 +1,6 @@
-use crate::{BasicTranscript, Challenge, ForkableTranscript, Transcript};
+use crate::{
+    BasicTranscript, Challenge, ForkableTranscript, SnapshotTranscript, Transcript, TranscriptState,
+};
 use ff_ext::ExtensionField;
 use std::cell::RefCell;
 
@@ -56,4 +58,14 @@ impl<E: ExtensionField> Transcript<E> for BasicTranscriptWithStat<'_, E> {
     }
 }
 
+impl<E: ExtensionField> SnapshotTranscript<E> for BasicTranscriptWithStat<'_, E> {
+    fn snapshot(&self) -> TranscriptState<E> {
+        self.inner.snapshot()
+    }
+
+    fn restore(&mut self, state: TranscriptState<E>) {
+        self.inner.restore(state)
+    }
+}
+
 impl<E: ExtensionField> ForkableTranscript<E> for BasicTranscriptWithStat<'_, E> {}